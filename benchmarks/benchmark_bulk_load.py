@@ -0,0 +1,114 @@
+#!/usr/bin/env python3
+from __future__ import annotations
+
+import argparse
+import gc
+import random
+import statistics as stats
+from time import perf_counter as now
+
+from system_info_collector import collect_system_info, format_system_info_markdown_lite
+
+from fastquadtree._native import QuadTree as NativeQuadTree
+
+BOUNDS = (0.0, 0.0, 1000.0, 1000.0)
+CAPACITY = 64
+MAX_DEPTH = 10
+SEED = 42
+
+
+def gen_columns(n: int, rng: random.Random):
+    ids = list(range(n))
+    xs = [rng.random() * 1000.0 for _ in range(n)]
+    ys = [rng.random() * 1000.0 for _ in range(n)]
+    return ids, xs, ys
+
+
+def gen_queries(m: int, rng: random.Random):
+    qs = []
+    for _ in range(m):
+        x = rng.uniform(0, 900)
+        y = rng.uniform(0, 900)
+        qs.append((x, y, x + rng.uniform(1, 100), y + rng.uniform(1, 100)))
+    return qs
+
+
+def build_loop(ids, xs, ys) -> tuple[NativeQuadTree, float]:
+    t0 = now()
+    qt = NativeQuadTree(BOUNDS, CAPACITY, max_depth=MAX_DEPTH)
+    for id_, x, y in zip(ids, xs, ys):
+        qt.insert(id_, (x, y))
+    return qt, now() - t0
+
+
+def build_bulk(ids, xs, ys) -> tuple[NativeQuadTree, float]:
+    t0 = now()
+    qt = NativeQuadTree(BOUNDS, CAPACITY, max_depth=MAX_DEPTH)
+    qt.insert_many(ids, xs, ys)
+    return qt, now() - t0
+
+
+def median_build(build, cols, repeats: int) -> tuple[NativeQuadTree, float]:
+    times = []
+    qt = None
+    for _ in range(repeats):
+        gc.disable()
+        qt, dt = build(*cols)
+        gc.enable()
+        times.append(dt)
+    return qt, stats.median(times)  # type: ignore[return-value]
+
+
+def main():
+    ap = argparse.ArgumentParser(
+        description="Benchmark: insert_many(ids, xs, ys) vs looping insert"
+    )
+    ap.add_argument("--points", type=int, default=1_000_000)
+    ap.add_argument("--queries", type=int, default=1_000)
+    ap.add_argument("--repeats", type=int, default=3)
+    args = ap.parse_args()
+
+    print("Bulk load vs insert loop benchmark")
+    print("=" * 50)
+    print("Configuration:")
+    print(f"  Points: {args.points:,}")
+    print(f"  Queries: {args.queries:,}")
+    print(f"  Repeats: {args.repeats}")
+    print()
+
+    rng = random.Random(SEED)
+    cols = gen_columns(args.points, rng)
+    queries = gen_queries(args.queries, rng)
+
+    # Warmup
+    small = tuple(c[:10_000] for c in cols)
+    build_loop(*small)
+    build_bulk(*small)
+
+    loop_qt, t_loop = median_build(build_loop, cols, args.repeats)
+    bulk_qt, t_bulk = median_build(build_bulk, cols, args.repeats)
+
+    # Both trees must hold the same items and answer queries the same way
+    assert loop_qt.count_items() == bulk_qt.count_items() == args.points
+    for q in queries:
+        assert sorted(loop_qt.query_ids(q)) == sorted(bulk_qt.query_ids(q)), q
+
+    def fmt(x: float) -> str:
+        if x < 1:
+            return f"{x * 1e3:.1f} ms"
+        return f"{x:.3f} s"
+
+    print("Results (median of repeats)")
+    print()
+    print("| Variant | Build time | Speedup |")
+    print("|---|---:|---:|")
+    print(f"| insert loop | {fmt(t_loop)} | 1.00x |")
+    print(f"| insert_many | {fmt(t_bulk)} | {t_loop / t_bulk:.2f}x |")
+
+    info = collect_system_info()
+    print()
+    print(format_system_info_markdown_lite(info))
+
+
+if __name__ == "__main__":
+    main()
@@ -12,6 +12,7 @@ to suit different use cases:
 All implementations are backed by a high-performance Rust core for optimal speed.
 """
 
+from ._common import rect_from_center, rect_from_corners
 from ._insert_result import InsertResult
 from ._item import Item, PointItem, RectItem
 from .point_quadtree import QuadTree
@@ -38,4 +39,6 @@ __all__ = [
     "RectQuadTreeObjects",
     "Rectquadtree",
     "RectquadtreeObjects",
+    "rect_from_center",
+    "rect_from_corners",
 ]
@@ -15,6 +15,7 @@ from ._common import (
     Bounds,
     QuadTreeDType,
     SerializationError,
+    _ClosedHandle,
     _is_np_array,
     build_container,
     parse_container,
@@ -165,7 +166,7 @@ class _BaseQuadTree(Generic[G], ABC):
             )
 
         start_id = self._next_id
-        last_id = self._native.insert_many(start_id, geoms)
+        last_id = self._native.insert_many_auto(start_id, geoms)
         num = last_id - start_id + 1
 
         if num < len(geoms):
@@ -240,6 +241,7 @@ class _BaseQuadTree(Generic[G], ABC):
         """
         Empty the tree in place, preserving bounds, capacity, and max_depth.
         """
+        self._ensure_open()
         self._native = self._new_native(
             self._bounds, self._capacity, self._max_depth, self._dtype
         )
@@ -282,8 +284,36 @@ class _BaseQuadTree(Generic[G], ABC):
 
     # ---- Utilities ----
 
+    def close(self) -> None:
+        """
+        Release the native tree.
+
+        Further method calls raise RuntimeError. Calling close() again is a
+        no-op. Also called on leaving a ``with`` block.
+        """
+        if self.closed:
+            return
+        self._native = _ClosedHandle(type(self).__name__)
+        self._count = 0
+
+    @property
+    def closed(self) -> bool:
+        """True once close() has been called."""
+        return isinstance(self._native, _ClosedHandle)
+
+    def _ensure_open(self) -> None:
+        if self.closed:
+            raise RuntimeError(f"{type(self).__name__} is closed")
+
+    def __enter__(self) -> _BaseQuadTree[G]:
+        return self
+
+    def __exit__(self, *exc_info: object) -> None:
+        self.close()
+
     def __len__(self) -> int:
         """Return the number of items in the tree."""
+        self._ensure_open()
         return self._count
 
     def get_all_node_boundaries(self) -> list[Bounds]:
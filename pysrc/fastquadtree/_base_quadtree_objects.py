@@ -5,7 +5,7 @@ from __future__ import annotations
 
 import pickle
 from abc import ABC, abstractmethod
-from collections.abc import Sequence
+from collections.abc import Iterator, Sequence
 from typing import Any, Generic, TypeVar
 
 from ._common import (
@@ -19,6 +19,7 @@ from ._common import (
     Point,
     QuadTreeDType,
     SerializationError,
+    _ClosedHandle,
     _is_np_array,
     build_container,
     parse_container,
@@ -317,7 +318,7 @@ class _BaseQuadTreeObjects(Generic[G, ItemType], ABC):
             return InsertResult(count=0, start_id=start_id, end_id=start_id - 1)
 
         start_id = len(self._store._arr)
-        last_id = self._native.insert_many(start_id, geoms)
+        last_id = self._native.insert_many_auto(start_id, geoms)
         num = last_id - start_id + 1
 
         if num < len(geoms):
@@ -399,21 +400,23 @@ class _BaseQuadTreeObjects(Generic[G, ItemType], ABC):
 
     # ---- Queries ----
 
-    def query(self, rect: Bounds) -> list[ItemType]:
+    def query(self, rect: Bounds, *, strict: bool = False) -> list[ItemType]:
         """
         Return all items that intersect/contain the query rectangle.
 
         Args:
             rect: Query rectangle as (min_x, min_y, max_x, max_y).
+            strict: Raise ValueError for an inverted rect instead of
+                normalizing it.
 
         Returns:
             List of Item objects.
         """
-        return self._native.query_items(rect, self._store._arr)
+        return self._native.query_items(rect, self._store._arr, strict=strict)
 
         # return self._store.get_many_by_ids(self._native.query_ids(rect))
 
-    def query_ids(self, rect: Bounds) -> list[int]:
+    def query_ids(self, rect: Bounds, *, strict: bool = False) -> list[int]:
         """
         Return IDs of all items that intersect/contain the query rectangle.
 
@@ -421,18 +424,43 @@ class _BaseQuadTreeObjects(Generic[G, ItemType], ABC):
 
         Args:
             rect: Query rectangle as (min_x, min_y, max_x, max_y).
+            strict: Raise ValueError for an inverted rect instead of
+                normalizing it.
 
         Returns:
             List of integer IDs.
         """
-        return self._native.query_ids(rect)
+        return self._native.query_ids(rect, strict=strict)
 
-    def query_np(self, rect: Bounds) -> tuple[Any, Any]:
+    def query_objects(
+        self, rect: Bounds, *, strict: bool = False
+    ) -> list[tuple[Any, ...]]:
+        """
+        Return plain tuples with the associated object appended.
+
+        Points give (id, x, y, obj) and rects give
+        (id, min_x, min_y, max_x, max_y, obj). Skips building Item wrappers.
+
+        Args:
+            rect: Query rectangle as (min_x, min_y, max_x, max_y).
+            strict: Raise ValueError for an inverted rect instead of
+                normalizing it.
+
+        Returns:
+            List of tuples, obj is None for items inserted without one.
+        """
+        hits = self._native.query(rect, strict=strict)
+        objs = self._store.get_many_objects([hit[0] for hit in hits])
+        return [(*hit, obj) for hit, obj in zip(hits, objs)]
+
+    def query_np(self, rect: Bounds, *, strict: bool = False) -> tuple[Any, Any]:
         """
         Return all items as NumPy arrays.
 
         Args:
             rect: Query rectangle as (min_x, min_y, max_x, max_y).
+            strict: Raise ValueError for an inverted rect instead of
+                normalizing it.
 
         Returns:
             Tuple of (ids, coords) where ids is NDArray[np.int64] and coords matches tree dtype.
@@ -440,7 +468,7 @@ class _BaseQuadTreeObjects(Generic[G, ItemType], ABC):
         Raises:
             ImportError: If NumPy is not installed.
         """
-        return self._native.query_np(rect)
+        return self._native.query_np(rect, strict=strict)
 
     def nearest_neighbor(self, point: Point) -> ItemType | None:
         """
@@ -543,14 +571,24 @@ class _BaseQuadTreeObjects(Generic[G, ItemType], ABC):
             obj: The Python object to search for.
 
         Returns:
-            Number of items deleted.
+            Number of items deleted. Use `pop_by_object` for the deleted IDs.
+        """
+        return len(self.pop_by_object(obj))
+
+    def pop_by_object(self, obj: Any) -> list[int]:
+        """
+        Delete all items with the given object (by identity, not equality).
+
+        Same as `delete_by_object`, but returns the deleted IDs instead of a
+        count.
+
+        Args:
+            obj: The Python object to search for.
+
+        Returns:
+            IDs of the deleted items in ascending order, empty if none matched.
         """
-        items = self._store.by_obj_all(obj)
-        deleted_count = 0
-        for item in items:
-            if self.delete(item.id_):
-                deleted_count += 1
-        return deleted_count
+        return [it.id_ for it in self._store.by_obj_all(obj) if self.delete(it.id_)]
 
     def delete_one_by_object(self, obj: Any) -> bool:
         """
@@ -562,12 +600,27 @@ class _BaseQuadTreeObjects(Generic[G, ItemType], ABC):
             obj: The Python object to search for.
 
         Returns:
-            True if an item was deleted.
+            True if an item was deleted. Use `pop_one_by_object` for its ID.
+        """
+        return self.pop_one_by_object(obj) is not None
+
+    def pop_one_by_object(self, obj: Any) -> int | None:
+        """
+        Delete the lowest-ID item with the given object (by identity).
+
+        Same as `delete_one_by_object`, but returns the deleted ID instead of
+        a bool.
+
+        Args:
+            obj: The Python object to search for.
+
+        Returns:
+            The deleted ID, or None if no item has this object.
         """
         it = self._store.by_obj(obj)
-        if it is None:
-            return False
-        return self.delete(it.id_)
+        if it is None or not self.delete(it.id_):
+            return None
+        return it.id_
 
     def _update_geom(self, id_: int, old_geom: G, new_geom: G) -> bool:
         """
@@ -603,6 +656,7 @@ class _BaseQuadTreeObjects(Generic[G, ItemType], ABC):
 
     def clear(self) -> None:
         """Empty the tree in place, preserving bounds, capacity, and max_depth."""
+        self._ensure_open()
         self._native = self._new_native(self._bounds, self._capacity, self._max_depth)
         self._count = 0
         self._store.clear()
@@ -647,10 +701,55 @@ class _BaseQuadTreeObjects(Generic[G, ItemType], ABC):
         """Return all Item wrappers in the tree."""
         return list(self._store.items())
 
+    def iter_objects(self) -> Iterator[tuple[Any, ...]]:
+        """
+        Lazily yield every item as a plain tuple with its object, in id order.
+
+        Points give (id, x, y, obj) and rects give
+        (id, min_x, min_y, max_x, max_y, obj), matching query_objects().
+        obj is None for items inserted without one.
+
+        Raises:
+            RuntimeError: If the tree is closed. Checked on the call itself,
+                not on the first next().
+        """
+        self._ensure_open()
+        return ((id_, *it.geom, it.obj) for id_, it in self._store.items_by_id())
+
     # ---- Utilities ----
 
+    def close(self) -> None:
+        """
+        Release the native tree and every stored object reference.
+
+        Further method calls raise RuntimeError. Calling close() again is a
+        no-op. Also called on leaving a ``with`` block.
+        """
+        if self.closed:
+            return
+        self._native = _ClosedHandle(type(self).__name__)
+        self._store.clear()
+        self._store = _ClosedHandle(type(self).__name__)  # type: ignore[assignment]
+        self._count = 0
+
+    @property
+    def closed(self) -> bool:
+        """True once close() has been called."""
+        return isinstance(self._native, _ClosedHandle)
+
+    def _ensure_open(self) -> None:
+        if self.closed:
+            raise RuntimeError(f"{type(self).__name__} is closed")
+
+    def __enter__(self) -> _BaseQuadTreeObjects[G, ItemType]:
+        return self
+
+    def __exit__(self, *exc_info: object) -> None:
+        self.close()
+
     def __len__(self) -> int:
         """Return the number of items in the tree."""
+        self._ensure_open()
         return self._count
 
     def __contains__(self, geom: G) -> bool:
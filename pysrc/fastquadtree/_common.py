@@ -126,6 +126,39 @@ def validate_bounds(bounds: Any) -> Bounds:
     return tuple(numeric_vals)  # type: ignore[return-value]
 
 
+def rect_from_center(
+    center: Point, half_w: float | int, half_h: float | int
+) -> Bounds:
+    """
+    Build a rect reaching half_w and half_h either side of center.
+
+    Args:
+        center: Center point (x, y).
+        half_w: Half of the width.
+        half_h: Half of the height.
+
+    Returns:
+        Bounds tuple (min_x, min_y, max_x, max_y).
+    """
+    cx, cy = center
+    return (cx - half_w, cy - half_h, cx + half_w, cy + half_h)
+
+
+def rect_from_corners(a: Point, b: Point) -> Bounds:
+    """
+    Build a rect from two opposite corners given in any order.
+
+    Args:
+        a: One corner (x, y).
+        b: The opposite corner (x, y).
+
+    Returns:
+        Bounds tuple (min_x, min_y, max_x, max_y) with min <= max on both axes.
+    """
+    (ax, ay), (bx, by) = a, b
+    return (min(ax, bx), min(ay, by), max(ax, bx), max(ay, by))
+
+
 def validate_np_dtype(geoms: Any, expected_dtype: QuadTreeDType) -> None:
     """
     Validate that a NumPy array's dtype matches the expected dtype.
@@ -170,6 +203,26 @@ def validate_preallocation_limit_bucket(
         )
 
 
+class _ClosedHandle:
+    """
+    Stand-in for the native tree and object store after close().
+
+    Any attribute access or iteration raises RuntimeError, so every method
+    that reaches the engine fails with a clear message.
+    """
+
+    __slots__ = ("_owner",)
+
+    def __init__(self, owner: str) -> None:
+        self._owner = owner
+
+    def __getattr__(self, name: str) -> Any:
+        raise RuntimeError(f"{self._owner} is closed")
+
+    def __iter__(self) -> Any:
+        raise RuntimeError(f"{self._owner} is closed")
+
+
 # ---------------------------
 # Serialization helpers
 # ---------------------------
@@ -1,6 +1,7 @@
 # _bimap.py
 from __future__ import annotations
 
+import sys
 from collections.abc import Iterable, Iterator, Sequence
 from operator import itemgetter
 from typing import Any, Generic, TypeVar
@@ -9,6 +10,25 @@ from ._item import Item  # base class for PointItem and RectItem
 
 TItem = TypeVar("TItem", bound=Item)
 
+# Native ids are u64, so no store id may exceed this
+MAX_ID = 2**64 - 1
+
+
+class StoreError(Exception):
+    """Base class for ObjStore id errors. Each subclass is also a builtin."""
+
+
+class IdOutOfBoundsError(StoreError, IndexError):
+    """The id is negative."""
+
+
+class IdDoesNotFitError(StoreError, OverflowError):
+    """The id is larger than a native u64 id can hold."""
+
+
+class OutOfOrderIdError(StoreError, ValueError):
+    """The id skips past the end of the dense id space."""
+
 
 class ObjStore(Generic[TItem]):
     """
@@ -75,6 +95,37 @@ class ObjStore(Generic[TItem]):
             items.append(item_factory(item.id_, item.geom, item.obj))
         return cls(items)
 
+    def export_layout(self) -> list[tuple[int, Any]]:
+        """
+        Return live (id, geom) pairs in id order, without the Python objects.
+
+        Pair with import_layout() to checkpoint id assignments and geometry
+        when the objects are rebuilt separately.
+        """
+        return [(i, it.geom) for i, it in self.items_by_id()]
+
+    @classmethod
+    def import_layout(
+        cls, layout: Iterable[tuple[int, Any]], item_factory: Any
+    ) -> ObjStore[TItem]:
+        """
+        Rebuild a store from export_layout() output, with every obj set to None.
+
+        Holes below the highest id go back on the free-list, so alloc_id()
+        hands out the same ids in the same order on every import. Freed ids
+        above the highest live id are not kept; alloc_id() appends from
+        highest id + 1.
+
+        Args:
+            layout: (id, geom) pairs, ids unique and in any order.
+            item_factory: A callable that takes (id, geom, obj) and returns an Item.
+
+        Returns:
+            An ObjStore instance matching the layout.
+        """
+        items = [item_factory(id_, geom, None) for id_, geom in sorted(layout)]
+        return cls(items)
+
     # -------- core --------
 
     def add(self, item: TItem, handle_out_of_order: bool = False) -> None:
@@ -87,14 +138,25 @@ class ObjStore(Generic[TItem]):
             handle_out_of_order: If True, fill gaps with None instead of raising.
                 Used during deserialization to preserve original IDs from serialized data.
                 Gaps are NOT added to the free-list (permanent holes until post-processing).
+
+        Raises:
+            IdOutOfBoundsError: If the id is negative (an IndexError).
+            IdDoesNotFitError: If the id exceeds MAX_ID (an OverflowError).
+            OutOfOrderIdError: If the id skips past the end and
+                handle_out_of_order is False (a ValueError).
         """
         id_ = item.id_
         obj = item.obj
 
+        if id_ < 0:
+            raise IdOutOfBoundsError(f"ObjStore id must be non-negative, got {id_}")
+        if id_ > MAX_ID:
+            raise IdDoesNotFitError(f"ObjStore id {id_} does not fit in a u64")
+
         # ids must be dense and assigned by the caller
         if id_ > len(self._arr):
             if not handle_out_of_order:
-                raise AssertionError(
+                raise OutOfOrderIdError(
                     "ObjStore.add received an out-of-order id, use alloc_id() to get the next available id"
                 )
             # fill holes with None
@@ -180,8 +242,17 @@ class ObjStore(Generic[TItem]):
         """
         Get a reusable dense id. Uses free-list else appends at the tail.
         Build your Item with this id then call add(item).
+
+        A freed id that add() has since filled directly is still on the
+        free-list; it is skipped here, so an occupied id is never returned.
         """
-        return self._free.pop() if self._free else len(self._arr)
+        free = self._free
+        arr = self._arr
+        while free:
+            id_ = free.pop()
+            if id_ < len(arr) and arr[id_] is None:
+                return id_
+        return len(arr)
 
     # -------- fast batch gathers --------
 
@@ -219,6 +290,53 @@ class ObjStore(Generic[TItem]):
     def __len__(self) -> int:
         return self._len
 
+    def memory_bytes(self) -> int:
+        """
+        Approximate bytes held by the store's own containers: the items and
+        objects arrays, the free-list, and the reverse map with its id sets.
+        The stored Items and user objects are not counted.
+        """
+        size = sys.getsizeof
+        total = size(self._arr) + size(self._objs) + size(self._free)
+        total += size(self._obj_to_ids)
+        total += sum(size(ids) for ids in self._obj_to_ids.values())
+        return total
+
+    def dense_len(self) -> int:
+        """Size of the id space: live ids plus holes, up to the last slot."""
+        return len(self._arr)
+
+    def hole_count(self) -> int:
+        """Number of empty slots in the id space, dense_len() - len()."""
+        return len(self._arr) - self._len
+
+    def free_ids(self) -> list[int]:
+        """
+        Sorted copy of the ids alloc_id() can hand out before appending.
+
+        Stale free-list entries, ones refilled by add() or cut off by
+        shrink_to_fit(), are left out, so this matches the holes exactly.
+        """
+        arr = self._arr
+        return sorted({i for i in self._free if i < len(arr) and arr[i] is None})
+
+    def shrink_to_fit(self) -> None:
+        """
+        Trim trailing holes so the id space ends at the highest live id.
+
+        Freed ids past the new end leave the free-list, so alloc_id() appends
+        from there instead. The reverse map is rebuilt to release its spare
+        slots. After everything is popped, dense_len() is 0.
+        """
+        n = len(self._arr)
+        while n and self._arr[n - 1] is None:
+            n -= 1
+        if n < len(self._arr):
+            del self._arr[n:]
+            del self._objs[n:]
+            self._free = [i for i in self._free if i < n]
+        self._obj_to_ids = dict(self._obj_to_ids)
+
     def clear(self) -> None:
         self._arr.clear()
         self._objs.clear()
@@ -83,12 +83,17 @@ class QuadTree(_BaseQuadTree[Point]):
 
     # ---- Queries ----
 
-    def query(self, rect: Bounds) -> list[_IdCoord]:
+    def query(self, rect: Bounds, *, strict: bool = False) -> list[_IdCoord]:
         """
         Find all points within a rectangular region.
 
+        A rect with min and max swapped, such as (max_x, max_y, min_x, min_y),
+        is normalized rather than silently matching nothing.
+
         Args:
             rect: Query rectangle as (min_x, min_y, max_x, max_y).
+            strict: Raise ValueError for an inverted rect instead of
+                normalizing it.
 
         Returns:
             List of (id, x, y) tuples for points inside the rectangle.
@@ -100,14 +105,16 @@ class QuadTree(_BaseQuadTree[Point]):
                 print(f"Point {id_} at ({x}, {y})")
             ```
         """
-        return self._native.query(rect)
+        return self._native.query(rect, strict=strict)
 
-    def query_np(self, rect: Bounds) -> tuple[Any, Any]:
+    def query_np(self, rect: Bounds, *, strict: bool = False) -> tuple[Any, Any]:
         """
         Find all points within a rectangular region, returning NumPy arrays.
 
         Args:
             rect: Query rectangle as (min_x, min_y, max_x, max_y).
+            strict: Raise ValueError for an inverted rect instead of
+                normalizing it.
 
         Returns:
             Tuple of (ids, coords) where:
@@ -124,7 +131,7 @@ class QuadTree(_BaseQuadTree[Point]):
                 print(f"Point {id_} at ({x}, {y})")
             ```
         """
-        return self._native.query_np(rect)
+        return self._native.query_np(rect, strict=strict)
 
     def nearest_neighbor(self, point: Point) -> _IdCoord | None:
         """
@@ -338,12 +345,13 @@ class QuadTree(_BaseQuadTree[Point]):
         """
         Iterate over all (id, x, y) tuples in the tree.
 
+        Iterates over a snapshot taken when iteration starts, so inserting or
+        deleting inside the loop is safe and does not change what is yielded.
+
         Example:
             ```python
             for id_, x, y in qt:
                 print(f"ID {id_} at ({x}, {y})")
             ```
         """
-        # Query the entire bounds to get all items
-        all_items = self._native.query(self._bounds)
-        return iter(all_items)
+        return iter(self._native.all_items())
@@ -88,12 +88,14 @@ class RectQuadTree(_BaseQuadTree[Bounds]):
 
     # ---- Queries ----
 
-    def query(self, rect: Bounds) -> list[_IdRect]:
+    def query(self, rect: Bounds, *, strict: bool = False) -> list[_IdRect]:
         """
         Find all rectangles that intersect with a query rectangle.
 
         Args:
             rect: Query rectangle as (min_x, min_y, max_x, max_y).
+            strict: Raise ValueError for an inverted rect instead of
+                normalizing it.
 
         Returns:
             List of (id, min_x, min_y, max_x, max_y) tuples for intersecting rectangles.
@@ -105,14 +107,16 @@ class RectQuadTree(_BaseQuadTree[Bounds]):
                 print(f"Rect {id_} at ({min_x}, {min_y}, {max_x}, {max_y})")
             ```
         """
-        return self._native.query(rect)
+        return self._native.query(rect, strict=strict)
 
-    def query_np(self, rect: Bounds) -> tuple[Any, Any]:
+    def query_np(self, rect: Bounds, *, strict: bool = False) -> tuple[Any, Any]:
         """
         Find intersecting rectangles, returning NumPy arrays.
 
         Args:
             rect: Query rectangle as (min_x, min_y, max_x, max_y).
+            strict: Raise ValueError for an inverted rect instead of
+                normalizing it.
 
         Returns:
             Tuple of (ids, coords) where:
@@ -129,7 +133,7 @@ class RectQuadTree(_BaseQuadTree[Bounds]):
                 print(f"Rect {id_} at ({min_x}, {min_y}, {max_x}, {max_y})")
             ```
         """
-        return self._native.query_np(rect)
+        return self._native.query_np(rect, strict=strict)
 
     def nearest_neighbor(self, point: Point) -> _IdRect | None:
         """
@@ -13,6 +13,10 @@ impl<T> Coord for T where
 {}
 
 // Generic mid function for all Coord types
+// Stepping half the span from `a` never forms a + b, so large or negative
+// bounds don't overflow or lose more than half an ulp. Integer spans must
+// still fit in T. Routing and child rects both come from this one function,
+// so they always agree on which side of the split a point falls.
 #[inline(always)]
 pub fn mid<T: Coord>(a: T, b: T) -> T {
     // a + (b - a) / 2
@@ -25,6 +29,50 @@ pub struct Point<T: Coord> {
     pub y: T,
 }
 
+impl<T: Coord> Point<T> {
+    // False if either coordinate is NaN or infinite. Always true for integers.
+    // v * 0 is zero for every finite value and NaN for NaN and the
+    // infinities, so this needs no cast to f64.
+    #[inline(always)]
+    pub fn is_finite(&self) -> bool {
+        let finite = |v: T| v * T::zero() == T::zero();
+        finite(self.x) && finite(self.y)
+    }
+
+    #[inline]
+    pub fn translate(&self, dx: T, dy: T) -> Point<T> {
+        Point { x: self.x + dx, y: self.y + dy }
+    }
+}
+
+impl<T: Coord> Add for Point<T> {
+    type Output = Point<T>;
+
+    #[inline]
+    fn add(self, rhs: Point<T>) -> Point<T> {
+        Point { x: self.x + rhs.x, y: self.y + rhs.y }
+    }
+}
+
+impl<T: Coord> Sub for Point<T> {
+    type Output = Point<T>;
+
+    #[inline]
+    fn sub(self, rhs: Point<T>) -> Point<T> {
+        Point { x: self.x - rhs.x, y: self.y - rhs.y }
+    }
+}
+
+// Scalar multiply, scaling both coordinates
+impl<T: Coord> Mul<T> for Point<T> {
+    type Output = Point<T>;
+
+    #[inline]
+    fn mul(self, k: T) -> Point<T> {
+        Point { x: self.x * k, y: self.y * k }
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Default, SchemaWrite, SchemaRead)]
 pub struct Rect<T: Coord> {
     pub min_x: T,
@@ -34,6 +82,25 @@ pub struct Rect<T: Coord> {
 }
 
 impl<T: Coord> Rect<T> {
+    // Rect reaching `half_w` / `half_h` either side of `center`
+    #[inline]
+    pub fn from_center(center: Point<T>, half_w: T, half_h: T) -> Rect<T> {
+        Rect {
+            min_x: center.x - half_w,
+            min_y: center.y - half_h,
+            max_x: center.x + half_w,
+            max_y: center.y + half_h,
+        }
+    }
+
+    // Rect spanned by two opposite corners given in any order
+    #[inline]
+    pub fn from_corners(a: Point<T>, b: Point<T>) -> Rect<T> {
+        let lo = |a: T, b: T| if b < a { b } else { a };
+        let hi = |a: T, b: T| if b > a { b } else { a };
+        Rect { min_x: lo(a.x, b.x), min_y: lo(a.y, b.y), max_x: hi(a.x, b.x), max_y: hi(a.y, b.y) }
+    }
+
     pub fn contains(&self, point: &Point<T>) -> bool {
         return point.x >= self.min_x && point.x < self.max_x && point.y >= self.min_y && point.y < self.max_y;
     }
@@ -42,6 +109,81 @@ impl<T: Coord> Rect<T> {
     pub fn intersects(&self, other: &Rect<T>) -> bool {
         return self.min_x < other.max_x && self.max_x > other.min_x && self.min_y < other.max_y && self.max_y > other.min_y
     }
+
+    // max_x - min_x; negative for an inverted rect
+    pub fn width(&self) -> T {
+        self.max_x - self.min_x
+    }
+
+    // max_y - min_y; negative for an inverted rect
+    pub fn height(&self) -> T {
+        self.max_y - self.min_y
+    }
+
+    // Zero rather than negative when either side is inverted
+    pub fn area(&self) -> T {
+        let (w, h) = (self.width(), self.height());
+        if w <= T::zero() || h <= T::zero() {
+            return T::zero();
+        }
+        w * h
+    }
+
+    // Midpoint, using the same rounding as child splits
+    pub fn center(&self) -> Point<T> {
+        Point { x: mid(self.min_x, self.max_x), y: mid(self.min_y, self.max_y) }
+    }
+
+    // Smallest rect covering both
+    pub fn union(&self, other: &Rect<T>) -> Rect<T> {
+        let lo = |a: T, b: T| if b < a { b } else { a };
+        let hi = |a: T, b: T| if b > a { b } else { a };
+        Rect {
+            min_x: lo(self.min_x, other.min_x),
+            min_y: lo(self.min_y, other.min_y),
+            max_x: hi(self.max_x, other.max_x),
+            max_y: hi(self.max_y, other.max_y),
+        }
+    }
+
+    #[inline]
+    pub fn translate(&self, dx: T, dy: T) -> Rect<T> {
+        Rect {
+            min_x: self.min_x + dx,
+            min_y: self.min_y + dy,
+            max_x: self.max_x + dx,
+            max_y: self.max_y + dy,
+        }
+    }
+
+    // Grows (factor > 1) or shrinks (factor < 1) each side about `center`.
+    // Integer coords round the same way `center` does.
+    #[inline]
+    pub fn scale_from_center(&self, factor: T) -> Rect<T> {
+        let c = self.center();
+        Rect {
+            min_x: c.x - (c.x - self.min_x) * factor,
+            min_y: c.y - (c.y - self.min_y) * factor,
+            max_x: c.x + (self.max_x - c.x) * factor,
+            max_y: c.y + (self.max_y - c.y) * factor,
+        }
+    }
+
+    // Overlap of both, or None when `intersects` is false. Rects that only
+    // share an edge or corner don't intersect, so they yield None too.
+    pub fn intersection(&self, other: &Rect<T>) -> Option<Rect<T>> {
+        if !self.intersects(other) {
+            return None;
+        }
+        let lo = |a: T, b: T| if b < a { b } else { a };
+        let hi = |a: T, b: T| if b > a { b } else { a };
+        Some(Rect {
+            min_x: hi(self.min_x, other.min_x),
+            min_y: hi(self.min_y, other.min_y),
+            max_x: lo(self.max_x, other.max_x),
+            max_y: lo(self.max_y, other.max_y),
+        })
+    }
 }
 
 pub fn dist_sq_point_to_rect<T: Coord>(p: &Point<T>, r: &Rect<T>) -> T {
@@ -64,8 +206,102 @@ pub fn dist_sq_point_to_rect<T: Coord>(p: &Point<T>, r: &Rect<T>) -> T {
     dx * dx + dy * dy
 }
 
+// Squared gap between the closest points of two rects, zero if they touch
+// or overlap
+pub fn dist_sq_rect_to_rect<T: Coord>(a: &Rect<T>, b: &Rect<T>) -> T {
+    let dx = if b.min_x > a.max_x {
+        b.min_x - a.max_x
+    } else if a.min_x > b.max_x {
+        a.min_x - b.max_x
+    } else {
+        T::zero()
+    };
+
+    let dy = if b.min_y > a.max_y {
+        b.min_y - a.max_y
+    } else if a.min_y > b.max_y {
+        a.min_y - b.max_y
+    } else {
+        T::zero()
+    };
+
+    dx * dx + dy * dy
+}
+
 pub fn dist_sq_points<T: Coord>(a: &Point<T>, b: &Point<T>) -> T {
     let dx = a.x - b.x;
     let dy = a.y - b.y;
     dx * dx + dy * dy
 }
+
+#[inline(always)]
+fn abs_diff<T: Coord>(a: T, b: T) -> T {
+    if a > b { a - b } else { b - a }
+}
+
+// Per-axis gap from `p` to `r`, zero on an axis where p is within the span
+#[inline(always)]
+fn axis_gaps_point_to_rect<T: Coord>(p: &Point<T>, r: &Rect<T>) -> (T, T) {
+    let dx = if p.x < r.min_x { r.min_x - p.x } else if p.x > r.max_x { p.x - r.max_x } else { T::zero() };
+    let dy = if p.y < r.min_y { r.min_y - p.y } else if p.y > r.max_y { p.y - r.max_y } else { T::zero() };
+    (dx, dy)
+}
+
+pub fn manhattan_dist_points<T: Coord>(a: &Point<T>, b: &Point<T>) -> T {
+    abs_diff(a.x, b.x) + abs_diff(a.y, b.y)
+}
+
+pub fn chebyshev_dist_points<T: Coord>(a: &Point<T>, b: &Point<T>) -> T {
+    let (dx, dy) = (abs_diff(a.x, b.x), abs_diff(a.y, b.y));
+    if dx > dy { dx } else { dy }
+}
+
+pub fn manhattan_dist_point_to_rect<T: Coord>(p: &Point<T>, r: &Rect<T>) -> T {
+    let (dx, dy) = axis_gaps_point_to_rect(p, r);
+    dx + dy
+}
+
+pub fn chebyshev_dist_point_to_rect<T: Coord>(p: &Point<T>, r: &Rect<T>) -> T {
+    let (dx, dy) = axis_gaps_point_to_rect(p, r);
+    if dx > dy { dx } else { dy }
+}
+
+// Distance metric for metric-aware queries.
+//
+// The methods return comparison keys rather than distances: Euclidean keys
+// are squared so no sqrt is needed, the others are the distance itself.
+// Compare keys only against other keys of the same metric.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum Metric {
+    #[default]
+    Euclidean,
+    Manhattan,
+    Chebyshev,
+}
+
+impl Metric {
+    pub fn key_points<T: Coord>(self, a: &Point<T>, b: &Point<T>) -> T {
+        match self {
+            Metric::Euclidean => dist_sq_points(a, b),
+            Metric::Manhattan => manhattan_dist_points(a, b),
+            Metric::Chebyshev => chebyshev_dist_points(a, b),
+        }
+    }
+
+    // Key of the closest point of `r` to `p`, zero if p is inside
+    pub fn key_point_to_rect<T: Coord>(self, p: &Point<T>, r: &Rect<T>) -> T {
+        match self {
+            Metric::Euclidean => dist_sq_point_to_rect(p, r),
+            Metric::Manhattan => manhattan_dist_point_to_rect(p, r),
+            Metric::Chebyshev => chebyshev_dist_point_to_rect(p, r),
+        }
+    }
+
+    // Key for a plain distance, e.g. a query radius
+    pub fn key_for_distance<T: Coord>(self, d: T) -> T {
+        match self {
+            Metric::Euclidean => d * d,
+            Metric::Manhattan | Metric::Chebyshev => d,
+        }
+    }
+}
@@ -1,23 +1,35 @@
 pub mod geom;
 pub mod quadtree;
+pub mod quadtree_map;
 pub mod rect_quadtree;
 pub mod serialization;
-
-pub use crate::geom::{dist_sq_point_to_rect, dist_sq_points, mid, Coord, Point, Rect};
-pub use crate::quadtree::{Item, QuadTree};
+pub mod shared_quadtree;
+
+pub use crate::geom::{
+    dist_sq_point_to_rect, dist_sq_points, dist_sq_rect_to_rect, mid, Coord, Metric, Point, Rect,
+};
+pub use crate::quadtree::{
+    CircleCursor, ConfigError, DeleteOutcome, InsertError, Item, NearestCursor, NearestIter,
+    NodeInfo, QuadTree, QuadTreeBuilder, QuadTreeConfig, QuadTreeStats, QueryScratch, RectCursor,
+    ReduceOp, SplitStrategy,
+};
+pub use crate::quadtree_map::QuadTreeMap;
 pub use crate::rect_quadtree::{RectItem, RectQuadTree};
 pub use crate::serialization::SerializationError;
+pub use crate::shared_quadtree::SharedQuadTree;
 
 use numpy::PyReadonlyArray2;
 use numpy::PyArray1;
 use numpy::PyArray2;
 use numpy::PyArrayMethods;
-use pyo3::exceptions::PyValueError;
+use pyo3::exceptions::{PyRuntimeError, PyTypeError, PyValueError};
 use pyo3::prelude::*;
-use pyo3::types::{PyBytes, PyList, PyModule, PyTuple};
+use pyo3::types::{PyBytes, PyDict, PyList, PyModule, PyTuple};
 use pyo3::PyResult;
 use pyo3::ffi;
-use std::any::TypeId;
+use std::any::{Any, TypeId};
+use std::panic::{self, AssertUnwindSafe};
+use std::collections::{HashMap, HashSet};
 
 fn item_to_tuple<T: Coord + Copy>(it: Item<T>) -> (u64, T, T) {
     (it.id, it.point.x, it.point.y)
@@ -27,6 +39,44 @@ fn rect_to_tuple<T: Coord + Copy>(r: Rect<T>) -> (T, T, T, T) {
     (r.min_x, r.min_y, r.max_x, r.max_y)
 }
 
+// Query rects arrive as (min_x, min_y, max_x, max_y). Swapped corners are
+// put back in order unless `strict`, in which case they raise ValueError.
+// Every method that takes a query rect, on both tree kinds, goes through
+// here and exposes `strict`.
+fn query_rect<T: Coord>(rect: (T, T, T, T), strict: bool) -> PyResult<Rect<T>> {
+    let (min_x, min_y, max_x, max_y) = rect;
+    if min_x > max_x || min_y > max_y {
+        if strict {
+            return Err(PyValueError::new_err(
+                "query rect is inverted; expected (min_x, min_y, max_x, max_y)",
+            ));
+        }
+        return Ok(Rect::from_corners(Point { x: min_x, y: min_y }, Point { x: max_x, y: max_y }));
+    }
+    Ok(Rect { min_x, min_y, max_x, max_y })
+}
+
+fn parse_metric(name: &str) -> PyResult<Metric> {
+    match name {
+        "euclidean" => Ok(Metric::Euclidean),
+        "manhattan" => Ok(Metric::Manhattan),
+        "chebyshev" => Ok(Metric::Chebyshev),
+        _ => Err(PyValueError::new_err(format!(
+            "unknown metric {name:?}; expected 'euclidean', 'manhattan' or 'chebyshev'"
+        ))),
+    }
+}
+
+fn parse_split_strategy(name: &str) -> PyResult<SplitStrategy> {
+    match name {
+        "quadrant" => Ok(SplitStrategy::Quadrant),
+        "longest_axis" => Ok(SplitStrategy::LongestAxis),
+        _ => Err(PyValueError::new_err(format!(
+            "unknown split_strategy {name:?}; expected 'quadrant' or 'longest_axis'"
+        ))),
+    }
+}
+
 fn default_max_depth_for<T: 'static>() -> usize {
     // Caps aligned with meaningful resolution per dtype.
     // f32: 24 mantissa bits -> deeper splits stop helping.
@@ -45,35 +95,191 @@ fn default_max_depth_for<T: 'static>() -> usize {
     }
 }
 
+// Error policy at the Python boundary: failures are returned as PyErr, never
+// unwrapped. Raw FFI allocations are checked and wrapped in a Bound right away
+// so an early return releases them. Every method that touches the tree,
+// getters included, goes through `guarded` or `detach_guarded`, which turn a
+// panic (a bug in this crate) into RuntimeError. The release profile unwinds
+// for this; under panic = "abort" nothing could catch it and the interpreter
+// would die.
+fn panic_to_err(payload: Box<dyn Any + Send>) -> PyErr {
+    let msg = payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic");
+    PyRuntimeError::new_err(format!("fastquadtree internal error: {msg}"))
+}
+
+fn guarded<R>(f: impl FnOnce() -> R) -> PyResult<R> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(panic_to_err)
+}
+
+// `guarded` with the GIL released around `f`
+fn detach_guarded<R: Send>(py: Python<'_>, f: impl FnOnce() -> R + Send) -> PyResult<R> {
+    py.detach(|| panic::catch_unwind(AssertUnwindSafe(f))).map_err(panic_to_err)
+}
 
 // Reusable core for point QuadTrees
 macro_rules! define_point_quadtree_pyclass {
-    ($t:ty, $rs_name:ident, $py_name:literal) => {
+    (
+        $t:ty, $rs_name:ident, $py_name:literal,
+        $iter_name:ident, $iter_py_name:literal,
+        $chunk_iter_name:ident, $chunk_iter_py_name:literal,
+        $nearest_iter_name:ident, $nearest_iter_py_name:literal
+    ) => {
         #[pyclass(name = $py_name)]
         pub struct $rs_name {
             inner: QuadTree<$t>,
         }
 
+        /// Lazy iterator over a live tree. Mutating the tree while iterating
+        /// may skip or repeat items.
+        #[pyclass(name = $iter_py_name)]
+        pub struct $iter_name {
+            tree: Py<$rs_name>,
+            cursor: CircleCursor<$t>,
+        }
+
+        #[pymethods]
+        impl $iter_name {
+            fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+                slf
+            }
+
+            fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<(u64, $t, $t)>> {
+                let tree = self.tree.try_borrow(py)?;
+                Ok(guarded(|| self.cursor.next_item(&tree.inner))?.map(item_to_tuple))
+            }
+
+            pub fn nodes_visited(&self) -> usize {
+                self.cursor.nodes_visited()
+            }
+        }
+
+        /// Lazy iterator of lists of (id, x, y) in a rect, at most chunk_size
+        /// per list. Mutating the tree while iterating may skip or repeat items.
+        #[pyclass(name = $chunk_iter_py_name)]
+        pub struct $chunk_iter_name {
+            tree: Py<$rs_name>,
+            cursor: RectCursor<$t>,
+            chunk_size: usize,
+        }
+
+        /// Lazy iterator of (id, x, y) nearest first. Mutating the tree while
+        /// iterating may skip or repeat items.
+        #[pyclass(name = $nearest_iter_py_name)]
+        pub struct $nearest_iter_name {
+            tree: Py<$rs_name>,
+            cursor: NearestCursor<$t>,
+        }
+
+        #[pymethods]
+        impl $nearest_iter_name {
+            fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+                slf
+            }
+
+            fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<(u64, $t, $t)>> {
+                let tree = self.tree.try_borrow(py)?;
+                Ok(guarded(|| self.cursor.next_item(&tree.inner))?.map(item_to_tuple))
+            }
+        }
+
+        #[pymethods]
+        impl $chunk_iter_name {
+            fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+                slf
+            }
+
+            fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<Vec<(u64, $t, $t)>>> {
+                let tree = self.tree.try_borrow(py)?;
+                let mut chunk = Vec::with_capacity(self.chunk_size.min(4096));
+                if guarded(|| self.cursor.next_chunk(&tree.inner, self.chunk_size, &mut chunk))? == 0 {
+                    return Ok(None);
+                }
+                Ok(Some(chunk.into_iter().map(item_to_tuple).collect()))
+            }
+        }
+
         #[pymethods]
         impl $rs_name {
+            /// With expandable=True, inserting outside bounds grows the tree
+            /// instead of failing. Passing split_threshold or merge_threshold
+            /// decouples splitting and merging from capacity; the other one
+            /// defaults to capacity. Raises ValueError unless
+            /// split_threshold >= capacity >= merge_threshold,
+            /// or if bounds has zero or negative width or height.
             #[new]
-            #[pyo3(signature = (bounds, capacity, max_depth=None))]
-            pub fn new(bounds: ($t, $t, $t, $t), capacity: usize, max_depth: Option<usize>) -> Self {
+            #[pyo3(signature = (bounds, capacity, max_depth=None, expandable=false, *, split_threshold=None, merge_threshold=None, split_strategy="quadrant"))]
+            pub fn new(
+                bounds: ($t, $t, $t, $t),
+                capacity: usize,
+                max_depth: Option<usize>,
+                expandable: bool,
+                split_threshold: Option<usize>,
+                merge_threshold: Option<usize>,
+                split_strategy: &str,
+            ) -> PyResult<Self> {
                 let (min_x, min_y, max_x, max_y) = bounds;
-                let rect = Rect { min_x, min_y, max_x, max_y };
-                let inner = match max_depth {
-                    Some(d) => QuadTree::new(rect, capacity, d),
-                    None => QuadTree::new(
-                        rect,
-                        capacity,
-                        default_max_depth_for::<$t>(),
-                    ),
-                };
-                Self { inner }
+                let mut builder = QuadTree::builder(Rect { min_x, min_y, max_x, max_y })
+                    .capacity(capacity)
+                    .max_depth(max_depth.unwrap_or_else(default_max_depth_for::<$t>))
+                    .expandable(expandable)
+                    .split_strategy(parse_split_strategy(split_strategy)?);
+                if let Some(split) = split_threshold {
+                    builder = builder.split_threshold(split);
+                }
+                if let Some(merge) = merge_threshold {
+                    builder = builder.merge_threshold(merge);
+                }
+                let inner = guarded(|| builder.build())?.map_err(|e| PyValueError::new_err(e.to_string()))?;
+                Ok(Self { inner })
+            }
+
+            /// "quadrant" or "longest_axis", as given at construction
+            #[getter(split_strategy)]
+            fn split_strategy_prop(&self) -> PyResult<&'static str> {
+                guarded(|| match self.inner.split_strategy() {
+                    SplitStrategy::Quadrant => "quadrant",
+                    SplitStrategy::LongestAxis => "longest_axis",
+                })
+            }
+
+            /// Returns (capacity, split_threshold, max_depth, merge_threshold)
+            pub fn get_config(&self) -> PyResult<(usize, usize, usize, usize)> {
+                let c = guarded(|| self.inner.config())?;
+                Ok((c.capacity, c.split_threshold, c.max_depth, c.merge_threshold))
+            }
+
+            pub fn is_expandable(&self) -> PyResult<bool> {
+                guarded(|| self.inner.is_expandable())
+            }
+
+            /// Current root bounds as (min_x, min_y, max_x, max_y)
+            pub fn get_bounds(&self) -> PyResult<($t, $t, $t, $t)> {
+                self.bounds_prop()
+            }
+
+            #[getter(capacity)]
+            pub fn capacity_prop(&self) -> PyResult<usize> {
+                guarded(|| self.inner.capacity())
+            }
+
+            #[getter(max_depth)]
+            pub fn max_depth_prop(&self) -> PyResult<usize> {
+                guarded(|| self.inner.max_depth())
+            }
+
+            /// Current root bounds as (min_x, min_y, max_x, max_y)
+            #[getter(bounds)]
+            pub fn bounds_prop(&self) -> PyResult<($t, $t, $t, $t)> {
+                let b = guarded(|| self.inner.bounds())?;
+                Ok((b.min_x, b.min_y, b.max_x, b.max_y))
             }
 
             pub fn to_bytes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
-                let buf = self.inner.to_bytes().map_err(|e| {
+                let buf = guarded(|| self.inner.to_bytes())?.map_err(|e| {
                     PyErr::new::<PyValueError, _>(format!("serialize failed: {e}"))
                 })?;
                 Ok(PyBytes::new(py, &buf))
@@ -86,31 +292,137 @@ macro_rules! define_point_quadtree_pyclass {
                 preallocation_limit_bytes: Option<usize>,
                 disable_preallocation_limit: bool,
             ) -> PyResult<Self> {
-                let inner = crate::serialization::decode_native_with_runtime_preallocation_limit::<QuadTree<$t>>(
+                let inner = guarded(|| crate::serialization::decode_native_with_runtime_preallocation_limit::<QuadTree<$t>>(
                     bytes.as_bytes(),
                     crate::serialization::NATIVE_KIND_POINT,
                     preallocation_limit_bytes,
                     disable_preallocation_limit,
-                ).map_err(|e| {
+                ))?.map_err(|e| {
                     PyErr::new::<PyValueError, _>(format!("deserialize failed: {e}"))
                 })?;
                 Ok(Self { inner })
             }
 
-            pub fn insert(&mut self, id: u64, xy: ($t, $t)) -> bool {
+            /// Independent deep copy of the tree; edits to either side don't
+            /// show up in the other.
+            pub fn copy(&self, py: Python<'_>) -> PyResult<Self> {
+                Ok(Self { inner: detach_guarded(py, || self.inner.clone())? })
+            }
+
+            /// Test hook that panics inside a guarded call, with or without the GIL
+            /// held. Only built with the failpoints feature.
+            #[cfg(feature = "failpoints")]
+            fn _failpoint_panic(&self, py: Python<'_>, detached: bool) -> PyResult<()> {
+                if detached {
+                    detach_guarded(py, || panic!("failpoint"))
+                } else {
+                    guarded(|| panic!("failpoint"))
+                }
+            }
+
+            fn __copy__(&self, py: Python<'_>) -> PyResult<Self> {
+                self.copy(py)
+            }
+
+            fn __deepcopy__(&self, py: Python<'_>, _memo: &Bound<'_, PyAny>) -> PyResult<Self> {
+                self.copy(py)
+            }
+
+            /// Build a tree whose bounds tightly fit the given points, grown by padding.
+            /// Raises ValueError for negative or non-finite padding, NaN or inf
+            /// coordinates, or points too far apart for the dtype.
+            #[staticmethod]
+            #[pyo3(signature = (ids, xs, ys, capacity, padding=None, max_depth=None))]
+            pub fn from_points_autofit(
+                ids: Vec<u64>,
+                xs: Vec<$t>,
+                ys: Vec<$t>,
+                capacity: usize,
+                padding: Option<$t>,
+                max_depth: Option<usize>,
+            ) -> PyResult<Self> {
+                if ids.len() != xs.len() || ids.len() != ys.len() {
+                    return Err(PyValueError::new_err("ids, xs, and ys must have the same length"));
+                }
+                let items: Vec<Item<$t>> = ids
+                    .into_iter()
+                    .zip(xs.into_iter().zip(ys))
+                    .map(|(id, (x, y))| Item { id, point: Point { x, y } })
+                    .collect();
+                let inner = guarded(|| QuadTree::from_points_autofit(
+                    &items,
+                    capacity,
+                    max_depth.unwrap_or_else(default_max_depth_for::<$t>),
+                    padding.unwrap_or(0 as $t),
+                ))?
+                .map_err(|e| PyValueError::new_err(e.to_string()))?;
+                Ok(Self { inner })
+            }
+
+            /// Returns False if xy is outside the bounds. Raises ValueError for NaN or inf.
+            pub fn insert(&mut self, id: u64, xy: ($t, $t)) -> PyResult<bool> {
                 let (x, y) = xy;
-                self.inner.insert(Item { id, point: Point { x, y } })
+                guarded(|| self.inner.try_insert(Item { id, point: Point { x, y } }))?
+                    .map_err(|e| PyValueError::new_err(e.to_string()))
+            }
+
+            /// Insert and return the depth of the leaf it landed in (root is 0), or
+            /// None if the point is outside the bounds. Raises ValueError for
+            /// non-finite coordinates like insert.
+            pub fn insert_depth(&mut self, id: u64, xy: ($t, $t)) -> PyResult<Option<usize>> {
+                let (x, y) = xy;
+                let item = Item { id, point: Point { x, y } };
+                if !item.point.is_finite() {
+                    return Err(PyValueError::new_err(InsertError::NonFiniteCoordinate.to_string()));
+                }
+                guarded(|| self.inner.insert_depth(item))
+            }
+
+            /// Insert a point under the tree's next auto id and return that id,
+            /// or None if the point is outside the boundary.
+            pub fn insert_auto(&mut self, xy: ($t, $t)) -> PyResult<Option<u64>> {
+                let (x, y) = xy;
+                guarded(|| self.inner.insert_auto(Point { x, y }))
             }
 
             /// Insert many points with auto ids starting at start_id. Returns the last id used.
-            pub fn insert_many(&mut self, start_id: u64, points: Vec<($t, $t)>) -> u64 {
-                let mut id = start_id;
-                for (x, y) in points {
-                    if self.inner.insert(Item { id, point: Point { x, y } }) {
-                        id += 1;
+            pub fn insert_many_auto(&mut self, start_id: u64, points: Vec<($t, $t)>) -> PyResult<u64> {
+                guarded(|| {
+                    let mut id = start_id;
+                    for (x, y) in points {
+                        if self.inner.insert(Item { id, point: Point { x, y } }) {
+                            id += 1;
+                        }
                     }
+                    id.saturating_sub(1)
+                })
+            }
+
+            /// Load points with explicit ids in one pass, building the tree top-down
+            /// instead of inserting one at a time. Expandable trees grow to fit as
+            /// with insert; points that still fall outside the bounds, or have NaN
+            /// or inf coordinates, are skipped. Returns the number of points loaded.
+            pub fn insert_many(
+                &mut self,
+                py: Python<'_>,
+                ids: Vec<u64>,
+                xs: Vec<$t>,
+                ys: Vec<$t>,
+            ) -> PyResult<usize> {
+                if ids.len() != xs.len() || ids.len() != ys.len() {
+                    return Err(PyValueError::new_err("ids, xs, and ys must have the same length"));
                 }
-                id.saturating_sub(1)
+                let items: Vec<Item<$t>> = ids
+                    .into_iter()
+                    .zip(xs.into_iter().zip(ys))
+                    .map(|(id, (x, y))| Item { id, point: Point { x, y } })
+                    .collect();
+                detach_guarded(py, || self.inner.bulk_load(&items))
+            }
+
+            /// Rebuilds the tree from its live items, dropping sparse split nodes
+            pub fn rebuild(&mut self, py: Python<'_>) -> PyResult<()> {
+                detach_guarded(py, || self.inner.rebuild())
             }
 
             /// Assume (N x 2) numpy array of points with dtype matching this class.
@@ -125,7 +437,7 @@ macro_rules! define_point_quadtree_pyclass {
                     return Err(PyValueError::new_err("points must have shape (N, 2)"));
                 }
                 let mut id = start_id;
-                py.detach(|| {
+                detach_guarded(py, || {
                     if let Some(slice) = view.as_slice() {
                         for ch in slice.chunks_exact(2) {
                             let (x, y) = (ch[0], ch[1]);
@@ -141,72 +453,166 @@ macro_rules! define_point_quadtree_pyclass {
                             }
                         }
                     }
-                });
+                })?;
                 Ok(id.saturating_sub(1))
             }
 
-            pub fn delete(&mut self, id: u64, xy: ($t, $t)) -> bool {
+            pub fn delete(&mut self, id: u64, xy: ($t, $t)) -> PyResult<bool> {
+                let (x, y) = xy;
+                guarded(|| self.inner.delete(id, Point { x, y }))
+            }
+
+            /// Like delete, but returns (removed, merged). merged is True when
+            /// nodes folded back together, so cached node rectangles are stale.
+            pub fn delete_reporting(&mut self, id: u64, xy: ($t, $t)) -> PyResult<(bool, bool)> {
+                let (x, y) = xy;
+                let out = guarded(|| self.inner.delete_reporting(id, Point { x, y }))?;
+                Ok((out.removed, out.merged))
+            }
+
+            /// Removes one item with this id wherever it is and returns its (x, y),
+            /// or None. Scans the whole tree; prefer delete when xy is known.
+            pub fn delete_by_id(&mut self, py: Python<'_>, id: u64) -> PyResult<Option<($t, $t)>> {
+                Ok(detach_guarded(py, || self.inner.delete_by_id(id))?.map(|p| (p.x, p.y)))
+            }
+
+            /// True if (id, xy) is stored, checking only the leaf that would hold xy
+            pub fn contains(&self, id: u64, xy: ($t, $t)) -> PyResult<bool> {
+                let (x, y) = xy;
+                guarded(|| self.inner.contains(id, Point { x, y }))
+            }
+
+            /// Returns list[(id, x, y)] of every item stored exactly at xy
+            pub fn items_at(&self, xy: ($t, $t)) -> PyResult<Vec<(u64, $t, $t)>> {
+                let (x, y) = xy;
+                Ok(guarded(|| self.inner.items_at(Point { x, y }))?.into_iter().map(item_to_tuple).collect())
+            }
+
+            /// Moves (id, old_xy) to new_xy. Returns False if the old point was not
+            /// found or new_xy is outside the bounds.
+            pub fn update_position(&mut self, id: u64, old_xy: ($t, $t), new_xy: ($t, $t)) -> PyResult<bool> {
+                let (ox, oy) = old_xy;
+                let (nx, ny) = new_xy;
+                guarded(|| self.inner.update_position(id, Point { x: ox, y: oy }, Point { x: nx, y: ny }))
+            }
+
+            /// Changes the id of (old_id, xy) to new_id without moving it. Returns
+            /// False if that item was not found.
+            pub fn reassign_id(&mut self, old_id: u64, new_id: u64, xy: ($t, $t)) -> PyResult<bool> {
                 let (x, y) = xy;
-                self.inner.delete(id, Point { x, y })
+                guarded(|| self.inner.reassign_id(old_id, new_id, Point { x, y }))
+            }
+
+            /// Returns list[(self_id, other_id)] of cross pairs within radius
+            pub fn join_within(
+                &self,
+                py: Python<'_>,
+                other: PyRef<'_, Self>,
+                radius: $t,
+            ) -> PyResult<Vec<(u64, u64)>> {
+                let (a, b) = (&self.inner, &other.inner);
+                detach_guarded(py, || a.join_within(b, radius))
             }
 
+            /// Returns list[(id_a, id_b)] with id_a < id_b for each pair within radius
+            pub fn pairs_within(&self, py: Python<'_>, radius: $t) -> PyResult<Vec<(u64, u64)>> {
+                detach_guarded(py, || self.inner.pairs_within(radius))
+            }
+
+            /// Removes and returns list[(id, x, y)] within radius of center_xy
+            pub fn drain_circle(
+                &mut self,
+                py: Python<'_>,
+                center_xy: ($t, $t),
+                radius: $t,
+            ) -> PyResult<Vec<(u64, $t, $t)>> {
+                let (x, y) = center_xy;
+                detach_guarded(py, || {
+                    self.inner
+                        .drain_circle(Point { x, y }, radius)
+                        .into_iter()
+                        .map(item_to_tuple)
+                        .collect()
+                })
+            }
+
+            /// Removes and returns list[(id, x, y)] inside rect
+            #[pyo3(signature = (rect, strict=false))]
+            pub fn delete_in_rect(
+                &mut self,
+                py: Python<'_>,
+                rect: ($t, $t, $t, $t),
+                strict: bool,
+            ) -> PyResult<Vec<(u64, $t, $t)>> {
+                let range = query_rect(rect, strict)?;
+                detach_guarded(py, || {
+                    self.inner
+                        .delete_in_rect(range)
+                        .into_iter()
+                        .map(item_to_tuple)
+                        .collect()
+                })
+            }
+
+            /// Returns list[(id, x, y)] inside rect. A rect with min and max
+            /// swapped is normalized first; pass strict=True to raise ValueError
+            /// for it instead.
+            #[pyo3(signature = (rect, strict=false))]
             pub fn query<'py>(
                 &self,
                 py: Python<'py>,
                 rect: ($t, $t, $t, $t),
-            ) -> Bound<'py, PyList> {
-                let (min_x, min_y, max_x, max_y) = rect;
+                strict: bool,
+            ) -> PyResult<Bound<'py, PyList>> {
+                let range = query_rect(rect, strict)?;
 
                 // Release the GIL during the Rust search
-                let tuples = py.detach(|| self.inner.query(Rect { min_x, min_y, max_x, max_y }));
+                let tuples = detach_guarded(py, || self.inner.query(range))?;
 
+                // Every allocation is checked and owned by a Bound, so a failure
+                // raises (MemoryError) and releases what was built so far
                 unsafe {
                     let len = tuples.len() as isize;
-                    let list_ptr = ffi::PyList_New(len);
-                    if list_ptr.is_null() {
-                        ffi::PyErr_NoMemory();
-                    }
+                    let list = Bound::from_owned_ptr_or_err(py, ffi::PyList_New(len))?;
 
                     // preserve ints for integer trees, floats for float trees
                     let is_float = TypeId::of::<$t>() == TypeId::of::<f32>()
                         || TypeId::of::<$t>() == TypeId::of::<f64>();
 
                     for (i, (id, x, y)) in tuples.into_iter().enumerate() {
-                        let tup_ptr = ffi::PyTuple_New(3);
-                        if tup_ptr.is_null() {
-                            ffi::PyErr_NoMemory();
-                        }
+                        let tup = Bound::from_owned_ptr_or_err(py, ffi::PyTuple_New(3))?;
 
-                        let py_id = ffi::PyLong_FromUnsignedLongLong(id);
+                        let py_id = Bound::from_owned_ptr_or_err(py, ffi::PyLong_FromUnsignedLongLong(id))?;
                         let (py_x, py_y) = if is_float {
-                            (ffi::PyFloat_FromDouble(x as f64),
-                            ffi::PyFloat_FromDouble(y as f64))
+                            (Bound::from_owned_ptr_or_err(py, ffi::PyFloat_FromDouble(x as f64))?,
+                            Bound::from_owned_ptr_or_err(py, ffi::PyFloat_FromDouble(y as f64))?)
                         } else {
-                            (ffi::PyLong_FromLongLong(x as i64),
-                            ffi::PyLong_FromLongLong(y as i64))
+                            (Bound::from_owned_ptr_or_err(py, ffi::PyLong_FromLongLong(x as i64))?,
+                            Bound::from_owned_ptr_or_err(py, ffi::PyLong_FromLongLong(y as i64))?)
                         };
 
                         // SetItem functions steal references
-                        ffi::PyTuple_SetItem(tup_ptr, 0, py_id);
-                        ffi::PyTuple_SetItem(tup_ptr, 1, py_x);
-                        ffi::PyTuple_SetItem(tup_ptr, 2, py_y);
-                        ffi::PyList_SetItem(list_ptr, i as isize, tup_ptr);
+                        ffi::PyTuple_SetItem(tup.as_ptr(), 0, py_id.into_ptr());
+                        ffi::PyTuple_SetItem(tup.as_ptr(), 1, py_x.into_ptr());
+                        ffi::PyTuple_SetItem(tup.as_ptr(), 2, py_y.into_ptr());
+                        ffi::PyList_SetItem(list.as_ptr(), i as isize, tup.into_ptr());
                     }
 
-                    // Create Bound<PyAny> then cast to Bound<PyList>
-                    Bound::from_owned_ptr(py, list_ptr).cast_into_unchecked::<PyList>()
+                    Ok(list.cast_into_unchecked::<PyList>())
                 }
             }
 
             /// Returns (ids: np.ndarray[u64], points: np.ndarray[Nx2])
+            #[pyo3(signature = (rect, strict=false))]
             pub fn query_np<'py>(
                 &self,
                 py: Python<'py>,
                 rect: ($t, $t, $t, $t),
+                strict: bool,
             ) -> PyResult<Bound<'py, PyTuple>> {
-                let (min_x, min_y, max_x, max_y) = rect;
-                let (ids_vec, xs_vec, ys_vec) = py.detach(|| {
-                    let tuples = self.inner.query(Rect { min_x, min_y, max_x, max_y });
+                let range = query_rect(rect, strict)?;
+                let (ids_vec, xs_vec, ys_vec) = detach_guarded(py, || {
+                    let tuples = self.inner.query(range);
                     let n = tuples.len();
                     let mut ids = Vec::with_capacity(n);
                     let mut xs  = Vec::with_capacity(n);
@@ -217,7 +623,7 @@ macro_rules! define_point_quadtree_pyclass {
                         ys.push(y);
                     }
                     (ids, xs, ys)
-                });
+                })?;
 
                 let n = ids_vec.len();
                 // Create NumPy arrays
@@ -237,71 +643,253 @@ macro_rules! define_point_quadtree_pyclass {
                 }
             }
 
+            /// False if rect lies entirely outside the tree bounds
+            #[pyo3(signature = (rect, strict=false))]
+            pub fn bounds_intersect(&self, rect: ($t, $t, $t, $t), strict: bool) -> PyResult<bool> {
+                let range = query_rect(rect, strict)?;
+                guarded(|| self.inner.bounds_intersect(&range))
+            }
+
+            /// Returns up to limit (id, x, y) in rect, stopping once that many are found
+            #[pyo3(signature = (rect, limit, strict=false))]
+            pub fn query_limit(
+                &self,
+                py: Python<'_>,
+                rect: ($t, $t, $t, $t),
+                limit: usize,
+                strict: bool,
+            ) -> PyResult<Vec<(u64, $t, $t)>> {
+                let range = query_rect(rect, strict)?;
+                detach_guarded(py, || self.inner.query_limit(range, limit))
+            }
+
             /// Returns list[id, ...]
+            #[pyo3(signature = (rect, strict=false))]
             pub fn query_ids<'py>(
                 &self,
                 py: Python<'py>,
                 rect: ($t, $t, $t, $t),
-            ) -> Bound<'py, PyList> {
-                let (min_x, min_y, max_x, max_y) = rect;
-                let ids: Vec<u64> = py.detach(|| {
-                    self.inner
-                        .query(Rect { min_x, min_y, max_x, max_y })
-                        .into_iter()
-                        .map(|it| it.0) // (id, x, y) -> id
-                        .collect()
-                });
-                PyList::new(py, &ids).expect("Failed to create Python list")
+                strict: bool,
+            ) -> PyResult<Bound<'py, PyList>> {
+                let range = query_rect(rect, strict)?;
+                let ids: Vec<u64> = detach_guarded(py, || self.inner.query_ids(range))?;
+                PyList::new(py, &ids)
             }
 
             /// Returns np.ndarray[u64] of ids only
+            #[pyo3(signature = (rect, strict=false))]
             pub fn query_ids_np<'py>(
                 &self,
                 py: Python<'py>,
                 rect: ($t, $t, $t, $t),
+                strict: bool,
             ) -> PyResult<Bound<'py, PyArray1<u64>>> {
-                let (min_x, min_y, max_x, max_y) = rect;
+                let range = query_rect(rect, strict)?;
 
                 // Run the search without the GIL and collect ids
-                let ids: Vec<u64> = py.detach(|| {
-                    self.inner
-                        .query(Rect { min_x, min_y, max_x, max_y })
-                        .into_iter()
-                        .map(|it| it.0) // (id, x, y) -> id
-                        .collect()
-                });
+                let ids: Vec<u64> = detach_guarded(py, || self.inner.query_ids(range))?;
 
                 // Materialize as a NumPy array
                 Ok(PyArray1::<u64>::from_vec(py, ids))
             }
             
+            /// Mean (x, y) of the items in rect as floats, or None if it is empty
+            #[pyo3(signature = (rect, strict=false))]
+            pub fn centroid_in_rect(
+                &self,
+                py: Python<'_>,
+                rect: ($t, $t, $t, $t),
+                strict: bool,
+            ) -> PyResult<Option<(f64, f64)>> {
+                let range = query_rect(rect, strict)?;
+                let centroid = detach_guarded(py, || self.inner.centroid_in_rect(range))?;
+                Ok(centroid.map(|p| (p.x, p.y)))
+            }
+
+            /// Tight (min_x, min_y, max_x, max_y) of the items in rect, or None if
+            /// it is empty. The max edges are inclusive.
+            #[pyo3(signature = (rect, strict=false))]
+            pub fn extent_in_rect(
+                &self,
+                py: Python<'_>,
+                rect: ($t, $t, $t, $t),
+                strict: bool,
+            ) -> PyResult<Option<($t, $t, $t, $t)>> {
+                let range = query_rect(rect, strict)?;
+                let extent = detach_guarded(py, || self.inner.extent_in_rect(range))?;
+                Ok(extent.map(rect_to_tuple))
+            }
+
+            /// Item counts in a rows x cols grid over the bounds as a flat row-major
+            /// list, row 0 at min_y
+            pub fn density_grid(&self, py: Python<'_>, rows: usize, cols: usize) -> PyResult<Vec<u32>> {
+                detach_guarded(py, || self.inner.density_grid(rows, cols))
+            }
+
+            /// Count items in rect whose id is in allowed_ids (set or frozenset)
+            #[pyo3(signature = (rect, allowed_ids, strict=false))]
+            pub fn count_in_rect_filtered(
+                &self,
+                py: Python<'_>,
+                rect: ($t, $t, $t, $t),
+                allowed_ids: HashSet<u64>,
+                strict: bool,
+            ) -> PyResult<usize> {
+                let range = query_rect(rect, strict)?;
+                detach_guarded(py, || {
+                    self.inner
+                        .count_in_rect_filtered(range, &allowed_ids)
+                })
+            }
+
+            /// Returns list[(id, x, y)] within radius of center_xy, boundary inclusive
+            pub fn query_circle(
+                &self,
+                py: Python<'_>,
+                center_xy: ($t, $t),
+                radius: $t,
+            ) -> PyResult<Vec<(u64, $t, $t)>> {
+                let (x, y) = center_xy;
+                detach_guarded(py, || {
+                    self.inner
+                        .query_circle(Point { x, y }, radius)
+                        .into_iter()
+                        .map(item_to_tuple)
+                        .collect()
+                })
+            }
+
+            /// query_circle with metric in "euclidean", "manhattan", "chebyshev"
+            pub fn query_circle_metric(
+                &self,
+                py: Python<'_>,
+                center_xy: ($t, $t),
+                radius: $t,
+                metric: &str,
+            ) -> PyResult<Vec<(u64, $t, $t)>> {
+                let metric = parse_metric(metric)?;
+                let (x, y) = center_xy;
+                detach_guarded(py, || {
+                    self.inner
+                        .query_circle_metric(Point { x, y }, radius, metric)
+                        .into_iter()
+                        .map(item_to_tuple)
+                        .collect()
+                })
+            }
+
+            /// Nearest (id, x, y) under metric "euclidean", "manhattan" or "chebyshev", or None
+            pub fn nearest_neighbor_metric(&self, xy: ($t, $t), metric: &str) -> PyResult<Option<(u64, $t, $t)>> {
+                let metric = parse_metric(metric)?;
+                let (x, y) = xy;
+                Ok(guarded(|| self.inner.nearest_neighbor_metric(Point { x, y }, metric))?.map(item_to_tuple))
+            }
+
+            /// Reduces tags[id] over items in rect with op in "sum", "min", "max", "mean".
+            /// Items missing from tags are skipped. Returns None if nothing tagged is in range.
+            #[pyo3(signature = (rect, op, tags, strict=false))]
+            pub fn reduce_tag_in_rect(
+                &self,
+                py: Python<'_>,
+                rect: ($t, $t, $t, $t),
+                op: &str,
+                tags: HashMap<u64, f64>,
+                strict: bool,
+            ) -> PyResult<Option<f64>> {
+                let op = match op {
+                    "sum" => ReduceOp::Sum,
+                    "min" => ReduceOp::Min,
+                    "max" => ReduceOp::Max,
+                    "mean" => ReduceOp::Mean,
+                    _ => {
+                        return Err(PyValueError::new_err(format!(
+                            "unknown op {op:?}; expected 'sum', 'min', 'max' or 'mean'"
+                        )))
+                    }
+                };
+                let range = query_rect(rect, strict)?;
+                detach_guarded(py, || {
+                    self.inner.reduce_tag_in_rect(
+                        range,
+                        op,
+                        |id| tags.get(&id).copied(),
+                    )
+                })
+            }
+
+            /// Returns list[(id, x, y)] with (p - point) . normal >= 0
+            pub fn query_halfplane(
+                &self,
+                py: Python<'_>,
+                point: ($t, $t),
+                normal: ($t, $t),
+            ) -> PyResult<Vec<(u64, $t, $t)>> {
+                let (px, py_) = point;
+                let (nx, ny) = normal;
+                detach_guarded(py, || {
+                    self.inner
+                        .query_halfplane(Point { x: px, y: py_ }, Point { x: nx, y: ny })
+                        .into_iter()
+                        .map(item_to_tuple)
+                        .collect()
+                })
+            }
+
+            /// Returns list[(id, x, y)] ordered along a Morton (Z-order) curve
+            #[pyo3(signature = (rect, strict=false))]
+            pub fn query_morton_ordered(
+                &self,
+                py: Python<'_>,
+                rect: ($t, $t, $t, $t),
+                strict: bool,
+            ) -> PyResult<Vec<(u64, $t, $t)>> {
+                let range = query_rect(rect, strict)?;
+                detach_guarded(py, || {
+                    self.inner
+                        .query_morton_ordered(range)
+                        .into_iter()
+                        .map(item_to_tuple)
+                        .collect()
+                })
+            }
+
+            /// Returns list[(id, x, y)] in rect sorted by (id, x, y), stable across versions
+            #[pyo3(signature = (rect, strict=false))]
+            pub fn query_sorted(
+                &self,
+                py: Python<'_>,
+                rect: ($t, $t, $t, $t),
+                strict: bool,
+            ) -> PyResult<Vec<(u64, $t, $t)>> {
+                let range = query_rect(rect, strict)?;
+                detach_guarded(py, || {
+                    self.inner
+                        .query_sorted(range)
+                        .into_iter()
+                        .map(item_to_tuple)
+                        .collect()
+                })
+            }
+
             /// Returns list[Item, ...] by indexing into ObjStore._arr
-            #[pyo3(signature = (rect, arr_list))]
+            #[pyo3(signature = (rect, arr_list, strict=false))]
             pub fn query_items<'py>(
                 &self,
                 py: Python<'py>,
                 rect: ($t, $t, $t, $t),
                 arr_list: &Bound<'py, PyList>,
+                strict: bool,
             ) -> PyResult<Bound<'py, PyList>> {
-                let (min_x, min_y, max_x, max_y) = rect;
+                let range = query_rect(rect, strict)?;
 
                 // 1) Run the quadtree query without the GIL and collect ids
-                let ids: Vec<u64> = py.detach(|| {
-                    self.inner
-                        .query(Rect { min_x, min_y, max_x, max_y })
-                        .into_iter()
-                        .map(|it| it.0) // (id, x, y) -> id
-                        .collect()
-                });
+                let ids: Vec<u64> = detach_guarded(py, || self.inner.query_ids(range))?;
                 
                 // 3) Build output list by indexing arr_list in C (no ids.tolist(), no itemgetter)
                 unsafe {
                     let n = ids.len();
-                    let out_ptr = ffi::PyList_New(n as isize);
-                    if out_ptr.is_null() {
-                        ffi::PyErr_NoMemory();
-                        return Err(PyErr::fetch(py));
-                    }
+                    // Owned immediately so early error returns release it
+                    let out = Bound::from_owned_ptr_or_err(py, ffi::PyList_New(n as isize))?;
 
                     let storage_len = ffi::PyList_Size(arr_list.as_ptr()) as usize;
 
@@ -332,20 +920,93 @@ macro_rules! define_point_quadtree_pyclass {
 
                         // PyList_SetItem steals a reference, so INCREF first
                         ffi::Py_INCREF(item_ptr);
-                        let rc = ffi::PyList_SetItem(out_ptr, i as isize, item_ptr);
+                        let rc = ffi::PyList_SetItem(out.as_ptr(), i as isize, item_ptr);
                         if rc != 0 {
                             return Err(PyErr::fetch(py));
                         }
                     }
 
-                    Ok(Bound::from_owned_ptr(py, out_ptr).cast_into_unchecked::<PyList>())
+                    Ok(out.cast_into_unchecked::<PyList>())
                 }
             }
 
 
-            pub fn nearest_neighbor(&self, xy: ($t, $t)) -> Option<(u64, $t, $t)> {
+            /// Lazily iterates (id, x, y) over every item in depth-first order
+            fn __iter__(slf: PyRef<'_, Self>) -> $iter_name {
+                $iter_name {
+                    tree: slf.into(),
+                    cursor: CircleCursor::unbounded(),
+                }
+            }
+
+            /// Returns a lazy iterator of lists of (id, x, y) in rect, each with at
+            /// most chunk_size items. The tree is walked between chunks, so the
+            /// full result is never held at once. chunk_size must be positive.
+            #[pyo3(signature = (rect, chunk_size, strict=false))]
+            pub fn query_chunked(
+                slf: PyRef<'_, Self>,
+                rect: ($t, $t, $t, $t),
+                chunk_size: usize,
+                strict: bool,
+            ) -> PyResult<$chunk_iter_name> {
+                if chunk_size == 0 {
+                    return Err(PyValueError::new_err("chunk_size must be positive"));
+                }
+                let range = query_rect(rect, strict)?;
+                Ok($chunk_iter_name {
+                    tree: slf.into(),
+                    cursor: RectCursor::new(range),
+                    chunk_size,
+                })
+            }
+
+            /// Returns a lazy iterator of every (id, x, y) in ascending distance from
+            /// xy, ties by ascending id
+            pub fn nearest_iter(slf: PyRef<'_, Self>, xy: ($t, $t)) -> $nearest_iter_name {
                 let (x, y) = xy;
-                self.inner.nearest_neighbor(Point { x, y }).map(item_to_tuple)
+                $nearest_iter_name {
+                    tree: slf.into(),
+                    cursor: NearestCursor::new(Point { x, y }),
+                }
+            }
+
+            /// Returns list[(id, x, y)] inside rect, nearest to focus_xy first and
+            /// ties by ascending id. With limit, only the closest that many are
+            /// found and the rest of the matches are never sorted.
+            #[pyo3(signature = (rect, focus_xy, limit=None, strict=false))]
+            pub fn query_ordered_by(
+                &self,
+                py: Python<'_>,
+                rect: ($t, $t, $t, $t),
+                focus_xy: ($t, $t),
+                limit: Option<usize>,
+                strict: bool,
+            ) -> PyResult<Vec<(u64, $t, $t)>> {
+                let range = query_rect(rect, strict)?;
+                let (x, y) = focus_xy;
+                let mut cursor = NearestCursor::in_rect(Point { x, y }, range);
+                detach_guarded(py, || {
+                    let mut out = Vec::new();
+                    while out.len() < limit.unwrap_or(usize::MAX) {
+                        let Some(it) = cursor.next_item(&self.inner) else { break; };
+                        out.push(item_to_tuple(it));
+                    }
+                    out
+                })
+            }
+
+            /// Returns a lazy iterator of (id, x, y) within radius of xy
+            pub fn iter_circle(slf: PyRef<'_, Self>, xy: ($t, $t), radius: $t) -> $iter_name {
+                let (x, y) = xy;
+                $iter_name {
+                    tree: slf.into(),
+                    cursor: CircleCursor::new(Point { x, y }, radius),
+                }
+            }
+
+            pub fn nearest_neighbor(&self, xy: ($t, $t)) -> PyResult<Option<(u64, $t, $t)>> {
+                let (x, y) = xy;
+                Ok(guarded(|| self.inner.nearest_neighbor(Point { x, y }))?.map(item_to_tuple))
             }
 
             /// Returns (id, coords) or None, where coords is ndarray shape (2,)
@@ -355,7 +1016,7 @@ macro_rules! define_point_quadtree_pyclass {
                 xy: ($t, $t),
             ) -> PyResult<Option<Bound<'py, PyTuple>>> {
                 let (x, y) = xy;
-                match self.inner.nearest_neighbor(Point { x, y }) {
+                match guarded(|| self.inner.nearest_neighbor(Point { x, y }))? {
                     None => Ok(None),
                     Some(item) => {
                         let (id, px, py_) = item_to_tuple(item);
@@ -371,13 +1032,160 @@ macro_rules! define_point_quadtree_pyclass {
                 }
             }
 
-            pub fn nearest_neighbors(&self, xy: ($t, $t), k: usize) -> Vec<(u64, $t, $t)> {
+            pub fn nearest_neighbors(&self, xy: ($t, $t), k: usize) -> PyResult<Vec<(u64, $t, $t)>> {
                 let (x, y) = xy;
-                self.inner
-                    .nearest_neighbors(Point { x, y }, k)
-                    .into_iter()
-                    .map(item_to_tuple)
-                    .collect()
+                let items = guarded(|| self.inner.nearest_neighbors(Point { x, y }, k))?;
+                Ok(items.into_iter().map(item_to_tuple).collect())
+            }
+
+            /// Approximate k nearest as list[(id, x, y)], nearest first. The k-th result is
+            /// at most (1 + epsilon) times farther than the true k-th nearest; epsilon=0 is exact.
+            pub fn nearest_neighbors_approx(
+                &self,
+                py: Python<'_>,
+                xy: ($t, $t),
+                k: usize,
+                epsilon: f64,
+            ) -> PyResult<Vec<(u64, $t, $t)>> {
+                if epsilon.is_nan() || epsilon < 0.0 {
+                    return Err(PyValueError::new_err("epsilon must be >= 0"));
+                }
+                let (x, y) = xy;
+                detach_guarded(py, || {
+                    self.inner
+                        .nearest_neighbors_approx(Point { x, y }, k, epsilon)
+                        .into_iter()
+                        .map(item_to_tuple)
+                        .collect()
+                })
+            }
+
+            /// Nearest neighbor for each (x, y) in points, in the same order, in one call.
+            /// Returns list[(id, x, y) | None].
+            pub fn nearest_neighbor_batch(
+                &self,
+                py: Python<'_>,
+                points: Vec<($t, $t)>,
+            ) -> PyResult<Vec<Option<(u64, $t, $t)>>> {
+                detach_guarded(py, || {
+                    let pts: Vec<Point<$t>> = points.into_iter().map(|(x, y)| Point { x, y }).collect();
+                    self.inner
+                        .nearest_neighbor_batch(&pts)
+                        .into_iter()
+                        .map(|nn| nn.map(item_to_tuple))
+                        .collect()
+                })
+            }
+
+            /// Returns list[(id, x, y, dist)] nearest first, where dist is the
+            /// Euclidean (not squared) distance to xy
+            pub fn nearest_neighbors_with_dist(
+                &self,
+                py: Python<'_>,
+                xy: ($t, $t),
+                k: usize,
+            ) -> PyResult<Vec<(u64, $t, $t, f64)>> {
+                let (x, y) = xy;
+                detach_guarded(py, || {
+                    self.inner
+                        .nearest_neighbors_with_dist(Point { x, y }, k)
+                        .into_iter()
+                        .map(|(it, d)| (it.id, it.point.x, it.point.y, d))
+                        .collect()
+                })
+            }
+
+            /// Nearest (id, x, y) for which predicate(id, x, y) is truthy, or None
+            pub fn nearest_neighbor_filter(
+                &self,
+                xy: ($t, $t),
+                predicate: Bound<'_, PyAny>,
+            ) -> PyResult<Option<(u64, $t, $t)>> {
+                let (x, y) = xy;
+                let err: std::cell::RefCell<Option<PyErr>> = std::cell::RefCell::new(None);
+                let out = guarded(|| self.inner.nearest_neighbor_filter(Point { x, y }, |it| {
+                    if err.borrow().is_some() {
+                        return false;
+                    }
+                    match predicate
+                        .call1((it.id, it.point.x, it.point.y))
+                        .and_then(|v| v.is_truthy())
+                    {
+                        Ok(b) => b,
+                        Err(e) => {
+                            *err.borrow_mut() = Some(e);
+                            false
+                        }
+                    }
+                }))?;
+                if let Some(e) = err.into_inner() {
+                    return Err(e);
+                }
+                Ok(out.map(item_to_tuple))
+            }
+
+            /// Returns list[(id, x, y)] of the k highest-scoring items, best first.
+            /// score_callback(id, x, y, distance) -> float is called for each candidate.
+            pub fn top_k_scored(
+                &self,
+                xy: ($t, $t),
+                k: usize,
+                score_callback: Bound<'_, PyAny>,
+            ) -> PyResult<Vec<(u64, $t, $t)>> {
+                let (x, y) = xy;
+                let err: std::cell::RefCell<Option<PyErr>> = std::cell::RefCell::new(None);
+                let out = guarded(|| self.inner.top_k_scored(Point { x, y }, k, |it, d| {
+                    if err.borrow().is_some() {
+                        return f64::NEG_INFINITY;
+                    }
+                    match score_callback
+                        .call1((it.id, it.point.x, it.point.y, d))
+                        .and_then(|v| v.extract::<f64>())
+                    {
+                        Ok(s) => s,
+                        Err(e) => {
+                            *err.borrow_mut() = Some(e);
+                            f64::NEG_INFINITY
+                        }
+                    }
+                }))?;
+                if let Some(e) = err.into_inner() {
+                    return Err(e);
+                }
+                Ok(out.into_iter().map(item_to_tuple).collect())
+            }
+
+            /// Returns up to k (id, x, y) closer than max_radius, nearest first.
+            /// Raises ValueError unless max_radius is positive; use
+            /// nearest_neighbors for an unbounded search.
+            pub fn nearest_neighbors_within(
+                &self,
+                py: Python<'_>,
+                xy: ($t, $t),
+                k: usize,
+                max_radius: $t,
+            ) -> PyResult<Vec<(u64, $t, $t)>> {
+                // The core reads 0 as unbounded; NaN compares as None and is refused too
+                if max_radius.partial_cmp(&(0 as $t)) != Some(std::cmp::Ordering::Greater) {
+                    return Err(PyValueError::new_err(format!(
+                        "max_radius must be positive, got {max_radius}"
+                    )));
+                }
+                let (x, y) = xy;
+                detach_guarded(py, || {
+                    self.inner
+                        .nearest_neighbors_within(Point { x, y }, k, max_radius)
+                        .into_iter()
+                        .map(item_to_tuple)
+                        .collect()
+                })
+            }
+
+            /// Returns list[(id, x, y)] of the k nearest items with distinct ids
+            pub fn nearest_distinct_ids(&self, xy: ($t, $t), k: usize) -> PyResult<Vec<(u64, $t, $t)>> {
+                let (x, y) = xy;
+                let items = guarded(|| self.inner.nearest_distinct_ids(Point { x, y }, k))?;
+                Ok(items.into_iter().map(item_to_tuple).collect())
             }
 
             /// Returns (ids, coords) where ids is ndarray shape (k,) and coords is ndarray shape (k, 2)
@@ -388,7 +1196,7 @@ macro_rules! define_point_quadtree_pyclass {
                 k: usize,
             ) -> PyResult<Bound<'py, PyTuple>> {
                 let (x, y) = xy;
-                let (ids_vec, xs_vec, ys_vec) = py.detach(|| {
+                let (ids_vec, xs_vec, ys_vec) = detach_guarded(py, || {
                     let items = self.inner.nearest_neighbors(Point { x, y }, k);
                     let n = items.len();
                     let mut ids = Vec::with_capacity(n);
@@ -401,7 +1209,7 @@ macro_rules! define_point_quadtree_pyclass {
                         ys.push(py_);
                     }
                     (ids, xs, ys)
-                });
+                })?;
 
                 let n = ids_vec.len();
                 let ids_arr = PyArray1::<u64>::from_vec(py, ids_vec);
@@ -418,20 +1226,189 @@ macro_rules! define_point_quadtree_pyclass {
                 }
             }
 
-            pub fn get_all_node_boundaries(&self) -> Vec<($t, $t, $t, $t)> {
-                self.inner
-                    .get_all_node_boundaries()
-                    .into_iter()
-                    .map(rect_to_tuple)
-                    .collect()
+            pub fn get_all_node_boundaries(&self) -> PyResult<Vec<($t, $t, $t, $t)>> {
+                guarded(|| {
+                    self.inner
+                        .get_all_node_boundaries()
+                        .into_iter()
+                        .map(rect_to_tuple)
+                        .collect()
+                })
+            }
+
+            /// Returns list[(min_x, min_y, max_x, max_y, depth, item_count, is_leaf)]
+            /// in the same order as get_all_node_boundaries
+            pub fn get_nodes(&self) -> PyResult<Vec<($t, $t, $t, $t, usize, usize, bool)>> {
+                guarded(|| {
+                    self.inner
+                        .get_nodes()
+                        .into_iter()
+                        .map(|n| {
+                            let r = n.rect;
+                            (r.min_x, r.min_y, r.max_x, r.max_y, n.depth, n.item_count, n.is_leaf)
+                        })
+                        .collect()
+                })
+            }
+
+            /// Nested JSON str of nodes: {rect, items: [{id, x, y}], children: [...]}
+            pub fn to_json(&self, py: Python<'_>) -> PyResult<String> {
+                detach_guarded(py, || self.inner.to_json())
+            }
+
+            /// Rotates all points by angle (radians) about center_xy and rebuilds.
+            /// Returns list[(id, x, y)] of points that could not be reinserted.
+            pub fn rotate_rebuild(
+                &mut self,
+                py: Python<'_>,
+                center_xy: ($t, $t),
+                angle: f64,
+            ) -> PyResult<Vec<(u64, $t, $t)>> {
+                let (x, y) = center_xy;
+                detach_guarded(py, || {
+                    self.inner
+                        .rotate_rebuild(Point { x, y }, angle)
+                        .into_iter()
+                        .map(item_to_tuple)
+                        .collect()
+                })
+            }
+
+            /// Subdivides uniformly to depth levels (capped at max_depth), creating 4^depth leaves
+            pub fn pre_subdivide(&mut self, depth: usize) -> PyResult<()> {
+                guarded(|| self.inner.pre_subdivide(depth))
+            }
+
+            /// Returns list[(id, x, y)] inside rect with both max edges included.
+            /// query is half-open (min <= p < max); use this for picking at a
+            /// selection box's exact edge.
+            #[pyo3(signature = (rect, strict=false))]
+            pub fn query_inclusive(&self, py: Python<'_>, rect: ($t, $t, $t, $t), strict: bool) -> PyResult<Vec<(u64, $t, $t)>> {
+                let range = query_rect(rect, strict)?;
+                detach_guarded(py, || {
+                    self.inner
+                        .query_inclusive(range)
+                        .into_iter()
+                        .map(item_to_tuple)
+                        .collect()
+                })
+            }
+
+            /// Number of points in rect, without building a result list.
+            /// Inverted rects are handled as in query.
+            #[pyo3(signature = (rect, strict=false))]
+            pub fn count_in_rect(
+                &self,
+                py: Python<'_>,
+                rect: ($t, $t, $t, $t),
+                strict: bool,
+            ) -> PyResult<usize> {
+                let range = query_rect(rect, strict)?;
+                detach_guarded(py, || self.inner.count_in_rect(range))
+            }
+
+            /// Remove all points, keeping bounds, capacity and max_depth
+            pub fn clear(&mut self) -> PyResult<()> {
+                guarded(|| self.inner.clear())
+            }
+
+            /// Returns list[(id, x, y)] of every stored item in depth-first order
+            pub fn all_items(&self, py: Python<'_>) -> PyResult<Vec<(u64, $t, $t)>> {
+                detach_guarded(py, || self.inner.iter().map(|it| item_to_tuple(*it)).collect())
+            }
+
+            /// Returns list[int] of every stored id, including repeats
+            pub fn all_ids(&self) -> PyResult<Vec<u64>> {
+                guarded(|| self.inner.all_ids())
+            }
+
+            /// Returns a sorted list[int] with each id present once
+            pub fn distinct_ids(&self) -> PyResult<Vec<u64>> {
+                guarded(|| self.inner.distinct_ids())
+            }
+
+            /// Returns (list[rect], list[int]) of leaf boundaries and their item counts
+            pub fn leaf_counts(&self) -> PyResult<(Vec<($t, $t, $t, $t)>, Vec<usize>)> {
+                let (rects, counts) = guarded(|| self.inner.leaf_counts())?;
+                Ok((rects.into_iter().map(rect_to_tuple).collect(), counts))
+            }
+
+            pub fn count_items(&self) -> PyResult<usize> {
+                guarded(|| self.inner.count_items())
+            }
+
+            fn __len__(&self) -> PyResult<usize> {
+                guarded(|| self.inner.count_items())
+            }
+
+            /// True when the tree holds at least one item
+            fn __bool__(&self) -> PyResult<bool> {
+                guarded(|| !self.inner.is_empty())
+            }
+
+            pub fn get_max_depth(&self) -> PyResult<usize> {
+                guarded(|| self.inner.get_max_depth())
+            }
+
+            /// Depth of the deepest leaf (root is 0)
+            pub fn max_depth_reached(&self) -> PyResult<usize> {
+                guarded(|| self.inner.max_depth_reached())
+            }
+
+            pub fn node_count(&self) -> PyResult<usize> {
+                guarded(|| self.inner.node_count())
+            }
+
+            /// Approximate bytes held by the tree, including spare item capacity
+            pub fn memory_bytes(&self) -> PyResult<usize> {
+                guarded(|| self.inner.memory_bytes())
+            }
+
+            /// Returns list[int] where index = depth and value = items stored at that depth
+            pub fn items_per_depth(&self) -> PyResult<Vec<usize>> {
+                guarded(|| self.inner.items_per_depth())
+            }
+
+            /// True if the deepest leaf is within max_depth and mean leaf occupancy is at least min_occupancy
+            pub fn is_well_formed(&self, max_depth: usize, min_occupancy: f64) -> PyResult<bool> {
+                guarded(|| self.inner.is_well_formed(max_depth, min_occupancy))
             }
 
-            pub fn count_items(&self) -> usize {
-                self.inner.count_items()
+            /// Order-independent hash of the stored (id, x, y) items
+            pub fn content_hash(&self) -> PyResult<u64> {
+                guarded(|| self.inner.content_hash())
             }
 
-            pub fn get_max_depth(&self) -> usize {
-                self.inner.get_max_depth()
+            /// Hash of items and node layout; differs from content_hash when structure differs
+            pub fn structural_hash(&self) -> PyResult<u64> {
+                guarded(|| self.inner.structural_hash())
+            }
+
+            /// True if both trees hold the same (id, x, y) items, regardless of structure
+            pub fn content_eq(&self, other: PyRef<'_, Self>) -> PyResult<bool> {
+                guarded(|| self.inner.content_eq(&other.inner))
+            }
+
+            fn __eq__(&self, other: PyRef<'_, Self>) -> PyResult<bool> {
+                guarded(|| self.inner.content_eq(&other.inner))
+            }
+
+            /// Trees are mutable and compare by content, so they are unhashable,
+            /// like list and dict. Use content_hash() for a snapshot fingerprint.
+            fn __hash__(&self) -> PyResult<isize> {
+                Err(PyTypeError::new_err(concat!("unhashable type: '", $py_name, "'")))
+            }
+
+            /// Returns dict of node_count, leaf_count, item_count, max_depth_reached, over_capacity_leaves
+            pub fn stats<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+                let s = guarded(|| self.inner.stats())?;
+                let d = PyDict::new(py);
+                d.set_item("node_count", s.node_count)?;
+                d.set_item("leaf_count", s.leaf_count)?;
+                d.set_item("item_count", s.item_count)?;
+                d.set_item("max_depth_reached", s.max_depth_reached)?;
+                d.set_item("over_capacity_leaves", s.over_capacity_leaves)?;
+                Ok(d)
             }
         }
     };
@@ -449,22 +1426,16 @@ macro_rules! define_rect_quadtree_pyclass {
         impl $rs_name {
             #[new]
             #[pyo3(signature = (bounds, capacity, max_depth=None))]
-            pub fn new(bounds: ($t, $t, $t, $t), capacity: usize, max_depth: Option<usize>) -> Self {
+            pub fn new(bounds: ($t, $t, $t, $t), capacity: usize, max_depth: Option<usize>) -> PyResult<Self> {
                 let (min_x, min_y, max_x, max_y) = bounds;
                 let rect = Rect { min_x, min_y, max_x, max_y };
-                let inner = match max_depth {
-                    Some(d) => RectQuadTree::new(rect, capacity, d),
-                    None => RectQuadTree::new(
-                        rect,
-                        capacity,
-                        default_max_depth_for::<$t>(),
-                    ),
-                };
-                Self { inner }
+                let max_depth = max_depth.unwrap_or_else(default_max_depth_for::<$t>);
+                let inner = guarded(|| RectQuadTree::new(rect, capacity, max_depth))?;
+                Ok(Self { inner })
             }
 
             pub fn to_bytes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
-                let buf = self.inner.to_bytes().map_err(|e| {
+                let buf = guarded(|| self.inner.to_bytes())?.map_err(|e| {
                     PyErr::new::<PyValueError, _>(format!("serialize failed: {e}"))
                 })?;
                 Ok(PyBytes::new(py, &buf))
@@ -477,37 +1448,41 @@ macro_rules! define_rect_quadtree_pyclass {
                 preallocation_limit_bytes: Option<usize>,
                 disable_preallocation_limit: bool,
             ) -> PyResult<Self> {
-                let inner = crate::serialization::decode_native_with_runtime_preallocation_limit::<RectQuadTree<$t>>(
+                let inner = guarded(|| crate::serialization::decode_native_with_runtime_preallocation_limit::<RectQuadTree<$t>>(
                     bytes.as_bytes(),
                     crate::serialization::NATIVE_KIND_RECT,
                     preallocation_limit_bytes,
                     disable_preallocation_limit,
-                ).map_err(|e| {
+                ))?.map_err(|e| {
                     PyErr::new::<PyValueError, _>(format!("deserialize failed: {e}"))
                 })?;
                 Ok(Self { inner })
             }
 
-            pub fn insert(&mut self, id: u64, rect: ($t, $t, $t, $t)) -> bool {
+            pub fn insert(&mut self, id: u64, rect: ($t, $t, $t, $t)) -> PyResult<bool> {
                 let (min_x, min_y, max_x, max_y) = rect;
-                self.inner.insert(RectItem {
-                    id,
-                    rect: Rect { min_x, min_y, max_x, max_y },
+                guarded(|| {
+                    self.inner.insert(RectItem {
+                        id,
+                        rect: Rect { min_x, min_y, max_x, max_y },
+                    })
                 })
             }
 
             /// Insert many rects with auto ids starting at start_id. Returns the last id used.
-            pub fn insert_many(&mut self, start_id: u64, rects: Vec<($t, $t, $t, $t)>) -> u64 {
-                let mut id = start_id;
-                for (min_x, min_y, max_x, max_y) in rects {
-                    if self.inner.insert(RectItem {
-                        id,
-                        rect: Rect { min_x, min_y, max_x, max_y },
-                    }) {
-                        id += 1;
+            pub fn insert_many_auto(&mut self, start_id: u64, rects: Vec<($t, $t, $t, $t)>) -> PyResult<u64> {
+                guarded(|| {
+                    let mut id = start_id;
+                    for (min_x, min_y, max_x, max_y) in rects {
+                        if self.inner.insert(RectItem {
+                            id,
+                            rect: Rect { min_x, min_y, max_x, max_y },
+                        }) {
+                            id += 1;
+                        }
                     }
-                }
-                id.saturating_sub(1)
+                    id.saturating_sub(1)
+                })
             }
 
             /// Assume (N x 4) numpy array of rects with dtype matching this class.
@@ -522,7 +1497,7 @@ macro_rules! define_rect_quadtree_pyclass {
                     return Err(PyValueError::new_err("rects must have shape (N, 4)"));
                 }
                 let mut id = start_id;
-                py.detach(|| {
+                detach_guarded(py, || {
                     if let Some(slice) = view.as_slice() {
                         for ch in slice.chunks_exact(4) {
                             let r = Rect { min_x: ch[0], min_y: ch[1], max_x: ch[2], max_y: ch[3] };
@@ -538,42 +1513,49 @@ macro_rules! define_rect_quadtree_pyclass {
                             }
                         }
                     }
-                });
+                })?;
                 Ok(id.saturating_sub(1))
             }
 
-            pub fn delete(&mut self, id: u64, rect: ($t, $t, $t, $t)) -> bool {
+            pub fn delete(&mut self, id: u64, rect: ($t, $t, $t, $t)) -> PyResult<bool> {
                 let (min_x, min_y, max_x, max_y) = rect;
-                self.inner.delete(id, Rect { min_x, min_y, max_x, max_y })
+                guarded(|| self.inner.delete(id, Rect { min_x, min_y, max_x, max_y }))
             }
 
-            /// Returns list[(id, min_x, min_y, max_x, max_y)]
+            /// Returns list[(id, min_x, min_y, max_x, max_y)] intersecting rect.
+            /// Inverted rects are normalized unless strict=True, as in the point
+            /// tree's query.
+            #[pyo3(signature = (rect, strict=false))]
             pub fn query<'py>(
                 &self,
                 py: Python<'py>,
                 rect: ($t, $t, $t, $t),
-            ) -> Bound<'py, PyList> {
-                let (min_x, min_y, max_x, max_y) = rect;
-                let tuples: Vec<(u64, $t, $t, $t, $t)> = self
-                    .inner
-                    .query(Rect { min_x, min_y, max_x, max_y })
-                    .into_iter()
-                    .map(|(id, r)| (id, r.min_x, r.min_y, r.max_x, r.max_y))
-                    .collect();
-                PyList::new(py, &tuples).expect("Failed to create Python list")
+                strict: bool,
+            ) -> PyResult<Bound<'py, PyList>> {
+                let range = query_rect(rect, strict)?;
+                let tuples: Vec<(u64, $t, $t, $t, $t)> = detach_guarded(py, || {
+                    self.inner
+                        .query(range)
+                        .into_iter()
+                        .map(|(id, r)| (id, r.min_x, r.min_y, r.max_x, r.max_y))
+                        .collect()
+                })?;
+                PyList::new(py, &tuples)
             }
 
             /// Returns (ids: np.ndarray[u64], rects: np.ndarray[Nx4])
+            #[pyo3(signature = (rect, strict=false))]
             pub fn query_np<'py>(
                 &self,
                 py: Python<'py>,
                 rect: ($t, $t, $t, $t),
+                strict: bool,
             ) -> PyResult<Bound<'py, PyTuple>> {
-                let (min_x, min_y, max_x, max_y) = rect;
+                let range = query_rect(rect, strict)?;
 
                 // Run the Rust search without the GIL and collect into flat vectors
-                let (ids_vec, mins_x, mins_y, maxs_x, maxs_y) = py.detach(|| {
-                    let hits = self.inner.query(Rect { min_x, min_y, max_x, max_y });
+                let (ids_vec, mins_x, mins_y, maxs_x, maxs_y) = detach_guarded(py, || {
+                    let hits = self.inner.query(range);
                     let n = hits.len();
                     let mut ids    = Vec::with_capacity(n);
                     let mut v_minx = Vec::with_capacity(n);
@@ -589,7 +1571,7 @@ macro_rules! define_rect_quadtree_pyclass {
                         v_maxy.push(r.max_y);
                     }
                     (ids, v_minx, v_miny, v_maxx, v_maxy)
-                });
+                })?;
 
                 let n = ids_vec.len();
 
@@ -613,37 +1595,42 @@ macro_rules! define_rect_quadtree_pyclass {
             }
 
             /// Returns list[id, ...]
+            #[pyo3(signature = (rect, strict=false))]
             pub fn query_ids<'py>(
                 &self,
                 py: Python<'py>,
                 rect: ($t, $t, $t, $t),
-            ) -> Bound<'py, PyList> {
-                let (min_x, min_y, max_x, max_y) = rect;
-                let ids: Vec<u64> = self
-                    .inner
-                    .query(Rect { min_x, min_y, max_x, max_y })
-                    .into_iter()
-                    .map(|(id, _)| id)
-                    .collect();
-                PyList::new(py, &ids).expect("Failed to create Python list")
+                strict: bool,
+            ) -> PyResult<Bound<'py, PyList>> {
+                let range = query_rect(rect, strict)?;
+                let ids: Vec<u64> = detach_guarded(py, || {
+                    self.inner
+                        .query(range)
+                        .into_iter()
+                        .map(|(id, _)| id)
+                        .collect()
+                })?;
+                PyList::new(py, &ids)
             }
 
             /// Returns np.ndarray[u64] of ids only
+            #[pyo3(signature = (rect, strict=false))]
             pub fn query_ids_np<'py>(
                 &self,
                 py: Python<'py>,
                 rect: ($t, $t, $t, $t),
+                strict: bool,
             ) -> PyResult<Bound<'py, PyArray1<u64>>> {
-                let (min_x, min_y, max_x, max_y) = rect;
+                let range = query_rect(rect, strict)?;
 
                 // Run the search without the GIL and collect ids
-                let ids: Vec<u64> = py.detach(|| {
+                let ids: Vec<u64> = detach_guarded(py, || {
                     self.inner
-                        .query(Rect { min_x, min_y, max_x, max_y })
+                        .query(range)
                         .into_iter()
                         .map(|(id, _r)| id)
                         .collect()
-                });
+                })?;
 
                 // Materialize as a NumPy array
                 Ok(PyArray1::<u64>::from_vec(py, ids))
@@ -652,32 +1639,30 @@ macro_rules! define_rect_quadtree_pyclass {
             /// RectQuadTree version: query and return ObjStore Items (from ObjStore._arr) in hit order.
             /// Usage from Python:
             ///   items = rect_tree.query_items(rect, objstore)
-            #[pyo3(signature = (rect, arr_list))]
+            #[pyo3(signature = (rect, arr_list, strict=false))]
             pub fn query_items<'py>(
                 &self,
                 py: Python<'py>,
                 rect: ($t, $t, $t, $t),
                 arr_list: &Bound<'py, PyList>,
+                strict: bool,
             ) -> PyResult<Bound<'py, PyList>> {
-                let (min_x, min_y, max_x, max_y) = rect;
+                let range = query_rect(rect, strict)?;
 
                 // 1) Run the rect quadtree query without the GIL and collect ids
-                let ids: Vec<u64> = py.detach(|| {
+                let ids: Vec<u64> = detach_guarded(py, || {
                     self.inner
-                        .query(Rect { min_x, min_y, max_x, max_y })
+                        .query(range)
                         .into_iter()
                         .map(|(id, _r)| id)
                         .collect()
-                });
+                })?;
 
                 // 3) Build output list by indexing arr_list in C
                 unsafe {
                     let n = ids.len();
-                    let out_ptr = ffi::PyList_New(n as isize);
-                    if out_ptr.is_null() {
-                        ffi::PyErr_NoMemory();
-                        return Err(PyErr::fetch(py));
-                    }
+                    // Owned immediately so early error returns release it
+                    let out = Bound::from_owned_ptr_or_err(py, ffi::PyList_New(n as isize))?;
 
                     let storage_len = ffi::PyList_Size(arr_list.as_ptr()) as usize;
 
@@ -707,20 +1692,21 @@ macro_rules! define_rect_quadtree_pyclass {
 
                         // PyList_SetItem steals a reference, so INCREF first
                         ffi::Py_INCREF(item_ptr);
-                        let rc = ffi::PyList_SetItem(out_ptr, i as isize, item_ptr);
+                        let rc = ffi::PyList_SetItem(out.as_ptr(), i as isize, item_ptr);
                         if rc != 0 {
                             return Err(PyErr::fetch(py));
                         }
                     }
 
-                    Ok(Bound::from_owned_ptr(py, out_ptr).cast_into_unchecked::<PyList>())
+                    Ok(out.cast_into_unchecked::<PyList>())
                 }
             }
 
             /// Returns a single nearest neighbor (id, min_x, min_y, max_x, max_y)
-            pub fn nearest_neighbor(&self, xy: ($t, $t)) -> Option<(u64, $t, $t, $t, $t)> {
+            pub fn nearest_neighbor(&self, xy: ($t, $t)) -> PyResult<Option<(u64, $t, $t, $t, $t)>> {
                 let (x, y) = xy;
-                self.inner.nearest_neighbor(Point { x, y }).map(|item| (item.id, item.rect.min_x, item.rect.min_y, item.rect.max_x, item.rect.max_y))
+                let item = guarded(|| self.inner.nearest_neighbor(Point { x, y }))?;
+                Ok(item.map(|item| (item.id, item.rect.min_x, item.rect.min_y, item.rect.max_x, item.rect.max_y)))
             }
 
             /// Returns (id, coords) or None, where coords is ndarray shape (4,)
@@ -730,7 +1716,7 @@ macro_rules! define_rect_quadtree_pyclass {
                 xy: ($t, $t),
             ) -> PyResult<Option<Bound<'py, PyTuple>>> {
                 let (x, y) = xy;
-                match self.inner.nearest_neighbor(Point { x, y }) {
+                match guarded(|| self.inner.nearest_neighbor(Point { x, y }))? {
                     None => Ok(None),
                     Some(item) => {
                         let id = item.id;
@@ -749,13 +1735,13 @@ macro_rules! define_rect_quadtree_pyclass {
             }
 
             /// Returns K nearest neighbors as a list[(id, min_x, min_y, max_x, max_y)]
-            pub fn nearest_neighbors(&self, xy: ($t, $t), k: usize) -> Vec<(u64, $t, $t, $t, $t)> {
+            pub fn nearest_neighbors(&self, xy: ($t, $t), k: usize) -> PyResult<Vec<(u64, $t, $t, $t, $t)>> {
                 let (x, y) = xy;
-                self.inner
-                    .nearest_neighbors(Point { x, y }, k)
+                let items = guarded(|| self.inner.nearest_neighbors(Point { x, y }, k))?;
+                Ok(items
                     .into_iter()
                     .map(|item| (item.id, item.rect.min_x, item.rect.min_y, item.rect.max_x, item.rect.max_y))
-                    .collect()
+                    .collect())
             }
 
             /// Returns (ids, coords) where ids is ndarray shape (k,) and coords is ndarray shape (k, 4)
@@ -766,7 +1752,7 @@ macro_rules! define_rect_quadtree_pyclass {
                 k: usize,
             ) -> PyResult<Bound<'py, PyTuple>> {
                 let (x, y) = xy;
-                let (ids_vec, mins_x, mins_y, maxs_x, maxs_y) = py.detach(|| {
+                let (ids_vec, mins_x, mins_y, maxs_x, maxs_y) = detach_guarded(py, || {
                     let items = self.inner.nearest_neighbors(Point { x, y }, k);
                     let n = items.len();
                     let mut ids = Vec::with_capacity(n);
@@ -782,7 +1768,7 @@ macro_rules! define_rect_quadtree_pyclass {
                         v_maxy.push(item.rect.max_y);
                     }
                     (ids, v_minx, v_miny, v_maxx, v_maxy)
-                });
+                })?;
 
                 let n = ids_vec.len();
                 let ids_arr = PyArray1::<u64>::from_vec(py, ids_vec);
@@ -801,41 +1787,79 @@ macro_rules! define_rect_quadtree_pyclass {
                 }
             }
 
-            pub fn get_all_node_boundaries(&self) -> Vec<($t, $t, $t, $t)> {
-                self.inner
-                    .get_all_node_boundaries()
-                    .into_iter()
-                    .map(rect_to_tuple)
-                    .collect()
+            pub fn get_all_node_boundaries(&self) -> PyResult<Vec<($t, $t, $t, $t)>> {
+                guarded(|| {
+                    self.inner
+                        .get_all_node_boundaries()
+                        .into_iter()
+                        .map(rect_to_tuple)
+                        .collect()
+                })
             }
 
-            pub fn count_items(&self) -> usize {
-                self.inner.count_items()
+            pub fn count_items(&self) -> PyResult<usize> {
+                guarded(|| self.inner.count_items())
             }
 
-            pub fn get_max_depth(&self) -> usize {
-                self.inner.get_max_depth()
+            pub fn get_max_depth(&self) -> PyResult<usize> {
+                guarded(|| self.inner.get_max_depth())
             }
         }
     };
 }
 
 // f32 default names for backward compat
-define_point_quadtree_pyclass!(f32, PyQuadTreeF32, "QuadTree");
+define_point_quadtree_pyclass!(
+    f32, PyQuadTreeF32, "QuadTree",
+    PyQuadTreeIterF32, "QuadTreeIter",
+    PyQuadTreeChunkIterF32, "QuadTreeChunkIter",
+    PyQuadTreeNearestIterF32, "QuadTreeNearestIter"
+);
 define_rect_quadtree_pyclass!(f32, PyRectQuadTreeF32, "RectQuadTree");
 
 // f64
-define_point_quadtree_pyclass!(f64, PyQuadTreeF64, "QuadTreeF64");
+define_point_quadtree_pyclass!(
+    f64, PyQuadTreeF64, "QuadTreeF64",
+    PyQuadTreeIterF64, "QuadTreeIterF64",
+    PyQuadTreeChunkIterF64, "QuadTreeChunkIterF64",
+    PyQuadTreeNearestIterF64, "QuadTreeNearestIterF64"
+);
 define_rect_quadtree_pyclass!(f64, PyRectQuadTreeF64, "RectQuadTreeF64");
 
 // i32
-define_point_quadtree_pyclass!(i32, PyQuadTreeI32, "QuadTreeI32");
+define_point_quadtree_pyclass!(
+    i32, PyQuadTreeI32, "QuadTreeI32",
+    PyQuadTreeIterI32, "QuadTreeIterI32",
+    PyQuadTreeChunkIterI32, "QuadTreeChunkIterI32",
+    PyQuadTreeNearestIterI32, "QuadTreeNearestIterI32"
+);
 define_rect_quadtree_pyclass!(i32, PyRectQuadTreeI32, "RectQuadTreeI32");
 
 // i64
-define_point_quadtree_pyclass!(i64, PyQuadTreeI64, "QuadTreeI64");
+define_point_quadtree_pyclass!(
+    i64, PyQuadTreeI64, "QuadTreeI64",
+    PyQuadTreeIterI64, "QuadTreeIterI64",
+    PyQuadTreeChunkIterI64, "QuadTreeChunkIterI64",
+    PyQuadTreeNearestIterI64, "QuadTreeNearestIterI64"
+);
 define_rect_quadtree_pyclass!(i64, PyRectQuadTreeI64, "RectQuadTreeI64");
 
+/// Squared distance between points a_xy and b_xy
+#[pyfunction(name = "dist_sq_points")]
+fn py_dist_sq_points(a_xy: (f64, f64), b_xy: (f64, f64)) -> f64 {
+    let (ax, ay) = a_xy;
+    let (bx, by) = b_xy;
+    dist_sq_points(&Point { x: ax, y: ay }, &Point { x: bx, y: by })
+}
+
+/// Squared distance from xy to the nearest point of rect; 0 when inside
+#[pyfunction(name = "dist_sq_point_to_rect")]
+fn py_dist_sq_point_to_rect(xy: (f64, f64), rect: (f64, f64, f64, f64)) -> f64 {
+    let (x, y) = xy;
+    let (min_x, min_y, max_x, max_y) = rect;
+    dist_sq_point_to_rect(&Point { x, y }, &Rect { min_x, min_y, max_x, max_y })
+}
+
 #[pymodule]
 fn _native(_py: Python, m: &Bound<PyModule>) -> PyResult<()> {
     // f32 defaults
@@ -853,5 +1877,9 @@ fn _native(_py: Python, m: &Bound<PyModule>) -> PyResult<()> {
     // i64
     m.add_class::<PyQuadTreeI64>()?;
     m.add_class::<PyRectQuadTreeI64>()?;
+
+    // Geometry helpers
+    m.add_function(wrap_pyfunction!(py_dist_sq_points, m)?)?;
+    m.add_function(wrap_pyfunction!(py_dist_sq_point_to_rect, m)?)?;
     Ok(())
 }
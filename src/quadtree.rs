@@ -1,10 +1,14 @@
-use crate::geom::{Point, Rect, dist_sq_point_to_rect, dist_sq_points, Coord, mid};
+use crate::geom::{Point, Rect, dist_sq_point_to_rect, dist_sq_points, dist_sq_rect_to_rect, Coord, Metric, mid};
 use crate::serialization::{
     decode_native, decode_native_unlimited, decode_native_with_preallocation_limit, encode_native,
-    NativeDecodeConfig, NativeEncodingConfig, SerializationError,
+    decode_body_exact, NativeBody, NativeDecodeConfig, NativeEncodingConfig, SerializationError,
     DEFAULT_NATIVE_PREALLOCATION_LIMIT_BYTES, NATIVE_KIND_POINT,
 };
+use num_traits::NumCast;
 use smallvec::SmallVec;
+use std::collections::{BinaryHeap, HashSet};
+use std::fmt;
+use std::ops::ControlFlow;
 use wincode::{SchemaRead, SchemaWrite};
 
 #[derive(Copy, Clone, Debug, PartialEq, Default, SchemaWrite, SchemaRead)]
@@ -13,7 +17,523 @@ pub struct Item<T: Coord> {
     pub point: Point<T>,
 }
 
-#[derive(SchemaWrite, SchemaRead)]
+// Shape summary gathered in a single traversal by `QuadTree::stats`.
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct QuadTreeStats {
+    pub node_count: usize,
+    pub leaf_count: usize,
+    pub item_count: usize,
+    pub max_depth_reached: usize,
+    // Leaves holding more than `split_threshold` items (`capacity` unless
+    // configured). Only leaves at max_depth can end up here, since they stop
+    // splitting.
+    pub over_capacity_leaves: usize,
+}
+
+// One node as reported by `QuadTree::get_nodes`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NodeInfo<T: Coord> {
+    pub rect: Rect<T>,
+    pub depth: usize,
+    pub item_count: usize,
+    pub is_leaf: bool,
+}
+
+// What `QuadTree::delete_reporting` did. `merged` is true when any node on
+// the delete path folded its children back in, so node rectangles changed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct DeleteOutcome {
+    pub removed: bool,
+    pub merged: bool,
+}
+
+// Node sizing for `QuadTree::new_with_config`.
+//   capacity: items a leaf preallocates room for
+//   split_threshold: a leaf splits once an insert would take it past this
+//   max_depth: leaves at this depth never split
+//   merge_threshold: siblings merge back once they hold this many or fewer
+// Both default to capacity, which is what `new` uses. A split_threshold above
+// capacity and a merge_threshold below it leave a gap so a leaf near the
+// limit doesn't split and merge on every update.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct QuadTreeConfig {
+    pub capacity: usize,
+    pub split_threshold: usize,
+    pub max_depth: usize,
+    pub merge_threshold: usize,
+}
+
+// How a node divides when it splits. Either way a node gets four children.
+//   Quadrant: cut both axes at the midpoint
+//   LongestAxis: halve the longer side, then halve each half's longer side,
+//     i.e. two kd-tree levels per node. A 4:1 node becomes four strips
+//     instead of four 2:1 cells. On a square node (ties cut y first) the
+//     result is the same four quadrants.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, SchemaWrite, SchemaRead)]
+pub enum SplitStrategy {
+    #[default]
+    Quadrant,
+    LongestAxis,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    InvalidBoundary,
+    SplitThresholdBelowCapacity { split_threshold: usize, capacity: usize },
+    MergeThresholdAboveCapacity { merge_threshold: usize, capacity: usize },
+    InvalidPadding,
+    NonFinitePoint { id: u64 },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::InvalidBoundary => {
+                write!(f, "boundary must have min < max on both axes")
+            }
+            ConfigError::SplitThresholdBelowCapacity { split_threshold, capacity } => write!(
+                f,
+                "split_threshold ({split_threshold}) must be >= capacity ({capacity})"
+            ),
+            ConfigError::MergeThresholdAboveCapacity { merge_threshold, capacity } => write!(
+                f,
+                "merge_threshold ({merge_threshold}) must be <= capacity ({capacity})"
+            ),
+            ConfigError::InvalidPadding => write!(f, "padding must be finite and >= 0"),
+            ConfigError::NonFinitePoint { id } => {
+                write!(f, "point {id} has a NaN or infinite coordinate")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InsertError {
+    NonFiniteCoordinate,
+}
+
+impl fmt::Display for InsertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InsertError::NonFiniteCoordinate => write!(f, "point coordinates must be finite"),
+        }
+    }
+}
+
+impl std::error::Error for InsertError {}
+
+impl QuadTreeConfig {
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.split_threshold < self.capacity {
+            return Err(ConfigError::SplitThresholdBelowCapacity {
+                split_threshold: self.split_threshold,
+                capacity: self.capacity,
+            });
+        }
+        if self.merge_threshold > self.capacity {
+            return Err(ConfigError::MergeThresholdAboveCapacity {
+                merge_threshold: self.merge_threshold,
+                capacity: self.capacity,
+            });
+        }
+        Ok(())
+    }
+}
+
+// splitmix64 finalizer. Used instead of DefaultHasher so hashes are stable
+// across Rust versions and can be persisted as cache keys.
+#[inline(always)]
+fn mix64(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[inline(always)]
+fn hash_combine(h: u64, v: u64) -> u64 {
+    mix64(h ^ v.wrapping_add(0x9e37_79b9_7f4a_7c15))
+}
+
+// Exact bits for any supported coordinate type. Integral values go through
+// i64 so large i64 coordinates don't collide after an f64 cast.
+#[inline]
+fn coord_bits<T: Coord + NumCast>(v: T) -> u64 {
+    match v.to_i64() {
+        Some(i) if T::from(i) == Some(v) => i as u64,
+        _ => v.to_f64().map_or(0, f64::to_bits),
+    }
+}
+
+// Lowest and highest finite values of T, found by bisecting over f64 bit
+// patterns. Types wider than f64's mantissa (i64, u64) get limits a little
+// inside their true range.
+fn coord_limits<T: Coord + NumCast>() -> (T, T) {
+    let fits = |v: f64| <T as NumCast>::from(v).is_some_and(|c: T| c * T::zero() == T::zero());
+    let furthest = |sign: f64| {
+        let (mut ok, mut bad) = (0u64, f64::INFINITY.to_bits());
+        while bad - ok > 1 {
+            let m = ok + (bad - ok) / 2;
+            if fits(sign * f64::from_bits(m)) { ok = m; } else { bad = m; }
+        }
+        <T as NumCast>::from(sign * f64::from_bits(ok)).unwrap_or_else(T::zero)
+    };
+    (furthest(-1.0), furthest(1.0))
+}
+
+// `edge + by` when `up`, else `edge - by`, or None if that would pass the
+// `limits` from `coord_limits`. Compares halves so integer math can't overflow
+// near the limits; the extra unit covers the rounding of integer division.
+fn step_within<T: Coord>(edge: T, by: T, up: bool, (lo, hi): (T, T)) -> Option<T> {
+    if by == T::zero() {
+        return Some(edge);
+    }
+    let two = T::one() + T::one();
+    if up {
+        let fits = edge < hi && hi / two - edge / two > by / two + T::one();
+        fits.then(|| edge + by)
+    } else {
+        let fits = edge > lo && edge / two - lo / two > by / two + T::one();
+        fits.then(|| edge - by)
+    }
+}
+
+// Smallest value found above `v` by adding 1, 2, 4, ... (or a fraction of that
+// when 1 is below v's precision), which for floats is v's next representable
+// value once 1 no longer moves it. None at or past the top limit.
+fn next_above<T: Coord>(v: T, (_, hi): (T, T)) -> Option<T> {
+    if v >= hi {
+        return None;
+    }
+    let mut step = T::one();
+    while v + step <= v {
+        step = step + step;
+    }
+    Some(v + step)
+}
+
+#[inline]
+fn item_hash<T: Coord + NumCast>(it: &Item<T>) -> u64 {
+    let h = hash_combine(mix64(it.id), coord_bits(it.point.x));
+    hash_combine(h, coord_bits(it.point.y))
+}
+
+// Reduction applied by `QuadTree::reduce_tag_in_rect`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ReduceOp {
+    Sum,
+    Min,
+    Max,
+    Mean,
+}
+
+// Child indices from the root down to a node
+type NodePath = SmallVec<[u8; 32]>;
+
+// Resumable circle traversal. Nodes are tracked by path rather than by
+// reference so the cursor can outlive a borrow of the tree (e.g. behind a
+// Python iterator). Mutating the tree between calls invalidates the cursor:
+// it stays memory safe, but items may be skipped or repeated.
+// An unbounded cursor skips the distance tests and yields every item.
+pub struct CircleCursor<T: Coord> {
+    center: Point<T>,
+    // None when unbounded
+    radius_sq: Option<T>,
+    stack: Vec<NodePath>,
+    // Leaf being scanned and the next item index within it
+    leaf: Option<(NodePath, usize)>,
+    nodes_visited: usize,
+}
+
+impl<T: Coord> CircleCursor<T> {
+    pub fn new(center: Point<T>, radius: T) -> Self {
+        CircleCursor {
+            center,
+            radius_sq: Some(radius * radius),
+            stack: vec![NodePath::new()],
+            leaf: None,
+            nodes_visited: 0,
+        }
+    }
+
+    // Cursor over every item, in the same depth-first order as `QuadTree::iter`
+    pub fn unbounded() -> Self {
+        CircleCursor {
+            center: Point { x: T::zero(), y: T::zero() },
+            radius_sq: None,
+            stack: vec![NodePath::new()],
+            leaf: None,
+            nodes_visited: 0,
+        }
+    }
+
+    // Nodes that intersected the circle and were expanded so far
+    pub fn nodes_visited(&self) -> usize {
+        self.nodes_visited
+    }
+
+    // Next item within the radius (inclusive), or None when exhausted
+    pub fn next_item(&mut self, tree: &QuadTree<T>) -> Option<Item<T>> {
+        loop {
+            if let Some((path, idx)) = self.leaf.as_mut() {
+                if let Some(node) = tree.node_at(path) {
+                    while *idx < node.items.len() {
+                        let it = node.items[*idx];
+                        *idx += 1;
+                        if self.radius_sq.is_none_or(|r2| dist_sq_points(&self.center, &it.point) <= r2) {
+                            return Some(it);
+                        }
+                    }
+                }
+                self.leaf = None;
+            }
+
+            let path = self.stack.pop()?;
+            let Some(node) = tree.node_at(&path) else { continue; };
+            if self.radius_sq.is_some_and(|r2| dist_sq_point_to_rect(&self.center, &node.boundary) > r2) {
+                continue;
+            }
+            self.nodes_visited += 1;
+            if node.children.is_some() {
+                // Reverse so child 0 is scanned first
+                for i in (0..4u8).rev() {
+                    let mut child = path.clone();
+                    child.push(i);
+                    self.stack.push(child);
+                }
+            } else {
+                self.leaf = Some((path, 0));
+            }
+        }
+    }
+}
+
+// Resumable rect traversal with the same path-based stack and caveats as
+// `CircleCursor`. Matches use the half-open test from `query`.
+pub struct RectCursor<T: Coord> {
+    range: Rect<T>,
+    stack: Vec<NodePath>,
+    // Leaf being scanned and the next item index within it
+    leaf: Option<(NodePath, usize)>,
+}
+
+impl<T: Coord> RectCursor<T> {
+    pub fn new(range: Rect<T>) -> Self {
+        RectCursor {
+            range,
+            stack: vec![NodePath::new()],
+            leaf: None,
+        }
+    }
+
+    // Next item inside the range, or None when exhausted
+    pub fn next_item(&mut self, tree: &QuadTree<T>) -> Option<Item<T>> {
+        loop {
+            if let Some((path, idx)) = self.leaf.as_mut() {
+                if let Some(node) = tree.node_at(path) {
+                    while *idx < node.items.len() {
+                        let it = node.items[*idx];
+                        *idx += 1;
+                        if self.range.contains(&it.point) {
+                            return Some(it);
+                        }
+                    }
+                }
+                self.leaf = None;
+            }
+
+            let path = self.stack.pop()?;
+            let Some(node) = tree.node_at(&path) else { continue; };
+            if !self.range.intersects(&node.boundary) {
+                continue;
+            }
+            if node.children.is_some() {
+                // Reverse so child 0 is scanned first
+                for i in (0..4u8).rev() {
+                    let mut child = path.clone();
+                    child.push(i);
+                    self.stack.push(child);
+                }
+            } else {
+                self.leaf = Some((path, 0));
+            }
+        }
+    }
+
+    // Up to `max` further items appended to `out`; returns how many were added
+    pub fn next_chunk(&mut self, tree: &QuadTree<T>, max: usize, out: &mut Vec<Item<T>>) -> usize {
+        let before = out.len();
+        while out.len() - before < max {
+            match self.next_item(tree) {
+                Some(it) => out.push(it),
+                None => break,
+            }
+        }
+        out.len() - before
+    }
+}
+
+// Heap entry for `NearestCursor`. BinaryHeap pops the greatest entry, so
+// the order is reversed: the smallest key is greatest. At equal keys a node
+// comes out before an item, since it may still hold a lower-id tie, and
+// items come out in ascending id order.
+enum NearestEntry<T: Coord> {
+    Node(T, NodePath),
+    Item(T, Item<T>),
+}
+
+impl<T: Coord> NearestEntry<T> {
+    fn key(&self) -> T {
+        match self {
+            NearestEntry::Node(k, _) | NearestEntry::Item(k, _) => *k,
+        }
+    }
+}
+
+impl<T: Coord> Ord for NearestEntry<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        use std::cmp::Ordering;
+        // Keys are never NaN, since stored points are finite
+        other.key().partial_cmp(&self.key()).unwrap_or(Ordering::Equal).then_with(|| {
+            match (self, other) {
+                (NearestEntry::Node(..), NearestEntry::Item(..)) => Ordering::Greater,
+                (NearestEntry::Item(..), NearestEntry::Node(..)) => Ordering::Less,
+                (NearestEntry::Item(_, a), NearestEntry::Item(_, b)) => b.id.cmp(&a.id),
+                (NearestEntry::Node(..), NearestEntry::Node(..)) => Ordering::Equal,
+            }
+        })
+    }
+}
+
+impl<T: Coord> PartialOrd for NearestEntry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Coord> PartialEq for NearestEntry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
+
+impl<T: Coord> Eq for NearestEntry<T> {}
+
+// Resumable best-first search yielding every item in ascending distance
+// from `point`, ties by ascending id. Unlike `nearest_neighbors` an id stored
+// at several points is yielded once per point. Nodes are tracked by path,
+// with the same caveats as `CircleCursor`.
+pub struct NearestCursor<T: Coord> {
+    point: Point<T>,
+    heap: BinaryHeap<NearestEntry<T>>,
+    range: Option<Rect<T>>,
+}
+
+impl<T: Coord> NearestCursor<T> {
+    pub fn new(point: Point<T>) -> Self {
+        let mut heap = BinaryHeap::new();
+        heap.push(NearestEntry::Node(T::zero(), NodePath::new()));
+        NearestCursor { point, heap, range: None }
+    }
+
+    // Same order as `new`, limited to items inside `range` (half-open, as
+    // in `query`). Nodes outside `range` are never expanded.
+    pub fn in_rect(point: Point<T>, range: Rect<T>) -> Self {
+        NearestCursor { range: Some(range), ..NearestCursor::new(point) }
+    }
+
+    // Next closest item, or None when exhausted
+    pub fn next_item(&mut self, tree: &QuadTree<T>) -> Option<Item<T>> {
+        while let Some(entry) = self.heap.pop() {
+            let path = match entry {
+                NearestEntry::Item(_, it) => return Some(it),
+                NearestEntry::Node(_, path) => path,
+            };
+            let Some(node) = tree.node_at(&path) else { continue; };
+            match node.children.as_ref() {
+                Some(children) => {
+                    for (i, child) in children.iter().enumerate() {
+                        if self.range.is_some_and(|r| !r.intersects(&child.boundary)) {
+                            continue;
+                        }
+                        let mut child_path = path.clone();
+                        child_path.push(i as u8);
+                        let key = dist_sq_point_to_rect(&self.point, &child.boundary);
+                        self.heap.push(NearestEntry::Node(key, child_path));
+                    }
+                }
+                None => {
+                    for it in &node.items {
+                        if self.range.is_some_and(|r| !r.contains(&it.point)) {
+                            continue;
+                        }
+                        let key = dist_sq_points(&self.point, &it.point);
+                        self.heap.push(NearestEntry::Item(key, *it));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+// Buffers for `QuadTree::nearest_neighbors_with_scratch`. Keeping one
+// across many searches skips the per-call allocations. Nodes on the search
+// stack are tracked by path, as in the cursors, so the scratch holds no
+// borrow and one scratch can serve any number of trees, including ones that
+// are modified in between.
+pub struct QueryScratch<T: Coord> {
+    // (node path, bbox distance squared)
+    stack: Vec<(NodePath, T)>,
+    // (child index, bbox distance squared) of the node being expanded
+    kids: Vec<(u8, T)>,
+    // Addresses of the items already returned by the current search
+    picked: HashSet<usize>,
+}
+
+impl<T: Coord> QueryScratch<T> {
+    pub fn new() -> Self {
+        QueryScratch { stack: Vec::new(), kids: Vec::with_capacity(4), picked: HashSet::new() }
+    }
+
+    // Entries the search stack holds without reallocating
+    pub fn stack_capacity(&self) -> usize {
+        self.stack.capacity()
+    }
+}
+
+impl<T: Coord> Default for QueryScratch<T> {
+    fn default() -> Self {
+        QueryScratch::new()
+    }
+}
+
+// Borrowing iterator over a `NearestCursor`, from `QuadTree::nearest_iter`
+pub struct NearestIter<'a, T: Coord> {
+    tree: &'a QuadTree<T>,
+    cursor: NearestCursor<T>,
+}
+
+impl<T: Coord> Iterator for NearestIter<'_, T> {
+    type Item = Item<T>;
+
+    fn next(&mut self) -> Option<Item<T>> {
+        self.cursor.next_item(self.tree)
+    }
+}
+
+impl QuadTreeStats {
+    // Average items per leaf
+    pub fn mean_leaf_occupancy(&self) -> f64 {
+        if self.leaf_count == 0 {
+            return 0.0;
+        }
+        self.item_count as f64 / self.leaf_count as f64
+    }
+}
+
+#[derive(Clone, SchemaWrite, SchemaRead)]
 pub struct QuadTree<T: Coord> {
     pub boundary: Rect<T>,
     pub items: Vec<Item<T>>,
@@ -21,6 +541,73 @@ pub struct QuadTree<T: Coord> {
     pub children: Option<Box<[QuadTree<T>; 4]>>,
     depth: usize,
     max_depth: usize,
+    split_threshold: usize,
+    merge_threshold: usize,
+    // Root only: when set, out-of-bounds inserts grow the tree, keeping
+    // every edge within these lowest and highest values of T
+    grow_limits: Option<(T, T)>,
+    // Root only: next id handed out by `insert_auto`
+    next_id: u64,
+    split_strategy: SplitStrategy,
+}
+
+// Point tree bodies written at format v1 and v2. Decoded only to be turned
+// into the current layout: thresholds at capacity, quadrant splits, no
+// growth and a fresh `insert_auto` counter, which is how those versions
+// behaved. v2's expandable flag is dropped.
+#[derive(SchemaRead)]
+pub(crate) struct QuadTreeV1<T: Coord> {
+    boundary: Rect<T>,
+    items: Vec<Item<T>>,
+    capacity: usize,
+    children: Option<Box<[QuadTreeV1<T>; 4]>>,
+    depth: usize,
+    max_depth: usize,
+}
+
+#[derive(SchemaRead)]
+pub(crate) struct QuadTreeV2<T: Coord> {
+    boundary: Rect<T>,
+    items: Vec<Item<T>>,
+    capacity: usize,
+    children: Option<Box<[QuadTreeV2<T>; 4]>>,
+    depth: usize,
+    max_depth: usize,
+    #[allow(dead_code)]
+    expandable: bool,
+}
+
+impl<T: Coord> From<QuadTreeV1<T>> for QuadTree<T> {
+    fn from(old: QuadTreeV1<T>) -> Self {
+        let mut node = QuadTree::new_child(old.boundary, old.capacity, old.depth, old.max_depth);
+        node.items = old.items;
+        node.children = old.children.map(|kids| Box::new(kids.map(QuadTree::from)));
+        node
+    }
+}
+
+impl<T: Coord> From<QuadTreeV2<T>> for QuadTree<T> {
+    fn from(old: QuadTreeV2<T>) -> Self {
+        let mut node = QuadTree::new_child(old.boundary, old.capacity, old.depth, old.max_depth);
+        node.items = old.items;
+        node.children = old.children.map(|kids| Box::new(kids.map(QuadTree::from)));
+        node
+    }
+}
+
+impl<T: Coord, const LIMIT: usize> NativeBody<LIMIT> for QuadTree<T>
+where
+    Self: for<'de> SchemaRead<'de, NativeDecodeConfig<LIMIT>, Dst = Self>,
+    QuadTreeV1<T>: for<'de> SchemaRead<'de, NativeDecodeConfig<LIMIT>, Dst = QuadTreeV1<T>>,
+    QuadTreeV2<T>: for<'de> SchemaRead<'de, NativeDecodeConfig<LIMIT>, Dst = QuadTreeV2<T>>,
+{
+    fn decode_body(version: u16, body: &[u8]) -> Result<Self, SerializationError> {
+        match version {
+            1 => decode_body_exact::<QuadTreeV1<T>, LIMIT>(body).map(QuadTree::from),
+            2 => decode_body_exact::<QuadTreeV2<T>, LIMIT>(body).map(QuadTree::from),
+            _ => decode_body_exact::<Self, LIMIT>(body),
+        }
+    }
 }
 
 // Child index mapping (y increases upward or downward, both fine):
@@ -28,25 +615,389 @@ pub struct QuadTree<T: Coord> {
 // 1: (x >= cx, y < cy)
 // 2: (x < cx, y >= cy)
 // 3: (x >= cx, y >= cy)
+// LongestAxis packs the first cut into the high bit and the second into the
+// low bit, which on a square node is the same mapping.
 #[inline(always)]
-fn child_index_for_point<T: Coord>(b: &Rect<T>, p: &Point<T>) -> usize {
-    let cx = mid(b.min_x, b.max_x);
-    let cy = mid(b.min_y, b.max_y);
-    let x_ge = (p.x >= cx) as usize; // right half-bit
-    let y_ge = (p.y >= cy) as usize; // upper or lower half-bit
-    (y_ge << 1) | x_ge
+fn child_index_for_point<T: Coord>(b: &Rect<T>, p: &Point<T>, split: SplitStrategy) -> usize {
+    match split {
+        SplitStrategy::Quadrant => {
+            let cx = mid(b.min_x, b.max_x);
+            let cy = mid(b.min_y, b.max_y);
+            let x_ge = (p.x >= cx) as usize; // right half-bit
+            let y_ge = (p.y >= cy) as usize; // upper or lower half-bit
+            (y_ge << 1) | x_ge
+        }
+        SplitStrategy::LongestAxis => {
+            let (hi, half) = bisect_longest(b, p);
+            let (lo, _) = bisect_longest(&half, p);
+            ((hi as usize) << 1) | lo as usize
+        }
+    }
 }
 
-impl<T: Coord> QuadTree<T> {
-    pub fn new(boundary: Rect<T>, capacity: usize, max_depth: usize) -> Self {
-        QuadTree {
-            boundary,
+// Halves `b` across its longer side (y on a tie). Returns whether `p` is on
+// the upper side of the cut and the half that holds it.
+#[inline(always)]
+fn bisect_longest<T: Coord>(b: &Rect<T>, p: &Point<T>) -> (bool, Rect<T>) {
+    let mut half = *b;
+    if b.width() > b.height() {
+        let cx = mid(b.min_x, b.max_x);
+        let hi = p.x >= cx;
+        if hi { half.min_x = cx; } else { half.max_x = cx; }
+        (hi, half)
+    } else {
+        let cy = mid(b.min_y, b.max_y);
+        let hi = p.y >= cy;
+        if hi { half.min_y = cy; } else { half.max_y = cy; }
+        (hi, half)
+    }
+}
+
+// Child rects of `b` in child index order
+fn child_rects<T: Coord>(b: &Rect<T>, split: SplitStrategy) -> [Rect<T>; 4] {
+    match split {
+        SplitStrategy::Quadrant => {
+            let cx = mid(b.min_x, b.max_x);
+            let cy = mid(b.min_y, b.max_y);
+            [
+                Rect { min_x: b.min_x, min_y: b.min_y, max_x: cx,      max_y: cy      }, // 0
+                Rect { min_x: cx,      min_y: b.min_y, max_x: b.max_x, max_y: cy      }, // 1
+                Rect { min_x: b.min_x, min_y: cy,      max_x: cx,      max_y: b.max_y }, // 2
+                Rect { min_x: cx,      min_y: cy,      max_x: b.max_x, max_y: b.max_y }, // 3
+            ]
+        }
+        SplitStrategy::LongestAxis => {
+            let halves = |r: &Rect<T>| -> [Rect<T>; 2] {
+                let (mut lo, mut hi) = (*r, *r);
+                if r.width() > r.height() {
+                    let cx = mid(r.min_x, r.max_x);
+                    lo.max_x = cx;
+                    hi.min_x = cx;
+                } else {
+                    let cy = mid(r.min_y, r.max_y);
+                    lo.max_y = cy;
+                    hi.min_y = cy;
+                }
+                [lo, hi]
+            };
+            let [lo, hi] = halves(b);
+            let [a, b] = halves(&lo);
+            let [c, d] = halves(&hi);
+            [a, b, c, d]
+        }
+    }
+}
+
+// Z-order (Morton) key of a point relative to `bounds`.
+// Built by bisecting with the same rule used to route children, so the key
+// agrees with the tree's own child layout. 2 bits per level.
+#[inline]
+fn morton_key<T: Coord>(bounds: &Rect<T>, p: &Point<T>, split: SplitStrategy) -> u64 {
+    let mut b = *bounds;
+    let mut key = 0u64;
+    for _ in 0..32 {
+        let idx = match split {
+            SplitStrategy::Quadrant => {
+                let cx = mid(b.min_x, b.max_x);
+                let cy = mid(b.min_y, b.max_y);
+                let x_ge = p.x >= cx;
+                let y_ge = p.y >= cy;
+                if x_ge { b.min_x = cx; } else { b.max_x = cx; }
+                if y_ge { b.min_y = cy; } else { b.max_y = cy; }
+                ((y_ge as u64) << 1) | (x_ge as u64)
+            }
+            SplitStrategy::LongestAxis => {
+                let (hi, half) = bisect_longest(&b, p);
+                let (lo, quarter) = bisect_longest(&half, p);
+                b = quarter;
+                ((hi as u64) << 1) | (lo as u64)
+            }
+        };
+        key = (key << 2) | idx;
+    }
+    key
+}
+
+// Metric key (see `Metric`) from `p` to the farthest corner of `r`.
+fn max_key_point_to_rect<T: Coord>(metric: Metric, p: &Point<T>, r: &Rect<T>) -> T {
+    let dx = if p.x - r.min_x > r.max_x - p.x { p.x - r.min_x } else { r.max_x - p.x };
+    let dy = if p.y - r.min_y > r.max_y - p.y { p.y - r.min_y } else { r.max_y - p.y };
+    match metric {
+        Metric::Euclidean => dx * dx + dy * dy,
+        Metric::Manhattan => dx + dy,
+        Metric::Chebyshev => if dx > dy { dx } else { dy },
+    }
+}
+
+// Writes a coordinate as a JSON number. Whole values print without a
+// fraction so integer coords stay exact; JSON has no NaN or infinity, so
+// those become null.
+fn push_json_number<T: Coord + NumCast>(out: &mut String, v: T) {
+    if let Some(i) = <i64 as NumCast>::from(v) {
+        if <T as NumCast>::from(i) == Some(v) {
+            out.push_str(&i.to_string());
+            return;
+        }
+    }
+    match <f64 as NumCast>::from(v) {
+        Some(f) if f.is_finite() => out.push_str(&f.to_string()),
+        _ => out.push_str("null"),
+    }
+}
+
+// Dual-tree walk calling `f` for each item pair within sqrt(r2). With
+// `self_join`, `a` and `b` are the same tree and each unordered pair of
+// distinct items is reported once; a node is never paired with itself twice.
+fn for_each_pair_within<'a, T: Coord, F: FnMut(&'a Item<T>, &'a Item<T>)>(
+    a: &'a QuadTree<T>,
+    b: &'a QuadTree<T>,
+    r2: T,
+    self_join: bool,
+    mut f: F,
+) {
+    let mut stack: Vec<(&QuadTree<T>, &QuadTree<T>)> = vec![(a, b)];
+    while let Some((x, y)) = stack.pop() {
+        if dist_sq_rect_to_rect(&x.boundary, &y.boundary) > r2 {
+            continue;
+        }
+        let same = self_join && std::ptr::eq(x, y);
+        match (x.children.as_ref(), y.children.as_ref()) {
+            (None, None) if same => {
+                for (i, p) in x.items.iter().enumerate() {
+                    for q in &x.items[i + 1..] {
+                        if dist_sq_points(&p.point, &q.point) <= r2 {
+                            f(p, q);
+                        }
+                    }
+                }
+            }
+            (None, None) => {
+                for p in &x.items {
+                    for q in &y.items {
+                        if dist_sq_points(&p.point, &q.point) <= r2 {
+                            f(p, q);
+                        }
+                    }
+                }
+            }
+            (Some(kids), _) if same => {
+                for i in 0..4 {
+                    for j in i..4 {
+                        stack.push((&kids[i], &kids[j]));
+                    }
+                }
+            }
+            // Descend the wider side so the two nodes stay similar in size
+            (Some(xk), Some(yk)) => {
+                if x.boundary.width() >= y.boundary.width() {
+                    stack.extend(xk.iter().map(|c| (c, y)));
+                } else {
+                    stack.extend(yk.iter().map(|c| (x, c)));
+                }
+            }
+            (Some(xk), None) => stack.extend(xk.iter().map(|c| (c, y))),
+            (None, Some(yk)) => stack.extend(yk.iter().map(|c| (x, c))),
+        }
+    }
+}
+
+// A root boundary must have positive width and height. Splitting a zero
+// width or inverted rect only yields children that can never hold a point.
+// NaN edges fail the comparisons too.
+fn check_boundary<T: Coord>(b: &Rect<T>) -> Result<(), ConfigError> {
+    if b.min_x < b.max_x && b.min_y < b.max_y {
+        Ok(())
+    } else {
+        Err(ConfigError::InvalidBoundary)
+    }
+}
+
+// Chainable construction for `QuadTree`, started with `QuadTree::builder`.
+// capacity defaults to 16 and max_depth to 32. Thresholds left unset split
+// and merge at capacity, like `new`; set ones are checked as
+// `QuadTreeConfig::validate` does.
+#[derive(Copy, Clone, Debug)]
+pub struct QuadTreeBuilder<T: Coord> {
+    boundary: Rect<T>,
+    capacity: usize,
+    max_depth: usize,
+    split_threshold: Option<usize>,
+    merge_threshold: Option<usize>,
+    grow_limits: Option<(T, T)>,
+    split_strategy: SplitStrategy,
+}
+
+impl<T: Coord> QuadTreeBuilder<T> {
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub fn split_threshold(mut self, split_threshold: usize) -> Self {
+        self.split_threshold = Some(split_threshold);
+        self
+    }
+
+    pub fn merge_threshold(mut self, merge_threshold: usize) -> Self {
+        self.merge_threshold = Some(merge_threshold);
+        self
+    }
+
+    pub fn expandable(mut self, expandable: bool) -> Self
+    where
+        T: NumCast,
+    {
+        self.grow_limits = expandable.then(coord_limits::<T>);
+        self
+    }
+
+    pub fn split_strategy(mut self, split_strategy: SplitStrategy) -> Self {
+        self.split_strategy = split_strategy;
+        self
+    }
+
+    pub fn build(self) -> Result<QuadTree<T>, ConfigError> {
+        check_boundary(&self.boundary)?;
+        let capacity = self.capacity;
+        let config = QuadTreeConfig {
+            capacity,
+            split_threshold: self.split_threshold.unwrap_or(capacity),
+            max_depth: self.max_depth,
+            merge_threshold: self.merge_threshold.unwrap_or(capacity),
+        };
+        config.validate()?;
+        Ok(QuadTree {
+            boundary: self.boundary,
             items: Vec::with_capacity(capacity),
             capacity,
             children: None,
             depth: 0,
-            max_depth: max_depth,
+            max_depth: self.max_depth,
+            split_threshold: config.split_threshold,
+            merge_threshold: config.merge_threshold,
+            grow_limits: self.grow_limits,
+            next_id: 0,
+            split_strategy: self.split_strategy,
+        })
+    }
+}
+
+impl<T: Coord> QuadTree<T> {
+    pub fn builder(boundary: Rect<T>) -> QuadTreeBuilder<T> {
+        QuadTreeBuilder {
+            boundary,
+            capacity: 16,
+            max_depth: 32,
+            split_threshold: None,
+            merge_threshold: None,
+            grow_limits: None,
+            split_strategy: SplitStrategy::Quadrant,
+        }
+    }
+
+    // Panics if `boundary` is zero-width, zero-height or inverted; use
+    // `builder` or `new_with_config` to get an error instead.
+    //
+    // capacity 0 gives a pure spatial hash: every insert splits all the way
+    // to max_depth and lands in a max-depth leaf, which holds any number of
+    // items. Siblings merge back only once they are all empty.
+    pub fn new(boundary: Rect<T>, capacity: usize, max_depth: usize) -> Self {
+        match QuadTree::builder(boundary).capacity(capacity).max_depth(max_depth).build() {
+            Ok(tree) => tree,
+            Err(e) => panic!("invalid QuadTree boundary: {e}"),
+        }
+    }
+
+    // Like `new` with split and merge points decoupled from capacity.
+    // Rejects configs where split_threshold < capacity or
+    // merge_threshold > capacity, and boundaries `new` would panic on.
+    pub fn new_with_config(boundary: Rect<T>, config: QuadTreeConfig) -> Result<Self, ConfigError> {
+        QuadTree::builder(boundary)
+            .capacity(config.capacity)
+            .max_depth(config.max_depth)
+            .split_threshold(config.split_threshold)
+            .merge_threshold(config.merge_threshold)
+            .build()
+    }
+
+    pub fn config(&self) -> QuadTreeConfig {
+        QuadTreeConfig {
+            capacity: self.capacity,
+            split_threshold: self.split_threshold,
+            max_depth: self.max_depth,
+            merge_threshold: self.merge_threshold,
+        }
+    }
+
+    // Empty node with this node's sizing, for splits and root rebuilds
+    fn new_like(&self, boundary: Rect<T>, depth: usize, max_depth: usize) -> Self {
+        let mut node = QuadTree::new_child(boundary, self.capacity, depth, max_depth);
+        node.split_threshold = self.split_threshold;
+        node.merge_threshold = self.merge_threshold;
+        node.split_strategy = self.split_strategy;
+        node
+    }
+
+    // Builds a tree over the tight bounding box of `items`, grown by `padding`
+    // on every side, and inserts them all. Max edges are exclusive, so when
+    // padding doesn't clear the largest coordinate the edge moves up by one,
+    // or to the next representable value when one is below T's precision.
+    // Errors for negative, NaN or infinite padding, for non-finite points, and
+    // when the padded box or its width doesn't fit in T.
+    pub fn from_points_autofit(
+        items: &[Item<T>],
+        capacity: usize,
+        max_depth: usize,
+        padding: T,
+    ) -> Result<Self, ConfigError>
+    where
+        T: NumCast,
+    {
+        if !(padding >= T::zero() && (Point { x: padding, y: padding }).is_finite()) {
+            return Err(ConfigError::InvalidPadding);
+        }
+        if let Some(it) = items.iter().find(|it| !it.point.is_finite()) {
+            return Err(ConfigError::NonFinitePoint { id: it.id });
+        }
+        let (mut lo_x, mut lo_y, mut hi_x, mut hi_y) = match items.first() {
+            Some(it) => (it.point.x, it.point.y, it.point.x, it.point.y),
+            None => (T::zero(), T::zero(), T::zero(), T::zero()),
+        };
+        for it in items {
+            let p = it.point;
+            if p.x < lo_x { lo_x = p.x; }
+            if p.y < lo_y { lo_y = p.y; }
+            if p.x > hi_x { hi_x = p.x; }
+            if p.y > hi_y { hi_y = p.y; }
         }
+
+        let limits = coord_limits::<T>();
+        let min_edge = |v: T| step_within(v, padding, false, limits).ok_or(ConfigError::InvalidBoundary);
+        let max_edge = |v: T| match step_within(v, padding, true, limits) {
+            Some(e) if e > v => Ok(e),
+            Some(_) => next_above(v, limits).ok_or(ConfigError::InvalidBoundary),
+            None => Err(ConfigError::InvalidBoundary),
+        };
+        let boundary = Rect { min_x: min_edge(lo_x)?, min_y: min_edge(lo_y)?, max_x: max_edge(hi_x)?, max_y: max_edge(hi_y)? };
+        // Splitting needs the width and height themselves to fit in T
+        let two = T::one() + T::one();
+        let span_fits = |a: T, b: T| b / two - a / two + T::one() <= limits.1 / two;
+        if !span_fits(boundary.min_x, boundary.max_x) || !span_fits(boundary.min_y, boundary.max_y) {
+            return Err(ConfigError::InvalidBoundary);
+        }
+
+        let mut tree = QuadTree::builder(boundary).capacity(capacity).max_depth(max_depth).build()?;
+        for it in items {
+            if !tree.insert(*it) {
+                return Err(ConfigError::InvalidBoundary);
+            }
+        }
+        Ok(tree)
     }
 
     pub fn to_bytes(&self) -> Result<Vec<u8>, SerializationError>
@@ -57,11 +1008,7 @@ impl<T: Coord> QuadTree<T> {
     }
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, SerializationError>
     where
-        Self: for<'de> SchemaRead<
-            'de,
-            NativeDecodeConfig<{ DEFAULT_NATIVE_PREALLOCATION_LIMIT_BYTES }>,
-            Dst = Self,
-        >,
+        Self: NativeBody<{ DEFAULT_NATIVE_PREALLOCATION_LIMIT_BYTES }>,
     {
         decode_native(bytes, NATIVE_KIND_POINT)
     }
@@ -69,17 +1016,13 @@ impl<T: Coord> QuadTree<T> {
         bytes: &[u8],
     ) -> Result<Self, SerializationError>
     where
-        Self: for<'de> SchemaRead<'de, NativeDecodeConfig<LIMIT>, Dst = Self>,
+        Self: NativeBody<LIMIT>,
     {
         decode_native_with_preallocation_limit::<Self, LIMIT>(bytes, NATIVE_KIND_POINT)
     }
     pub fn from_bytes_unlimited(bytes: &[u8]) -> Result<Self, SerializationError>
     where
-        Self: for<'de> SchemaRead<
-            'de,
-            NativeDecodeConfig<{ wincode::config::PREALLOCATION_SIZE_LIMIT_DISABLED }>,
-            Dst = Self,
-        >,
+        Self: NativeBody<{ wincode::config::PREALLOCATION_SIZE_LIMIT_DISABLED }>,
     {
         decode_native_unlimited(bytes, NATIVE_KIND_POINT)
     }
@@ -92,62 +1035,211 @@ impl<T: Coord> QuadTree<T> {
             children: None,
             depth: depth,
             max_depth: max_depth,
+            split_threshold: capacity,
+            merge_threshold: capacity,
+            grow_limits: None,
+            next_id: 0,
+            split_strategy: SplitStrategy::Quadrant,
         }
     }
 
-    // Returns True if the item is inserted successfully
+    // Like `new`, but inserting outside the boundary grows the tree instead of
+    // failing. Each growth step adds a new root twice as wide and tall toward
+    // the point and re-parents the old root under it, so any finite point that
+    // T can cover is accepted. max_depth counts from the original root and
+    // goes up by one per step, so existing leaves keep their split budget.
+    pub fn new_expandable(boundary: Rect<T>, capacity: usize, max_depth: usize) -> Self
+    where
+        T: NumCast,
+    {
+        let builder = QuadTree::builder(boundary).capacity(capacity).max_depth(max_depth);
+        match builder.expandable(true).build() {
+            Ok(tree) => tree,
+            Err(e) => panic!("invalid QuadTree boundary: {e}"),
+        }
+    }
+
+    pub fn is_expandable(&self) -> bool {
+        self.grow_limits.is_some()
+    }
+
+    // Turns growth on or off. Only has an effect on the root.
+    pub fn set_expandable(&mut self, expandable: bool)
+    where
+        T: NumCast,
+    {
+        self.grow_limits = expandable.then(coord_limits::<T>);
+    }
+
+    // Returns True if the item is inserted successfully.
+    // A leaf at max_depth never splits and acts as an unbounded bucket,
+    // so any in-bounds point is accepted no matter how many share a cell.
+    // NaN or infinite coordinates are always rejected.
     pub fn insert(&mut self, item: Item<T>) -> bool {
-        if !self.boundary.contains(&item.point) {
+        item.point.is_finite() && self.insert_finite(item)
+    }
+
+    // Like `insert`, but says why a point was refused: Err for non-finite
+    // coordinates, Ok(false) for a finite point outside the boundary.
+    pub fn try_insert(&mut self, item: Item<T>) -> Result<bool, InsertError> {
+        if !item.point.is_finite() {
+            return Err(InsertError::NonFiniteCoordinate);
+        }
+        Ok(self.insert_finite(item))
+    }
+
+    // Like `insert`, but returns the depth of the leaf the item ended up in
+    // (root is 0), or None if it was rejected.
+    pub fn insert_depth(&mut self, item: Item<T>) -> Option<usize> {
+        if !self.insert(item) {
+            return None;
+        }
+        Some(self.leaf_for_point(&item.point).depth - self.depth)
+    }
+
+    // Inserts `point` under the next unused auto id and returns that id, or
+    // None if the point is rejected. Ids count up from 0 and are not reused
+    // after deletes; a rejected point does not use one up. `clear` starts the
+    // count over. Mixing this with explicit ids is allowed, but keeping the
+    // two ranges apart is up to the caller.
+    pub fn insert_auto(&mut self, point: Point<T>) -> Option<u64> {
+        let id = self.next_id;
+        if !self.insert(Item { id, point }) {
+            return None;
+        }
+        self.next_id += 1;
+        Some(id)
+    }
+
+    fn insert_finite(&mut self, item: Item<T>) -> bool {
+        let in_bounds = self.boundary.contains(&item.point)
+            || (self.grow_limits.is_some() && self.grow_to_fit(&item.point));
+        if !in_bounds {
             return false;
         }
 
         // If children is None, we are a leaf node
         if self.children.is_none() {
             // If we have room or we are at the max depth, store it here
-            if self.items.len() < self.capacity || self.depth >= self.max_depth {
+            if self.items.len() < self.split_threshold || self.depth >= self.max_depth {
                 // We have room to store it here
                 self.items.push(item);
                 return true;
             }
-            self.split();
+            self.split();
+        }
+
+        // Need to insert this item into the right child
+        // Internal node: delegate to a child
+        let idx = child_index_for_point(&self.boundary, &item.point, self.split_strategy);
+        if let Some(children) = self.children.as_mut() {
+            return children[idx].insert_finite(item);
+        }
+
+        return true;
+    }
+
+    pub fn split(&mut self){
+        // Create child rectangles
+        let quads = child_rects(&self.boundary, self.split_strategy);
+
+        // Allocate children
+        let d = self.depth + 1;
+        let mut kids: [QuadTree<T>; 4] = [
+            self.new_like(quads[0], d, self.max_depth),
+            self.new_like(quads[1], d, self.max_depth),
+            self.new_like(quads[2], d, self.max_depth),
+            self.new_like(quads[3], d, self.max_depth),
+        ];
+        // Move existing items down
+        for it in self.items.drain(..) {
+            let idx = child_index_for_point(&self.boundary, &it.point, self.split_strategy);
+            kids[idx].insert_finite(it);
+        }
+        self.children = Some(Box::new(kids));
+    }
+
+    // Forces uniform subdivision `depth` levels below this node, capped at
+    // max_depth, creating empty leaves as needed. Existing items move down and
+    // later inserts land in the pre-made cells. Memory grows as 4^depth leaves
+    // (depth 8 is already ~65k nodes). Deletes may merge cells back together.
+    pub fn pre_subdivide(&mut self, depth: usize) {
+        if depth == 0 || self.depth >= self.max_depth {
+            return;
+        }
+        if self.children.is_none() {
+            self.split();
+        }
+        if let Some(children) = self.children.as_mut() {
+            for child in children.iter_mut() {
+                child.pre_subdivide(depth - 1);
+            }
         }
+    }
 
-        // Need to insert this item into the right child
-        // Internal node: delegate to a child
-        let idx = child_index_for_point(&self.boundary, &item.point);
-        if let Some(children) = self.children.as_mut() {
-            return children[idx].insert(item);
+    // Loads `items` without N independent inserts: they are sorted along the
+    // Morton curve so each node's items form a contiguous run, then the tree is
+    // built top-down, splitting exactly the nodes an incremental build would.
+    // Existing items are kept and the tree is rebuilt around them. An
+    // expandable root first grows toward out-of-bounds points as insert would;
+    // points still outside the boundary are skipped. Returns the number of
+    // items loaded.
+    pub fn bulk_load(&mut self, items: &[Item<T>]) -> usize {
+        if self.grow_limits.is_some() {
+            for it in items {
+                if !self.boundary.contains(&it.point) {
+                    self.grow_to_fit(&it.point);
+                }
+            }
         }
 
-        return true;
+        let mut all = Vec::with_capacity(self.count_items() + items.len());
+        self.take_all_items(&mut all);
+        let existing = all.len();
+        all.extend(items.iter().filter(|it| it.point.is_finite() && self.boundary.contains(&it.point)));
+        let loaded = all.len() - existing;
+
+        let (bounds, split) = (self.boundary, self.split_strategy);
+        let mut keyed: Vec<(u64, Item<T>)> = all
+            .into_iter()
+            .map(|it| (morton_key(&bounds, &it.point, split), it))
+            .collect();
+        // Stable, so items sharing a cell keep their input order
+        keyed.sort_by_key(|&(key, _)| key);
+        let mut sorted: Vec<Item<T>> = keyed.into_iter().map(|(_, it)| it).collect();
+        self.build_sorted(&mut sorted, 0);
+        loaded
     }
 
-    pub fn split(&mut self){
-        // Create child rectangles
-        let cx = mid(self.boundary.min_x, self.boundary.max_x);
-        let cy = mid(self.boundary.min_y, self.boundary.max_y);
-
-        let quads = [
-            Rect { min_x: self.boundary.min_x, min_y: self.boundary.min_y, max_x: cx,               max_y: cy               }, // 0
-            Rect { min_x: cx,                    min_y: self.boundary.min_y, max_x: self.boundary.max_x, max_y: cy               }, // 1
-            Rect { min_x: self.boundary.min_x,   min_y: cy,                  max_x: cx,               max_y: self.boundary.max_y }, // 2
-            Rect { min_x: cx,                    min_y: cy,                  max_x: self.boundary.max_x, max_y: self.boundary.max_y }, // 3
-        ];
+    // Rebuilds the tree from its live items, dropping split nodes that deletes
+    // left empty or sparse. The result matches a fresh `bulk_load` of the same
+    // items; boundary, capacity and max_depth are kept.
+    pub fn rebuild(&mut self) {
+        self.bulk_load(&[]);
+    }
 
-        // Allocate children
-        let d = self.depth + 1;
-        let mut kids: [QuadTree<T>; 4] = [
-            QuadTree::new_child(quads[0], self.capacity, d, self.max_depth),
-            QuadTree::new_child(quads[1], self.capacity, d, self.max_depth),
-            QuadTree::new_child(quads[2], self.capacity, d, self.max_depth),
-            QuadTree::new_child(quads[3], self.capacity, d, self.max_depth),
-        ];
-        // Move existing items down
-        for it in self.items.drain(..) {
-            let idx = child_index_for_point(&self.boundary, &it.point);
-            kids[idx].insert(it);
+    // Fills an empty leaf from a run already ordered by child index at `level`.
+    fn build_sorted(&mut self, items: &mut [Item<T>], level: usize) {
+        if items.len() <= self.split_threshold || self.depth >= self.max_depth {
+            self.items.extend_from_slice(items);
+            return;
+        }
+        // Morton keys only cover 32 levels; below that, order this run by child
+        if level >= 32 {
+            let (boundary, split) = (self.boundary, self.split_strategy);
+            items.sort_by_key(|it| child_index_for_point(&boundary, &it.point, split));
+        }
+
+        self.split();
+        let (boundary, split) = (self.boundary, self.split_strategy);
+        let Some(children) = self.children.as_mut() else { return; };
+        let mut rest = items;
+        for (i, child) in children.iter_mut().enumerate() {
+            let n = rest.partition_point(|it| child_index_for_point(&boundary, &it.point, split) <= i);
+            let (run, tail) = rest.split_at_mut(n);
+            child.build_sorted(run, level + 1);
+            rest = tail;
         }
-        self.children = Some(Box::new(kids));
     }
 
     #[inline(always)]
@@ -156,7 +1248,107 @@ impl<T: Coord> QuadTree<T> {
         a.max_x >= b.max_x && a.max_y >= b.max_y
     }
 
+    // False if `range` lies entirely outside the root, in which case no rect
+    // query can return anything. Touching edges count as outside, as in
+    // `Rect::intersects`.
+    pub fn bounds_intersect(&self, range: &Rect<T>) -> bool {
+        self.boundary.intersects(range)
+    }
+
+    // Items in `range` in traversal order, which is unspecified and may
+    // change between versions; use `query_sorted` for a stable order.
+    // Half-open like `Rect::contains` (min <= p < max), as are the other
+    // rect queries, so tiles that share an edge never both report a point.
+    // `query_inclusive` closes the max edges; circle queries are closed.
     pub fn query(&self, range: Rect<T>) -> Vec<(u64, T, T)> {
+        if !self.bounds_intersect(&range) {
+            return Vec::new();
+        }
+        let mut out: Vec<(u64, T, T)> = Vec::with_capacity(128);
+        self.query_into(range, &mut out);
+        out
+    }
+
+    // Same results as `query`, written into `out` after clearing it. Reusing
+    // one buffer across calls avoids an allocation per query.
+    pub fn query_into(&self, range: Rect<T>, out: &mut Vec<(u64, T, T)>) {
+        out.clear();
+        self.for_each_in_rect(range, |it| out.push((it.id, it.point.x, it.point.y)));
+    }
+
+    // Like `query`, but closed on every edge: points exactly on max_x or
+    // max_y are included too. Meant for picking; tiling should use `query`.
+    pub fn query_inclusive(&self, range: Rect<T>) -> Vec<Item<T>> {
+        let inside = |p: &Point<T>| {
+            p.x >= range.min_x && p.x <= range.max_x && p.y >= range.min_y && p.y <= range.max_y
+        };
+        let mut out = Vec::new();
+        let mut stack: SmallVec<[&QuadTree<T>; 64]> = SmallVec::new();
+        stack.push(self);
+        while let Some(node) = stack.pop() {
+            let b = &node.boundary;
+            // Node max edges stay exclusive, but a node starting exactly on
+            // a closed range edge can still hold matches
+            if b.min_x > range.max_x || b.max_x <= range.min_x
+                || b.min_y > range.max_y || b.max_y <= range.min_y
+            {
+                continue;
+            }
+            match node.children.as_ref() {
+                Some(children) => stack.extend(children.iter()),
+                None if Self::rect_contains_rect(&range, b) => out.extend_from_slice(&node.items),
+                None => out.extend(node.items.iter().filter(|it| inside(&it.point))),
+            }
+        }
+        out
+    }
+
+    // Ids of the items in `range`, in `query` order
+    pub fn query_ids(&self, range: Rect<T>) -> Vec<u64> {
+        let mut out = Vec::new();
+        self.for_each_in_rect(range, |it| out.push(it.id));
+        out
+    }
+
+    // Up to `limit` items in `range`, stopping the walk once that many are
+    // found. Which items come back when more than `limit` match is
+    // unspecified.
+    pub fn query_limit(&self, range: Rect<T>, limit: usize) -> Vec<(u64, T, T)> {
+        let mut out = Vec::with_capacity(limit.min(128));
+        if limit == 0 {
+            return out;
+        }
+        let _ = self.try_for_each_in_rect(range, |it| {
+            out.push((it.id, it.point.x, it.point.y));
+            if out.len() < limit { ControlFlow::Continue(()) } else { ControlFlow::Break(()) }
+        });
+        out
+    }
+
+    // True if any item in `range` satisfies `pred`. Stops at the first match.
+    pub fn any_in_rect<F: FnMut(&Item<T>) -> bool>(&self, range: Rect<T>, mut pred: F) -> bool {
+        self.try_for_each_in_rect(range, |it| {
+            if pred(it) { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+        })
+        .is_break()
+    }
+
+    // Calls `f` for every item in `range`, in the same order `query` returns
+    // them, without building an intermediate Vec.
+    pub fn for_each_in_rect<F: FnMut(&Item<T>)>(&self, range: Rect<T>, mut f: F) {
+        let _ = self.try_for_each_in_rect(range, |it| {
+            f(it);
+            ControlFlow::Continue(())
+        });
+    }
+
+    // Like `for_each_in_rect`, but the whole walk halts as soon as `f`
+    // returns Break. Returns Break if it was cut short.
+    pub fn try_for_each_in_rect<F: FnMut(&Item<T>) -> ControlFlow<()>>(
+        &self,
+        range: Rect<T>,
+        mut f: F,
+    ) -> ControlFlow<()> {
         #[derive(Copy, Clone)]
         enum Mode { Filter, ReportAll }
 
@@ -166,7 +1358,6 @@ impl<T: Coord> QuadTree<T> {
         let rx1 = range.max_x;
         let ry1 = range.max_y;
 
-        let mut out: Vec<(u64, T, T)> = Vec::with_capacity(128);
         let mut stack: SmallVec<[(&QuadTree<T>, Mode); 64]> = SmallVec::new();
         stack.push((self, Mode::Filter));
 
@@ -181,10 +1372,10 @@ impl<T: Coord> QuadTree<T> {
                         stack.push((&children[2], Mode::ReportAll));
                         stack.push((&children[3], Mode::ReportAll));
                     } else {
-                        // Leaf: append all items, no per-point test
-                        let items = &node.items;
-                        out.reserve(items.len());
-                        out.extend(items.iter().map(|it| (it.id, it.point.x, it.point.y)));
+                        // Leaf: visit all items, no per-point test
+                        for it in &node.items {
+                            f(it)?;
+                        }
                     }
                 }
 
@@ -213,47 +1404,485 @@ impl<T: Coord> QuadTree<T> {
                         if range.intersects(&c3.boundary) { stack.push((c3, Mode::Filter)); }
                     } else {
                         // Leaf scan with tight predicate
-                        let items = &node.items;
-                        // Reserve a little to reduce reallocs if many will pass
-                        out.reserve(items.len().min(64));
-                        for it in items {
+                        for it in &node.items {
                             let p = &it.point;
                             if p.x >= rx0 && p.x < rx1 && p.y >= ry0 && p.y < ry1 {
-                                out.push((it.id, p.x, p.y));
+                                f(it)?;
                             }
                         }
                     }
                 }
             }
         }
+        ControlFlow::Continue(())
+    }
+
+    // Number of items in `range` without collecting them. Fully covered
+    // subtrees are counted wholesale; only partially overlapping leaves test
+    // individual points.
+    pub fn count_in_rect(&self, range: Rect<T>) -> usize {
+        if !self.bounds_intersect(&range) {
+            return 0;
+        }
+        let mut count = 0;
+        let mut stack: SmallVec<[&QuadTree<T>; 64]> = SmallVec::new();
+        stack.push(self);
+        while let Some(node) = stack.pop() {
+            if !range.intersects(&node.boundary) {
+                continue;
+            }
+            if Self::rect_contains_rect(&range, &node.boundary) {
+                count += node.count_items();
+                continue;
+            }
+            match node.children.as_ref() {
+                Some(children) => stack.extend(children.iter()),
+                None => count += node.items.iter().filter(|it| range.contains(&it.point)).count(),
+            }
+        }
+        count
+    }
+
+    // Mean position of the items in `range`, or None if there are none.
+    // Sums are taken in f64 and the result stays f64, so integer coords
+    // don't lose the fractional part.
+    pub fn centroid_in_rect(&self, range: Rect<T>) -> Option<Point<f64>>
+    where
+        T: NumCast,
+    {
+        let to_f64 = |v: T| -> f64 { NumCast::from(v).unwrap_or(f64::NAN) };
+        let (mut sx, mut sy, mut n) = (0.0f64, 0.0f64, 0usize);
+        self.for_each_in_rect(range, |it| {
+            sx += to_f64(it.point.x);
+            sy += to_f64(it.point.y);
+            n += 1;
+        });
+        if n == 0 {
+            return None;
+        }
+        Some(Point { x: sx / n as f64, y: sy / n as f64 })
+    }
+
+    // Tight bounding box of the items in `range`, or None if there are none.
+    // The max edges are the largest coordinates found, so unlike a tree
+    // boundary they are inclusive; a single item gives a zero-area rect.
+    pub fn extent_in_rect(&self, range: Rect<T>) -> Option<Rect<T>> {
+        let mut extent: Option<Rect<T>> = None;
+        self.for_each_in_rect(range, |it| {
+            let p = it.point;
+            let e = extent.get_or_insert(Rect { min_x: p.x, min_y: p.y, max_x: p.x, max_y: p.y });
+            if p.x < e.min_x { e.min_x = p.x; }
+            if p.y < e.min_y { e.min_y = p.y; }
+            if p.x > e.max_x { e.max_x = p.x; }
+            if p.y > e.max_y { e.max_y = p.y; }
+        });
+        extent
+    }
+
+    // Item counts binned into a rows x cols grid over the boundary, row-major
+    // with row 0 at min_y. Subtrees that fall inside one cell are counted
+    // wholesale; only nodes straddling a cell edge are split further, down
+    // to per-point binning in leaves. Empty if rows or cols is zero.
+    pub fn density_grid(&self, rows: usize, cols: usize) -> Vec<u32>
+    where
+        T: NumCast,
+    {
+        if rows == 0 || cols == 0 {
+            return Vec::new();
+        }
+        let mut grid = vec![0u32; rows * cols];
+        let b = self.boundary;
+        let to_f64 = |v: T| -> f64 { NumCast::from(v).unwrap_or(f64::NAN) };
+        let (x0, y0) = (to_f64(b.min_x), to_f64(b.min_y));
+        let (w, h) = (to_f64(b.max_x) - x0, to_f64(b.max_y) - y0);
+        // Position along an axis in cell units
+        let fx = |v: T| (to_f64(v) - x0) / w * cols as f64;
+        let fy = |v: T| (to_f64(v) - y0) / h * rows as f64;
+        let cell = |f: f64, n: usize| (f.max(0.0) as usize).min(n - 1);
+
+        let mut stack: SmallVec<[&QuadTree<T>; 64]> = SmallVec::new();
+        stack.push(self);
+        while let Some(node) = stack.pop() {
+            let nb = &node.boundary;
+            let (c, r) = (cell(fx(nb.min_x), cols), cell(fy(nb.min_y), rows));
+            // Max edges are exclusive, so ending exactly on a cell edge still fits
+            if fx(nb.max_x) <= (c + 1) as f64 && fy(nb.max_y) <= (r + 1) as f64 {
+                grid[r * cols + c] += node.count_items() as u32;
+                continue;
+            }
+            match node.children.as_ref() {
+                Some(children) => stack.extend(children.iter()),
+                None => {
+                    for it in &node.items {
+                        let (c, r) = (cell(fx(it.point.x), cols), cell(fy(it.point.y), rows));
+                        grid[r * cols + c] += 1;
+                    }
+                }
+            }
+        }
+        grid
+    }
+
+    // Counts items in `range` whose id is in `allowed`, in a single traversal
+    pub fn count_in_rect_filtered(&self, range: Rect<T>, allowed: &HashSet<u64>) -> usize {
+        let mut count = 0;
+        let mut stack: SmallVec<[&QuadTree<T>; 64]> = SmallVec::new();
+        stack.push(self);
+        while let Some(node) = stack.pop() {
+            if !range.intersects(&node.boundary) {
+                continue;
+            }
+            if let Some(children) = node.children.as_ref() {
+                stack.extend(children.iter());
+                continue;
+            }
+            // Fully covered leaves skip the per-point test
+            let covered = Self::rect_contains_rect(&range, &node.boundary);
+            count += node
+                .items
+                .iter()
+                .filter(|it| (covered || range.contains(&it.point)) && allowed.contains(&it.id))
+                .count();
+        }
+        count
+    }
+
+    // Reduces `tag(id)` over the items in `range` in a single traversal. Tags
+    // are supplied by the caller; items without one are skipped. Returns None
+    // when no tagged item lies in the range.
+    pub fn reduce_tag_in_rect(
+        &self,
+        range: Rect<T>,
+        op: ReduceOp,
+        tag: impl Fn(u64) -> Option<f64>,
+    ) -> Option<f64> {
+        let mut acc = match op {
+            ReduceOp::Sum | ReduceOp::Mean => 0.0,
+            ReduceOp::Min => f64::INFINITY,
+            ReduceOp::Max => f64::NEG_INFINITY,
+        };
+        let mut count = 0usize;
+
+        let mut stack: SmallVec<[&QuadTree<T>; 64]> = SmallVec::new();
+        stack.push(self);
+        while let Some(node) = stack.pop() {
+            if !range.intersects(&node.boundary) {
+                continue;
+            }
+            if let Some(children) = node.children.as_ref() {
+                stack.extend(children.iter());
+                continue;
+            }
+            let covered = Self::rect_contains_rect(&range, &node.boundary);
+            for it in &node.items {
+                if !(covered || range.contains(&it.point)) {
+                    continue;
+                }
+                let Some(v) = tag(it.id) else { continue; };
+                count += 1;
+                acc = match op {
+                    ReduceOp::Sum | ReduceOp::Mean => acc + v,
+                    ReduceOp::Min => acc.min(v),
+                    ReduceOp::Max => acc.max(v),
+                };
+            }
+        }
+
+        match (count, op) {
+            (0, _) => None,
+            (n, ReduceOp::Mean) => Some(acc / n as f64),
+            _ => Some(acc),
+        }
+    }
+
+    // Items on the non-negative side of the line through `point` with the given
+    // `normal`, i.e. (p - point) . normal >= 0. Points on the line are included.
+    // Nodes entirely on one side are skipped or reported without per-item tests.
+    pub fn query_halfplane(&self, point: Point<T>, normal: Point<T>) -> Vec<Item<T>> {
+        #[derive(Copy, Clone)]
+        enum Mode { Filter, ReportAll }
+
+        let side = |x: T, y: T| (x - point.x) * normal.x + (y - point.y) * normal.y;
+        let zero = T::zero();
+
+        let mut out = Vec::new();
+        let mut stack: SmallVec<[(&QuadTree<T>, Mode); 64]> = SmallVec::new();
+        stack.push((self, Mode::Filter));
+
+        while let Some((node, mode)) = stack.pop() {
+            let mode = match mode {
+                Mode::ReportAll => Mode::ReportAll,
+                Mode::Filter => {
+                    // The signed distance is linear, so its extremes over the
+                    // node lie on the corners
+                    let b = &node.boundary;
+                    let corners = [
+                        side(b.min_x, b.min_y),
+                        side(b.max_x, b.min_y),
+                        side(b.min_x, b.max_y),
+                        side(b.max_x, b.max_y),
+                    ];
+                    if corners.iter().all(|&d| d < zero) {
+                        continue;
+                    }
+                    if corners.iter().all(|&d| d >= zero) { Mode::ReportAll } else { Mode::Filter }
+                }
+            };
+
+            if let Some(children) = node.children.as_ref() {
+                for c in children.iter() {
+                    stack.push((c, mode));
+                }
+            } else {
+                match mode {
+                    Mode::ReportAll => out.extend_from_slice(&node.items),
+                    Mode::Filter => out.extend(
+                        node.items.iter().filter(|it| side(it.point.x, it.point.y) >= zero),
+                    ),
+                }
+            }
+        }
+        out
+    }
+
+    // Same matches as `query`, ordered along a Z-order (Morton) curve over the
+    // tree bounds so consecutive results are spatially close.
+    pub fn query_morton_ordered(&self, range: Rect<T>) -> Vec<Item<T>> {
+        let mut keyed: Vec<(u64, Item<T>)> = self
+            .query(range)
+            .into_iter()
+            .map(|(id, x, y)| {
+                let point = Point { x, y };
+                (morton_key(&self.boundary, &point, SplitStrategy::Quadrant), Item { id, point })
+            })
+            .collect();
+        keyed.sort_unstable_by_key(|&(key, it)| (key, it.id));
+        keyed.into_iter().map(|(_, it)| it).collect()
+    }
 
+    // Same matches as `query`, sorted by (id, x, y). Unlike `query`, whose
+    // order follows the tree layout and may change between versions, this
+    // order is stable and safe to snapshot.
+    pub fn query_sorted(&self, range: Rect<T>) -> Vec<Item<T>> {
+        let mut out: Vec<Item<T>> = Vec::new();
+        self.for_each_in_rect(range, |it| out.push(*it));
+        // Stored coordinates are always finite, so partial_cmp never fails
+        out.sort_unstable_by(|a, b| {
+            a.id.cmp(&b.id)
+                .then_with(|| a.point.x.partial_cmp(&b.point.x).unwrap_or(std::cmp::Ordering::Equal))
+                .then_with(|| a.point.y.partial_cmp(&b.point.y).unwrap_or(std::cmp::Ordering::Equal))
+        });
         out
     }
 
-     // Default: unbounded search when max_distance == 0
+     // One `nearest_neighbor` per query point, in input order
+    pub fn nearest_neighbor_batch(&self, points: &[Point<T>]) -> Vec<Option<Item<T>>> {
+        points.iter().map(|&p| self.nearest_neighbor(p)).collect()
+    }
+
+    // Approximate k nearest, ascending by distance with ties by ascending id.
+    // Items sharing an id each take a slot, as in `nearest_neighbors`. Once k
+    // candidates are held, a node is skipped if it is farther than the k-th
+    // candidate's distance / (1 + epsilon), so the k-th result is at most
+    // (1 + epsilon) times farther than the true k-th nearest. epsilon = 0 gives
+    // the exact `nearest_neighbors` result; negative or NaN is treated as 0.
+    pub fn nearest_neighbors_approx(&self, point: Point<T>, k: usize, epsilon: f64) -> Vec<Item<T>>
+    where
+        T: NumCast,
+    {
+        if k == 0 {
+            return Vec::new();
+        }
+        let to_f64 = |v: T| -> f64 { NumCast::from(v).unwrap_or(f64::NAN) };
+        let slack = (1.0 + epsilon.max(0.0)).powi(2);
+
+        // Ascending by (squared distance, id), at most k entries
+        let mut best: Vec<(T, Item<T>)> = Vec::with_capacity(k + 1);
+        let mut stack: Vec<(&QuadTree<T>, T)> = vec![(self, dist_sq_point_to_rect(&point, &self.boundary))];
+
+        while let Some((node, node_d2)) = stack.pop() {
+            if best.len() == k && to_f64(node_d2) * slack > to_f64(best[k - 1].0) {
+                continue;
+            }
+            if let Some(children) = node.children.as_ref() {
+                // Push children farthest-first so nearest is popped first
+                let mut kids: SmallVec<[(&QuadTree<T>, T); 4]> = children
+                    .iter()
+                    .map(|c| (c, dist_sq_point_to_rect(&point, &c.boundary)))
+                    .collect();
+                kids.sort_unstable_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+                stack.extend(kids);
+                continue;
+            }
+            for it in &node.items {
+                let d2 = dist_sq_points(&point, &it.point);
+                // An exact tie with the k-th candidate goes to the lower id
+                let before = |&(b, bi): &(T, Item<T>)| b < d2 || (b == d2 && bi.id <= it.id);
+                if best.len() == k && before(&best[k - 1]) {
+                    continue;
+                }
+                let at = best.partition_point(before);
+                best.insert(at, (d2, *it));
+                best.truncate(k);
+            }
+        }
+        best.into_iter().map(|(_, it)| it).collect()
+    }
+
+    // Same results as `nearest_neighbors`, each paired with its Euclidean
+    // (not squared) distance to `point`, computed in f64. Ascending.
+    pub fn nearest_neighbors_with_dist(&self, point: Point<T>, k: usize) -> Vec<(Item<T>, f64)>
+    where
+        T: NumCast,
+    {
+        let to_f64 = |v: T| -> f64 { NumCast::from(v).unwrap_or(f64::NAN) };
+        let (px, py) = (to_f64(point.x), to_f64(point.y));
+        self.nearest_neighbors(point, k)
+            .into_iter()
+            .map(|it| (it, (to_f64(it.point.x) - px).hypot(to_f64(it.point.y) - py)))
+            .collect()
+    }
+
+    // Nearest item for which `pred` returns true. Branch-and-bound as usual:
+    // rejected items never tighten the bound, so pruning stays correct. The
+    // predicate is only called for items that would beat the current best.
+    pub fn nearest_neighbor_filter<F: Fn(&Item<T>) -> bool>(
+        &self,
+        point: Point<T>,
+        pred: F,
+    ) -> Option<Item<T>> {
+        self.nearest_by_metric(point, Metric::Euclidean, pred)
+    }
+
+    // Nearest item under `metric`. Among equidistant items the lowest id wins,
+    // as in `nearest_neighbor`.
+    pub fn nearest_neighbor_metric(&self, point: Point<T>, metric: Metric) -> Option<Item<T>> {
+        self.nearest_by_metric(point, metric, |_| true)
+    }
+
+    fn nearest_by_metric<F: Fn(&Item<T>) -> bool>(
+        &self,
+        point: Point<T>,
+        metric: Metric,
+        pred: F,
+    ) -> Option<Item<T>> {
+        let mut best: Option<(T, Item<T>)> = None;
+        let mut stack: Vec<(&QuadTree<T>, T)> = vec![(self, metric.key_point_to_rect(&point, &self.boundary))];
+
+        while let Some((node, node_d2)) = stack.pop() {
+            if best.is_some_and(|(b, _)| node_d2 > b) {
+                continue;
+            }
+            if let Some(children) = node.children.as_ref() {
+                // Push children farthest-first so nearest is popped first
+                let mut kids: SmallVec<[(&QuadTree<T>, T); 4]> = children
+                    .iter()
+                    .map(|c| (c, metric.key_point_to_rect(&point, &c.boundary)))
+                    .collect();
+                kids.sort_unstable_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+                stack.extend(kids);
+            } else {
+                for it in &node.items {
+                    let d2 = metric.key_points(&point, &it.point);
+                    let closer = best.is_none_or(|(b, bi)| d2 < b || (d2 == b && it.id < bi.id));
+                    if closer && pred(it) {
+                        best = Some((d2, *it));
+                    }
+                }
+            }
+        }
+        best.map(|(_, it)| it)
+    }
+
+    // Default: unbounded search when max_distance == 0.
+    // Among equidistant items the lowest id wins, so the result does not
+    // depend on traversal order. `nearest_neighbors` breaks ties the same way.
     pub fn nearest_neighbor(&self, point: Point<T>) -> Option<Item<T>> {
         self.nearest_neighbors_within(point, 1, T::zero())
             .into_iter()
             .next()
     }
 
-    // Default: unbounded search when max_distance == 0
+    // Up to k items sorted ascending by distance. The result has exactly
+    // min(k, count_items()) entries, so a short result means the tree ran out
+    // of items. Items sharing an id each take a slot; use
+    // `nearest_distinct_ids` for one entry per id. k == 0 returns an empty
+    // Vec. Equidistant items come out in ascending id order.
     pub fn nearest_neighbors(&self, point: Point<T>, k: usize) -> Vec<Item<T>> {
         self.nearest_neighbors_within(point, k, T::zero())
     }
 
+    // Lazily yields every item nearest first, so callers can stop on any
+    // condition instead of picking k up front. See `NearestCursor`.
+    pub fn nearest_iter(&self, point: Point<T>) -> NearestIter<'_, T> {
+        NearestIter { tree: self, cursor: NearestCursor::new(point) }
+    }
+
+    // Items in `range` sorted by ascending distance to `focus`, ties by
+    // ascending id. Built best-first, so stopping early with
+    // `NearestCursor::in_rect` never sorts the whole match set.
+    pub fn query_ordered_by(&self, range: Rect<T>, focus: Point<T>) -> Vec<Item<T>> {
+        let mut cursor = NearestCursor::in_rect(focus, range);
+        let mut out = Vec::new();
+        while let Some(it) = cursor.next_item(self) {
+            out.push(it);
+        }
+        out
+    }
+
+    // Nearest k items with distinct ids, keeping the closest position per id.
+    // Useful when an id was inserted at several (possibly stale) positions.
+    // Walks items best-first and drops every later copy of an id it has seen,
+    // so duplicates never take a slot; ties go to the lower id.
+    pub fn nearest_distinct_ids(&self, point: Point<T>, k: usize) -> Vec<Item<T>> {
+        let mut cursor = NearestCursor::new(point);
+        let mut seen = HashSet::new();
+        let mut out = Vec::with_capacity(k);
+        while out.len() < k {
+            let Some(it) = cursor.next_item(self) else { break; };
+            if seen.insert(it.id) {
+                out.push(it);
+            }
+        }
+        out
+    }
+
+    // Up to k items strictly closer than `max_distance`, nearest first.
+    // The distance cap also prunes subtrees during the search.
     // If max_distance == 0, treat as "no max"
     pub fn nearest_neighbors_within(
         &self,
         point: Point<T>,
         k: usize,
         max_distance: T,
+    ) -> Vec<Item<T>> {
+        self.nearest_neighbors_within_scratch(point, k, max_distance, &mut QueryScratch::new())
+    }
+
+    // Same results as `nearest_neighbors`, reusing `scratch` for the search
+    // stack and picked-item set instead of allocating them per call.
+    pub fn nearest_neighbors_with_scratch(
+        &self,
+        point: Point<T>,
+        k: usize,
+        scratch: &mut QueryScratch<T>,
+    ) -> Vec<Item<T>> {
+        self.nearest_neighbors_within_scratch(point, k, T::zero(), scratch)
+    }
+
+    fn nearest_neighbors_within_scratch(
+        &self,
+        point: Point<T>,
+        k: usize,
+        max_distance: T,
+        scratch: &mut QueryScratch<T>,
     ) -> Vec<Item<T>> {
         if k == 0 {
             return Vec::new();
         }
 
-        let mut picked = std::collections::HashSet::<u64>::new();
+        let QueryScratch { stack, kids, picked } = scratch;
+        picked.clear();
         let mut out = Vec::with_capacity(k);
 
         // Fixed cap for all iterations
@@ -264,128 +1893,633 @@ impl<T: Coord> QuadTree<T> {
         };
 
         for _ in 0..k {
-            // stack holds (node_ref, bbox_distance_sq)
-            let mut stack: Vec<(&QuadTree<T>, T)> = Vec::new();
-            stack.push((self, dist_sq_point_to_rect(&point, &self.boundary)));
+            stack.clear();
+            stack.push((NodePath::new(), dist_sq_point_to_rect(&point, &self.boundary)));
 
-            let mut best: Option<Item<T>> = None;
+            let mut best: Option<(Item<T>, usize)> = None;
             // Best for this iteration, starts at the cap (or None for unbounded)
             let mut best_d2: Option<T> = cap2;
 
-            while let Some((node, node_d2)) = stack.pop() {
+            while let Some((path, node_d2)) = stack.pop() {
                 // prune if strictly farther than current best
                 if let Some(b) = best_d2 {
                     if node_d2 > b {
                         continue;
                     }
                 }
+                let Some(node) = self.node_at(&path) else { continue; };
 
                 if let Some(children) = node.children.as_ref() {
                     // Push children farthest-first so nearest is popped first
-                    let mut kids: Vec<(&QuadTree<T>, T)> = children
-                        .iter()
-                        .map(|c| (c, dist_sq_point_to_rect(&point, &c.boundary)))
-                        .filter(|&(_, d2)| best_d2.map(|b| d2 < b).unwrap_or(true))
-                        .collect();
+                    kids.clear();
+                    kids.extend(
+                        children
+                            .iter()
+                            .enumerate()
+                            .map(|(i, c)| (i as u8, dist_sq_point_to_rect(&point, &c.boundary)))
+                            // Keep equal-distance nodes: they may hold a lower-id tie
+                            .filter(|&(_, d2)| best_d2.map(|b| d2 <= b).unwrap_or(true)),
+                    );
 
                     kids.sort_unstable_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-                    for e in kids {
-                        stack.push(e);
+                    for &(i, d2) in kids.iter() {
+                        let mut child = path.clone();
+                        child.push(i);
+                        stack.push((child, d2));
+                    }
+                } else {
+                    // leaf scan
+                    for it in &node.items {
+                        // Picked items are told apart by address, which is
+                        // stable while self is borrowed, so copies sharing an
+                        // id each get their own slot
+                        let slot = it as *const Item<T> as usize;
+                        if picked.contains(&slot) {
+                            continue;
+                        }
+                        let d2 = dist_sq_points(&point, &it.point);
+                        // strict < keeps cap exclusive; an exact tie with an
+                        // already chosen item goes to the lower id
+                        let closer = match (best_d2, best) {
+                            (None, _) => true,
+                            (Some(b), Some((bi, _))) if d2 == b => it.id < bi.id,
+                            (Some(b), _) => d2 < b,
+                        };
+                        if closer {
+                            best_d2 = Some(d2);
+                            best = Some((*it, slot));
+                        }
+                    }
+                }
+            }
+
+            if let Some((it, slot)) = best {
+                picked.insert(slot);
+                out.push(it);
+                // Note: do NOT tighten cap here
+            } else {
+                break;
+            }
+        }
+
+        debug_assert!(out.len() <= k);
+        out
+    }
+
+    // Up to k items with the highest `score(item, distance)`, best first.
+    // Arbitrary scores defeat distance pruning, so candidates are gathered in
+    // rings of doubling radius, starting at the k-th nearest neighbor, until the
+    // top-k ids are unchanged after an expansion or the ring covers the whole
+    // tree. An item that would only win from beyond the last ring is not seen,
+    // so scores should decay with distance for exact results.
+    pub fn top_k_scored(
+        &self,
+        point: Point<T>,
+        k: usize,
+        score: impl Fn(&Item<T>, f64) -> f64,
+    ) -> Vec<Item<T>>
+    where
+        T: NumCast,
+    {
+        if k == 0 {
+            return Vec::new();
+        }
+        let Some(kth) = self.nearest_neighbors(point, k).last().copied() else {
+            return Vec::new();
+        };
+
+        let to_f64 = |v: T| -> f64 { NumCast::from(v).unwrap_or(f64::NAN) };
+        let (px, py) = (to_f64(point.x), to_f64(point.y));
+        let dist = |p: &Point<T>| (to_f64(p.x) - px).hypot(to_f64(p.y) - py);
+        // Nearest and farthest distance from `point` to a node
+        let ring = |b: &Rect<T>| {
+            let (x0, y0, x1, y1) = (to_f64(b.min_x), to_f64(b.min_y), to_f64(b.max_x), to_f64(b.max_y));
+            let near = (px.clamp(x0, x1) - px).hypot(py.clamp(y0, y1) - py);
+            let far = (px - x0).abs().max((px - x1).abs()).hypot((py - y0).abs().max((py - y1).abs()));
+            (near, far)
+        };
+        let reach = ring(&self.boundary).1;
+
+        let mut radius = dist(&kth.point);
+        let mut scanned = f64::NEG_INFINITY;
+        let mut ranked: Vec<(f64, f64, Item<T>)> = Vec::new();
+        let mut prev_ids: Option<Vec<u64>> = None;
+
+        loop {
+            let mut stack: SmallVec<[&QuadTree<T>; 64]> = SmallVec::new();
+            stack.push(self);
+            while let Some(node) = stack.pop() {
+                let (near, far) = ring(&node.boundary);
+                // Skip nodes outside this ring or fully inside an earlier one
+                if near > radius || far <= scanned {
+                    continue;
+                }
+                if let Some(children) = node.children.as_ref() {
+                    stack.extend(children.iter());
+                } else {
+                    for it in &node.items {
+                        let d = dist(&it.point);
+                        if d > scanned && d <= radius {
+                            ranked.push((score(it, d), d, *it));
+                        }
+                    }
+                }
+            }
+
+            ranked.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.total_cmp(&b.1)));
+            ranked.truncate(k);
+
+            let ids: Vec<u64> = ranked.iter().map(|e| e.2.id).collect();
+            if radius >= reach || prev_ids.as_ref() == Some(&ids) {
+                break;
+            }
+            prev_ids = Some(ids);
+            scanned = radius;
+            radius = (radius * 2.0).max(reach / 64.0);
+        }
+
+        ranked.into_iter().map(|e| e.2).collect()
+    }
+
+    // Items within `radius` of `center`, boundary inclusive (d^2 <= radius^2).
+    // Nodes outside the circle are skipped and nodes fully inside it are
+    // reported without per-item tests.
+    pub fn query_circle(&self, center: Point<T>, radius: T) -> Vec<Item<T>> {
+        self.query_circle_metric(center, radius, Metric::Euclidean)
+    }
+
+    // `query_circle` under `metric`: a diamond for Manhattan, an axis-aligned
+    // square for Chebyshev. Boundary inclusive.
+    pub fn query_circle_metric(&self, center: Point<T>, radius: T, metric: Metric) -> Vec<Item<T>> {
+        #[derive(Copy, Clone)]
+        enum Mode { Filter, ReportAll }
+
+        let r2 = metric.key_for_distance(radius);
+        let mut out = Vec::new();
+        let mut stack: SmallVec<[(&QuadTree<T>, Mode); 64]> = SmallVec::new();
+        stack.push((self, Mode::Filter));
+
+        while let Some((node, mode)) = stack.pop() {
+            let mode = match mode {
+                Mode::ReportAll => Mode::ReportAll,
+                Mode::Filter => {
+                    if metric.key_point_to_rect(&center, &node.boundary) > r2 {
+                        continue;
                     }
-                } else {
-                    // leaf scan
-                    for it in &node.items {
-                        if picked.contains(&it.id) {
-                            continue;
-                        }
-                        let d2 = dist_sq_points(&point, &it.point);
-                        // strict < keeps cap exclusive and picks exactly one per iteration
-                        if best_d2.map(|b| d2 < b).unwrap_or(true) {
-                            best_d2 = Some(d2);
-                            best = Some(*it);
-                        }
+                    if max_key_point_to_rect(metric, &center, &node.boundary) <= r2 {
+                        Mode::ReportAll
+                    } else {
+                        Mode::Filter
                     }
                 }
-            }
+            };
 
-            if let Some(it) = best {
-                picked.insert(it.id);
-                out.push(it);
-                // Note: do NOT tighten cap here
+            if let Some(children) = node.children.as_ref() {
+                for c in children.iter() {
+                    stack.push((c, mode));
+                }
             } else {
-                break;
+                match mode {
+                    Mode::ReportAll => out.extend_from_slice(&node.items),
+                    Mode::Filter => out.extend(
+                        node.items.iter().filter(|it| metric.key_points(&center, &it.point) <= r2),
+                    ),
+                }
             }
         }
-
         out
     }
 
+    // Lazily yields items within `radius` of `center`, pruning nodes by distance.
+    // Stop early to skip the rest of the traversal.
+    pub fn iter_circle(&self, center: Point<T>, radius: T) -> impl Iterator<Item = Item<T>> + '_ {
+        let mut cursor = CircleCursor::new(center, radius);
+        std::iter::from_fn(move || cursor.next_item(self))
+    }
+
+    // Borrows every item in deterministic depth-first order (child 0 first)
+    // without collecting them. Extra memory is bounded by the tree depth.
+    pub fn iter(&self) -> impl Iterator<Item = &Item<T>> + '_ {
+        let mut stack: SmallVec<[&QuadTree<T>; 64]> = SmallVec::new();
+        stack.push(self);
+        let mut leaf = [].iter();
+        std::iter::from_fn(move || loop {
+            if let Some(it) = leaf.next() {
+                return Some(it);
+            }
+            let node = stack.pop()?;
+            match node.children.as_ref() {
+                // Reverse so child 0 is visited first
+                Some(children) => stack.extend(children.iter().rev()),
+                None => leaf = node.items.iter(),
+            }
+        })
+    }
+
+    // Follows child indices from this node. None if the path no longer exists.
+    fn node_at(&self, path: &[u8]) -> Option<&QuadTree<T>> {
+        let mut node = self;
+        for &i in path {
+            node = &node.children.as_ref()?[i as usize];
+        }
+        Some(node)
+    }
+
     // Traverses the entire quadtree and returns a list of all rectangle boundaries.
     pub fn get_all_node_boundaries(&self) -> Vec<Rect<T>> {
-        let mut rectangles = Vec::new();
-        self.collect_rectangles(&mut rectangles);
-        rectangles
+        self.get_nodes().into_iter().map(|n| n.rect).collect()
+    }
+
+    // Every node in pre-order (parent before children, child 0 first) with
+    // its depth below this node and the items it holds directly.
+    pub fn get_nodes(&self) -> Vec<NodeInfo<T>> {
+        let mut nodes = Vec::new();
+        self.collect_nodes(self.depth, &mut nodes);
+        nodes
     }
 
-    // Helper method to recursively collect all rectangle boundaries
-    fn collect_rectangles(&self, rectangles: &mut Vec<Rect<T>>) {
-        // Add this node's boundary
-        rectangles.push(self.boundary);
-        
-        // Recursively collect from children if they exist
+    fn collect_nodes(&self, base_depth: usize, nodes: &mut Vec<NodeInfo<T>>) {
+        nodes.push(NodeInfo {
+            rect: self.boundary,
+            depth: self.depth - base_depth,
+            item_count: self.items.len(),
+            is_leaf: self.children.is_none(),
+        });
         if let Some(children) = self.children.as_ref() {
             for child in children.iter() {
-                child.collect_rectangles(rectangles);
+                child.collect_nodes(base_depth, nodes);
+            }
+        }
+    }
+
+    // Nested JSON dump of the node structure for external tools:
+    // {"rect":[min_x,min_y,max_x,max_y],"items":[{"id":..,"x":..,"y":..}],"children":[..]}
+    // Children appear in child index order; leaves have an empty list.
+    pub fn to_json(&self) -> String
+    where
+        T: NumCast,
+    {
+        let mut out = String::new();
+        self.write_json(&mut out);
+        out
+    }
+
+    fn write_json(&self, out: &mut String)
+    where
+        T: NumCast,
+    {
+        let b = &self.boundary;
+        out.push_str("{\"rect\":[");
+        for (i, v) in [b.min_x, b.min_y, b.max_x, b.max_y].into_iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            push_json_number(out, v);
+        }
+        out.push_str("],\"items\":[");
+        for (i, it) in self.items.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            out.push_str("{\"id\":");
+            out.push_str(&it.id.to_string());
+            out.push_str(",\"x\":");
+            push_json_number(out, it.point.x);
+            out.push_str(",\"y\":");
+            push_json_number(out, it.point.y);
+            out.push('}');
+        }
+        out.push_str("],\"children\":[");
+        if let Some(children) = self.children.as_ref() {
+            for (i, child) in children.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                child.write_json(out);
+            }
+        }
+        out.push_str("]}");
+    }
+
+    // Leaf rectangles and their item counts as parallel arrays, in a single
+    // traversal. Cheaper than exporting leaf contents when only counts are needed.
+    pub fn leaf_counts(&self) -> (Vec<Rect<T>>, Vec<usize>) {
+        let mut rects = Vec::new();
+        let mut counts = Vec::new();
+        self.collect_leaf_counts(&mut rects, &mut counts);
+        (rects, counts)
+    }
+
+    fn collect_leaf_counts(&self, rects: &mut Vec<Rect<T>>, counts: &mut Vec<usize>) {
+        match self.children.as_ref() {
+            Some(children) => {
+                for child in children.iter() {
+                    child.collect_leaf_counts(rects, counts);
+                }
+            }
+            None => {
+                rects.push(self.boundary);
+                counts.push(self.items.len());
             }
         }
     }
 
     // Deletes an item by ID and location. Returns true if removed.
     pub fn delete(&mut self, id: u64, point: Point<T>) -> bool {
+        self.delete_reporting(id, point).removed
+    }
+
+    // Like `delete`, but also reports whether the tree structure changed.
+    pub fn delete_reporting(&mut self, id: u64, point: Point<T>) -> DeleteOutcome {
         if !self.boundary.contains(&point) {
-            return false;
+            return DeleteOutcome::default();
         }
         // Path-local merge is handled during recursion; avoid a second full walk.
         self.delete_internal(id, point)
     }
 
-    fn delete_internal(&mut self, id: u64, point: Point<T>) -> bool {
+    fn delete_internal(&mut self, id: u64, point: Point<T>) -> DeleteOutcome {
         // Leaf: remove in-place
         if self.children.is_none() {
             if let Some(pos) = self.items.iter().position(|it|
                 it.id == id && it.point.x == point.x && it.point.y == point.y
             ) {
                 self.items.swap_remove(pos);
-                return true;
+                return DeleteOutcome { removed: true, merged: false };
             }
-            return false;
+            return DeleteOutcome::default();
         }
 
         // Internal: route to the child that contains the point
-        let idx = child_index_for_point(&self.boundary, &point);
+        let idx = child_index_for_point(&self.boundary, &point, self.split_strategy);
         if let Some(children) = self.children.as_mut() {
-            let removed = children[idx].delete_internal(id, point);
-            if removed {
+            let mut outcome = children[idx].delete_internal(id, point);
+            if outcome.removed {
                 // Try to merge only at this node on the way back up.
-                self.try_merge();
+                outcome.merged |= self.try_merge();
+            }
+            return outcome;
+        }
+        DeleteOutcome::default()
+    }
+
+    // Removes one item with `id` wherever it is and returns its point. Walks
+    // the whole tree, so O(n) worst case; prefer `delete` when the point is
+    // known. If the id is stored more than once only the first found goes.
+    pub fn delete_by_id(&mut self, id: u64) -> Option<Point<T>> {
+        if let Some(pos) = self.items.iter().position(|it| it.id == id) {
+            return Some(self.items.swap_remove(pos).point);
+        }
+        let children = self.children.as_mut()?;
+        let removed = children.iter_mut().find_map(|c| c.delete_by_id(id));
+        if removed.is_some() {
+            self.try_merge();
+        }
+        removed
+    }
+
+    // True if the exact (id, point) pair is stored. Only the one leaf whose
+    // cell holds `point` is scanned.
+    pub fn contains(&self, id: u64, point: Point<T>) -> bool {
+        if !self.boundary.contains(&point) {
+            return false;
+        }
+        self.leaf_for_point(&point)
+            .items
+            .iter()
+            .any(|it| it.id == id && it.point.x == point.x && it.point.y == point.y)
+    }
+
+    // Every item stored exactly at `point`, in leaf order. Only the leaf that
+    // would hold `point` is scanned.
+    pub fn items_at(&self, point: Point<T>) -> Vec<Item<T>> {
+        if !self.boundary.contains(&point) {
+            return Vec::new();
+        }
+        self.leaf_for_point(&point)
+            .items
+            .iter()
+            .filter(|it| it.point.x == point.x && it.point.y == point.y)
+            .copied()
+            .collect()
+    }
+
+    // Renames the stored (old_id, point) item to new_id in place. Only the
+    // leaf holding `point` is touched, with no split or merge. Returns false
+    // if (old_id, point) isn't stored.
+    pub fn reassign_id(&mut self, old_id: u64, new_id: u64, point: Point<T>) -> bool {
+        if !self.boundary.contains(&point) {
+            return false;
+        }
+        let leaf = self.leaf_for_point_mut(&point);
+        match leaf.items.iter_mut().find(|it|
+            it.id == old_id && it.point.x == point.x && it.point.y == point.y
+        ) {
+            Some(it) => {
+                it.id = new_id;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Moves the item (id, old) to `new`. When both points fall in the same
+    // leaf the stored point is rewritten in place with no split or merge.
+    // Expandable roots grow toward a `new` outside the boundary the same way
+    // insert does. Returns false, leaving the items unchanged, if (id, old)
+    // isn't stored or `new` can't be placed.
+    pub fn update_position(&mut self, id: u64, old: Point<T>, new: Point<T>) -> bool {
+        if !self.boundary.contains(&old) {
+            return false;
+        }
+        let stored = self.leaf_for_point(&old).items.iter().any(|it|
+            it.id == id && it.point.x == old.x && it.point.y == old.y
+        );
+        if !stored {
+            return false;
+        }
+        let placeable = self.boundary.contains(&new)
+            || (self.grow_limits.is_some() && self.grow_to_fit(&new));
+        if !placeable {
+            return false;
+        }
+
+        let leaf = self.leaf_for_point_mut(&old);
+        let Some(pos) = leaf.items.iter().position(|it|
+            it.id == id && it.point.x == old.x && it.point.y == old.y
+        ) else {
+            return false;
+        };
+        if leaf.boundary.contains(&new) {
+            leaf.items[pos].point = new;
+            return true;
+        }
+
+        self.delete_internal(id, old);
+        self.insert(Item { id, point: new })
+    }
+
+    // Descends to the leaf whose cell contains `p`.
+    fn leaf_for_point(&self, p: &Point<T>) -> &QuadTree<T> {
+        let mut node = self;
+        while let Some(children) = node.children.as_ref() {
+            node = &children[child_index_for_point(&node.boundary, p, node.split_strategy)];
+        }
+        node
+    }
+
+    fn leaf_for_point_mut(&mut self, p: &Point<T>) -> &mut QuadTree<T> {
+        let mut node = self;
+        while node.children.is_some() {
+            let idx = child_index_for_point(&node.boundary, p, node.split_strategy);
+            node = &mut node.children.as_mut().unwrap()[idx];
+        }
+        node
+    }
+
+    // Every (self_id, other_id) whose points are within `radius`, boundary
+    // inclusive. Both trees are walked together and node pairs farther apart
+    // than `radius` are skipped whole. Order is unspecified.
+    pub fn join_within(&self, other: &QuadTree<T>, radius: T) -> Vec<(u64, u64)> {
+        let mut out = Vec::new();
+        for_each_pair_within(self, other, radius * radius, false, |a, b| out.push((a.id, b.id)));
+        out
+    }
+
+    // Every unordered pair of items within `radius`, boundary inclusive, as
+    // (smaller id, larger id). Each pair is reported once and pairs sharing
+    // an id are skipped. Order is unspecified.
+    pub fn pairs_within(&self, radius: T) -> Vec<(u64, u64)> {
+        let mut out = Vec::new();
+        for_each_pair_within(self, self, radius * radius, true, |a, b| {
+            if a.id != b.id {
+                out.push((a.id.min(b.id), a.id.max(b.id)));
+            }
+        });
+        out
+    }
+
+    // Removes and returns every item within `radius` of `center`, boundary
+    // inclusive. Emptied nodes are merged on the way back up.
+    pub fn drain_circle(&mut self, center: Point<T>, radius: T) -> Vec<Item<T>> {
+        let r2 = radius * radius;
+        let mut out = Vec::new();
+        self.drain_matching(
+            &|b| dist_sq_point_to_rect(&center, b) <= r2,
+            &|p| dist_sq_points(&center, p) <= r2,
+            &mut out,
+        );
+        out
+    }
+
+    // Removes and returns every item inside `range`, using the same half-open
+    // test as `query`. Each node is merged at most once, after its subtree
+    // has been drained.
+    pub fn delete_in_rect(&mut self, range: Rect<T>) -> Vec<Item<T>> {
+        let mut out = Vec::new();
+        self.drain_matching(&|b| range.intersects(b), &|p| range.contains(p), &mut out);
+        out
+    }
+
+    // Moves items whose point satisfies `hit` into `out`, only descending into
+    // nodes for which `touches` holds. Merges bottom-up where anything was removed.
+    fn drain_matching(
+        &mut self,
+        touches: &impl Fn(&Rect<T>) -> bool,
+        hit: &impl Fn(&Point<T>) -> bool,
+        out: &mut Vec<Item<T>>,
+    ) {
+        if !touches(&self.boundary) {
+            return;
+        }
+        let before = out.len();
+        match self.children.as_mut() {
+            Some(children) => {
+                for child in children.iter_mut() {
+                    child.drain_matching(touches, hit, out);
+                }
+                if out.len() > before {
+                    self.try_merge();
+                }
+            }
+            None => out.extend(self.items.extract_if(.., |it| hit(&it.point))),
+        }
+    }
+
+    // Grows the root until it contains `p`. Each step doubles the root toward
+    // `p`; an edge that would pass `grow_limits` stops at the limit instead.
+    // Fails for NaN or infinite coordinates, an empty boundary, or a point
+    // outside the limits (the top limit itself can't be inside a max edge).
+    fn grow_to_fit(&mut self, p: &Point<T>) -> bool {
+        let Some((lo, hi)) = self.grow_limits else {
+            return false;
+        };
+        if !p.is_finite() || p.x < lo || p.y < lo || p.x >= hi || p.y >= hi {
+            return false;
+        }
+        let down = |edge: T, by: T| step_within(edge, by, false, (lo, hi)).unwrap_or(lo);
+        let up = |edge: T, by: T| step_within(edge, by, true, (lo, hi)).unwrap_or(hi);
+
+        while !self.boundary.contains(p) {
+            let old = self.boundary;
+            let (w, h) = (old.width(), old.height());
+            if !(w > T::zero() && h > T::zero()) {
+                return false;
+            }
+            let mut grown = old;
+            if p.x < old.min_x { grown.min_x = down(old.min_x, w); } else { grown.max_x = up(old.max_x, w); }
+            if p.y < old.min_y { grown.min_y = down(old.min_y, h); } else { grown.max_y = up(old.max_y, h); }
+            if grown == old {
+                return false;
+            }
+
+            let new_root = self.new_like(grown, 0, self.max_depth + 1);
+            let mut old_root = std::mem::replace(self, new_root);
+            self.grow_limits = old_root.grow_limits.take();
+            self.next_id = old_root.next_id;
+            self.split();
+            let children = self.children.as_mut().expect("split creates children");
+            // The old root becomes the child on the side away from the growth
+            if let Some(idx) = children.iter().position(|c| c.boundary == old_root.boundary) {
+                old_root.shift_depth();
+                children[idx] = old_root;
+            } else {
+                // Midpoint rounding or the split strategy doesn't line up
+                // with the old edges
+                let mut items = Vec::with_capacity(old_root.count_items());
+                old_root.take_all_items(&mut items);
+                for it in items {
+                    self.insert(it);
+                }
+            }
+        }
+        true
+    }
+
+    // Moves this subtree one level down
+    fn shift_depth(&mut self) {
+        self.depth += 1;
+        self.max_depth += 1;
+        if let Some(children) = self.children.as_mut() {
+            for child in children.iter_mut() {
+                child.shift_depth();
             }
-            return removed;
         }
-        false
     }
 
     // Attempts to merge this node's children back into this node if possible.
+    // Returns true if it did.
     // Local check only: no recursion. O(1) per call except for moving items.
-    fn try_merge(&mut self) {
-        let Some(children) = self.children.as_mut() else { return; };
+    fn try_merge(&mut self) -> bool {
+        let Some(children) = self.children.as_mut() else { return false; };
 
         // Only merge if all children are leaves
         if !children.iter().all(|c| c.children.is_none()) {
-            return;
+            return false;
         }
 
         let total: usize = children.iter().map(|c| c.items.len()).sum();
-        if total <= self.capacity {
+        if total <= self.merge_threshold {
             // Move items up without cloning
             let mut merged = Vec::with_capacity(total);
             for c in children.iter_mut() {
@@ -393,10 +2527,135 @@ impl<T: Coord> QuadTree<T> {
             }
             self.items = merged;
             self.children = None;
+            return true;
+        }
+        false
+    }
+
+
+
+    // Visits every item once, letting `f` move its point or drop it by
+    // returning false. Items that leave their leaf are pulled out during the
+    // walk and reinserted from the root at the end; emptied nodes merge on
+    // the way back up. Moved items that no longer fit (outside a fixed
+    // boundary, or non-finite) are returned instead of being kept.
+    pub fn retain_and_update<F: FnMut(&mut Item<T>) -> bool>(&mut self, mut f: F) -> Vec<Item<T>> {
+        let mut moved = Vec::new();
+        self.retain_and_update_rec(&mut f, &mut moved);
+        let mut evicted = Vec::new();
+        for it in moved {
+            if !self.insert(it) {
+                evicted.push(it);
+            }
+        }
+        evicted
+    }
+
+    fn retain_and_update_rec<F: FnMut(&mut Item<T>) -> bool>(
+        &mut self,
+        f: &mut F,
+        moved: &mut Vec<Item<T>>,
+    ) {
+        match self.children.as_mut() {
+            Some(children) => {
+                for child in children.iter_mut() {
+                    child.retain_and_update_rec(f, moved);
+                }
+                self.try_merge();
+            }
+            None => {
+                let boundary = self.boundary;
+                self.items.retain_mut(|it| {
+                    if !f(it) {
+                        return false;
+                    }
+                    if boundary.contains(&it.point) {
+                        return true;
+                    }
+                    moved.push(*it);
+                    false
+                });
+            }
         }
     }
 
+    // Rotates every item by `angle_rad` about `center` and rebuilds the tree.
+    // The boundary grows to cover the rotated points but never shrinks. Integer
+    // coordinates are rounded. Items whose rotated position can't be represented
+    // in T, or sits at the top of T's range where no exclusive max edge can
+    // cover it, are dropped and returned.
+    pub fn rotate_rebuild(&mut self, center: Point<T>, angle_rad: f64) -> Vec<Item<T>>
+    where
+        T: NumCast,
+    {
+        let mut items = Vec::with_capacity(self.count_items());
+        self.take_all_items(&mut items);
+
+        let (sin, cos) = angle_rad.sin_cos();
+        let to_f64 = |v: T| -> f64 { NumCast::from(v).unwrap_or(f64::NAN) };
+        let integral = <T as NumCast>::from(0.5f64).is_none_or(|h| h == T::zero());
+        let from_f64 = |v: f64| -> Option<T> {
+            NumCast::from(if integral { v.round() } else { v })
+        };
+        let (cx, cy) = (to_f64(center.x), to_f64(center.y));
+        let limits = coord_limits::<T>();
+        // Max edges are exclusive, so they move to the next value above the
+        // point; None if the point sits at the top of T's range
+        let max_edge = |v: T, edge: T| if v < edge { Some(edge) } else { next_above(v, limits) };
+
+        let mut evicted = Vec::new();
+        let mut rotated = Vec::with_capacity(items.len());
+        let mut bounds = self.boundary;
+        for it in items {
+            let dx = to_f64(it.point.x) - cx;
+            let dy = to_f64(it.point.y) - cy;
+            let x = from_f64(cx + dx * cos - dy * sin);
+            let y = from_f64(cy + dx * sin + dy * cos);
+            let (Some(x), Some(y)) = (x, y) else {
+                evicted.push(it);
+                continue;
+            };
+            let (Some(max_x), Some(max_y)) = (max_edge(x, bounds.max_x), max_edge(y, bounds.max_y)) else {
+                evicted.push(it);
+                continue;
+            };
+            if x < bounds.min_x { bounds.min_x = x; }
+            if y < bounds.min_y { bounds.min_y = y; }
+            bounds.max_x = max_x;
+            bounds.max_y = max_y;
+            rotated.push(Item { id: it.id, point: Point { x, y } });
+        }
+
+        let (grow_limits, next_id) = (self.grow_limits, self.next_id);
+        *self = self.new_like(bounds, self.depth, self.max_depth);
+        self.grow_limits = grow_limits;
+        self.next_id = next_id;
+        for it in rotated {
+            if !self.insert(it) {
+                evicted.push(it);
+            }
+        }
+        evicted
+    }
+
+    // Removes all items and collapses back to a single leaf, keeping the
+    // boundary, capacity, max_depth and this node's item buffer. Auto ids
+    // start again from 0.
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.children = None;
+        self.next_id = 0;
+    }
 
+    // Moves every item in this subtree into `out`, leaving an empty leaf.
+    fn take_all_items(&mut self, out: &mut Vec<Item<T>>) {
+        out.append(&mut self.items);
+        if let Some(mut children) = self.children.take() {
+            for child in children.iter_mut() {
+                child.take_all_items(out);
+            }
+        }
+    }
 
     // Returns the total number of items in this subtree
     pub fn count_items(&self) -> usize {
@@ -409,9 +2668,255 @@ impl<T: Coord> QuadTree<T> {
         count
     }
 
+    // True when no item is stored. Stops at the first non-empty leaf rather
+    // than counting everything.
+    pub fn is_empty(&self) -> bool {
+        match self.children.as_ref() {
+            Some(children) => children.iter().all(|c| c.is_empty()),
+            None => self.items.is_empty(),
+        }
+    }
+
+    // Every stored id in traversal order. An id inserted at several points
+    // appears once per point.
+    pub fn all_ids(&self) -> Vec<u64> {
+        let mut out = Vec::with_capacity(self.count_items());
+        let mut stack: SmallVec<[&QuadTree<T>; 64]> = SmallVec::new();
+        stack.push(self);
+        while let Some(node) = stack.pop() {
+            match node.children.as_ref() {
+                Some(children) => stack.extend(children.iter()),
+                None => out.extend(node.items.iter().map(|it| it.id)),
+            }
+        }
+        out
+    }
+
+    // Each id present in the tree exactly once, sorted ascending.
+    pub fn distinct_ids(&self) -> Vec<u64> {
+        let mut ids = self.all_ids();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
     pub fn get_max_depth(&self) -> usize {
         self.max_depth
     }
 
+    // Leaf capacity given at construction
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    // Same as `get_max_depth`. Always set, since a default is resolved at
+    // construction; on an expandable root it grows by one per growth step.
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    // Current root boundary, including any growth
+    pub fn bounds(&self) -> Rect<T> {
+        self.boundary
+    }
+
+    pub fn split_strategy(&self) -> SplitStrategy {
+        self.split_strategy
+    }
+
+    // Depth of the deepest leaf (the root is depth 0)
+    pub fn max_depth_reached(&self) -> usize {
+        self.stats().max_depth_reached
+    }
+
+    // Total nodes, internal and leaf
+    pub fn node_count(&self) -> usize {
+        self.stats().node_count
+    }
+
+    // Approximate heap footprint in bytes: this node's struct, every child
+    // array allocation, and each item Vec's full capacity, spare slots included.
+    pub fn memory_bytes(&self) -> usize {
+        std::mem::size_of::<Self>() + self.heap_bytes()
+    }
+
+    // Bytes owned by this node beyond its own struct
+    fn heap_bytes(&self) -> usize {
+        let mut bytes = self.items.capacity() * std::mem::size_of::<Item<T>>();
+        if let Some(children) = self.children.as_ref() {
+            bytes += std::mem::size_of::<[QuadTree<T>; 4]>();
+            bytes += children.iter().map(|c| c.heap_bytes()).sum::<usize>();
+        }
+        bytes
+    }
+
+    // Item counts by node depth: index = depth, value = items stored at that
+    // depth. A long tail with a big last bucket points at clustering.
+    pub fn items_per_depth(&self) -> Vec<usize> {
+        let mut counts = Vec::new();
+        let mut stack: SmallVec<[&QuadTree<T>; 64]> = SmallVec::new();
+        stack.push(self);
+        while let Some(node) = stack.pop() {
+            let d = node.depth - self.depth;
+            if counts.len() <= d {
+                counts.resize(d + 1, 0);
+            }
+            counts[d] += node.items.len();
+            if let Some(children) = node.children.as_ref() {
+                stack.extend(children.iter());
+            }
+        }
+        counts
+    }
+
+    // Quick go/no-go on query performance: the deepest leaf is within
+    // `max_acceptable_depth` and leaves hold at least `min_occupancy` items on
+    // average. A false result suggests rebuilding with different settings.
+    pub fn is_well_formed(&self, max_acceptable_depth: usize, min_occupancy: f64) -> bool {
+        let stats = self.stats();
+        stats.max_depth_reached <= max_acceptable_depth
+            && stats.mean_leaf_occupancy() >= min_occupancy
+    }
+
+    // Order-independent hash of the (id, point) multiset.
+    // Equal for any two trees holding the same items, however they were built.
+    pub fn content_hash(&self) -> u64
+    where
+        T: NumCast,
+    {
+        let mut sum = 0u64;
+        let mut count = 0u64;
+        let mut stack: Vec<&QuadTree<T>> = vec![self];
+        while let Some(node) = stack.pop() {
+            for it in &node.items {
+                sum = sum.wrapping_add(item_hash(it));
+                count += 1;
+            }
+            if let Some(children) = node.children.as_ref() {
+                stack.extend(children.iter());
+            }
+        }
+        hash_combine(sum, count)
+    }
+
+    // True iff both trees hold the same multiset of (id, point) items,
+    // regardless of bounds or structure. The content hash rejects most
+    // mismatches cheaply; a sorted comparison rules out collisions.
+    pub fn content_eq(&self, other: &QuadTree<T>) -> bool
+    where
+        T: NumCast,
+    {
+        if self.count_items() != other.count_items() || self.content_hash() != other.content_hash() {
+            return false;
+        }
+        let sorted_keys = |tree: &QuadTree<T>| {
+            let mut keys = Vec::with_capacity(tree.count_items());
+            let mut stack: Vec<&QuadTree<T>> = vec![tree];
+            while let Some(node) = stack.pop() {
+                keys.extend(node.items.iter().map(|it| {
+                    (it.id, coord_bits(it.point.x), coord_bits(it.point.y))
+                }));
+                if let Some(children) = node.children.as_ref() {
+                    stack.extend(children.iter());
+                }
+            }
+            keys.sort_unstable();
+            keys
+        };
+        sorted_keys(self) == sorted_keys(other)
+    }
+
+    // Hash of contents *and* layout: node bounds, which nodes are split, and
+    // item order within each leaf. Trees that behave identically for every
+    // query hash equally; the same items arranged differently (e.g. after a
+    // different split history) generally do not. Use `content_hash` when only
+    // the stored items matter.
+    pub fn structural_hash(&self) -> u64
+    where
+        T: NumCast,
+    {
+        let mut h = 0u64;
+        let mut stack: Vec<&QuadTree<T>> = vec![self];
+        while let Some(node) = stack.pop() {
+            let b = &node.boundary;
+            h = hash_combine(h, coord_bits(b.min_x));
+            h = hash_combine(h, coord_bits(b.min_y));
+            h = hash_combine(h, coord_bits(b.max_x));
+            h = hash_combine(h, coord_bits(b.max_y));
+            h = hash_combine(h, node.items.len() as u64);
+            for it in &node.items {
+                h = hash_combine(h, item_hash(it));
+            }
+            match node.children.as_ref() {
+                Some(children) => {
+                    h = hash_combine(h, 1);
+                    stack.extend(children.iter().rev());
+                }
+                None => h = hash_combine(h, 0),
+            }
+        }
+        h
+    }
+
+    // Node, leaf, and item counts plus how many leaves overflowed capacity
+    pub fn stats(&self) -> QuadTreeStats {
+        let mut stats = QuadTreeStats::default();
+        let mut stack: Vec<&QuadTree<T>> = vec![self];
+        while let Some(node) = stack.pop() {
+            stats.node_count += 1;
+            stats.item_count += node.items.len();
+            stats.max_depth_reached = stats.max_depth_reached.max(node.depth);
+            match node.children.as_ref() {
+                Some(children) => stack.extend(children.iter()),
+                None => {
+                    stats.leaf_count += 1;
+                    if node.items.len() > node.split_threshold {
+                        stats.over_capacity_leaves += 1;
+                    }
+                }
+            }
+        }
+        stats
+    }
+
+
+}
+
+// Inserts each item, silently skipping any `insert` would reject. An empty
+// fixed-bounds tree is filled with `bulk_load` instead.
+impl<T: Coord> Extend<Item<T>> for QuadTree<T> {
+    fn extend<I: IntoIterator<Item = Item<T>>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        if self.grow_limits.is_none() && self.children.is_none() && self.items.is_empty() {
+            let items: Vec<Item<T>> = iter.collect();
+            self.bulk_load(&items);
+            return;
+        }
+        for item in iter {
+            self.insert(item);
+        }
+    }
+}
+
+impl<'a, T: Coord> Extend<&'a Item<T>> for QuadTree<T> {
+    fn extend<I: IntoIterator<Item = &'a Item<T>>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+// Batch queries spread across rayon's thread pool. The tree is only read, so
+// the independent queries run in parallel; results keep the input order.
+#[cfg(feature = "rayon")]
+impl<T: Coord + Send + Sync> QuadTree<T> {
+    // `query` for each rect
+    pub fn query_batch_par(&self, rects: &[Rect<T>]) -> Vec<Vec<(u64, T, T)>> {
+        use rayon::prelude::*;
+        rects.par_iter().map(|&r| self.query(r)).collect()
+    }
 
+    // `nearest_neighbor` for each point
+    pub fn nearest_neighbor_batch_par(&self, points: &[Point<T>]) -> Vec<Option<Item<T>>> {
+        use rayon::prelude::*;
+        points.par_iter().map(|&p| self.nearest_neighbor(p)).collect()
+    }
 }
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+use crate::geom::{Coord, Point, Rect};
+use crate::quadtree::{ConfigError, Item, QuadTree};
+
+// A point QuadTree where every id carries a value of type V.
+//
+// Values are not stored in the tree nodes. `QuadTree<T>` keeps `Item<T>` as a
+// small Copy type because its native byte format, the Python bindings and
+// every query return it, and a payload parameter there would change all of
+// them; a `QuadTree = QuadTreeMap<()>` alias isn't possible for the same
+// reason. Instead this type owns the id -> (position, value) table and keeps it
+// in step with the index on every insert, move and remove, so callers never
+// maintain a side map themselves. That table is also what gives each id
+// exactly one position here: inserting an id that is already present moves it
+// and replaces its value.
+pub struct QuadTreeMap<T: Coord, V> {
+    tree: QuadTree<T>,
+    entries: HashMap<u64, (Point<T>, V)>,
+}
+
+impl<T: Coord, V> QuadTreeMap<T, V> {
+    // Errors for zero-width, zero-height or inverted boundaries.
+    pub fn new(boundary: Rect<T>, capacity: usize, max_depth: usize) -> Result<Self, ConfigError> {
+        Ok(QuadTreeMap {
+            tree: QuadTree::builder(boundary).capacity(capacity).max_depth(max_depth).build()?,
+            entries: HashMap::new(),
+        })
+    }
+
+    // Stores `value` for `id` at `point`. Returns the previous value for
+    // `id`, if any. Points outside the boundary are rejected and handed back
+    // as Err without touching the existing entry.
+    pub fn insert(&mut self, id: u64, point: Point<T>, value: V) -> Result<Option<V>, V> {
+        if !self.tree.insert(Item { id, point }) {
+            return Err(value);
+        }
+        match self.entries.insert(id, (point, value)) {
+            Some((old_point, old_value)) => {
+                self.tree.delete(id, old_point);
+                Ok(Some(old_value))
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub fn get(&self, id: u64) -> Option<&V> {
+        self.entries.get(&id).map(|(_, v)| v)
+    }
+
+    pub fn get_mut(&mut self, id: u64) -> Option<&mut V> {
+        self.entries.get_mut(&id).map(|(_, v)| v)
+    }
+
+    pub fn position(&self, id: u64) -> Option<Point<T>> {
+        self.entries.get(&id).map(|(p, _)| *p)
+    }
+
+    // Removes `id` and returns its value
+    pub fn remove(&mut self, id: u64) -> Option<V> {
+        let (point, value) = self.entries.remove(&id)?;
+        self.tree.delete(id, point);
+        Some(value)
+    }
+
+    // Entries inside `range` as (id, point, &value), in `QuadTree::query` order
+    pub fn query(&self, range: Rect<T>) -> Vec<(u64, Point<T>, &V)> {
+        let mut out = Vec::new();
+        self.tree.for_each_in_rect(range, |it| {
+            if let Some((_, v)) = self.entries.get(&it.id) {
+                out.push((it.id, it.point, v));
+            }
+        });
+        out
+    }
+
+    pub fn nearest_neighbors(&self, point: Point<T>, k: usize) -> Vec<(u64, Point<T>, &V)> {
+        self.tree
+            .nearest_neighbors(point, k)
+            .into_iter()
+            .filter_map(|it| self.entries.get(&it.id).map(|(_, v)| (it.id, it.point, v)))
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // The underlying id-only index, for queries that don't need values
+    pub fn tree(&self) -> &QuadTree<T> {
+        &self.tree
+    }
+}
@@ -2,7 +2,7 @@ use smallvec::SmallVec;
 use crate::geom::{Point, Rect, Coord, mid, dist_sq_point_to_rect};
 use crate::serialization::{
     decode_native, decode_native_unlimited, decode_native_with_preallocation_limit, encode_native,
-    NativeDecodeConfig, NativeEncodingConfig, SerializationError,
+    decode_body_exact, NativeBody, NativeDecodeConfig, NativeEncodingConfig, SerializationError,
     DEFAULT_NATIVE_PREALLOCATION_LIMIT_BYTES, NATIVE_KIND_RECT,
 };
 use wincode::{SchemaRead, SchemaWrite};
@@ -23,6 +23,16 @@ pub struct RectQuadTree<T: Coord> {
     max_depth: usize,
 }
 
+// Rect trees still use the v1 layout, so every version decodes the same way.
+impl<T: Coord, const LIMIT: usize> NativeBody<LIMIT> for RectQuadTree<T>
+where
+    Self: for<'de> SchemaRead<'de, NativeDecodeConfig<LIMIT>, Dst = Self>,
+{
+    fn decode_body(_version: u16, body: &[u8]) -> Result<Self, SerializationError> {
+        decode_body_exact::<Self, LIMIT>(body)
+    }
+}
+
 // Child index mapping:
 // 0: left,  bottom
 // 1: right, bottom
@@ -72,11 +82,7 @@ impl<T: Coord> RectQuadTree<T> {
     }
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, SerializationError>
     where
-        Self: for<'de> SchemaRead<
-            'de,
-            NativeDecodeConfig<{ DEFAULT_NATIVE_PREALLOCATION_LIMIT_BYTES }>,
-            Dst = Self,
-        >,
+        Self: NativeBody<{ DEFAULT_NATIVE_PREALLOCATION_LIMIT_BYTES }>,
     {
         decode_native(bytes, NATIVE_KIND_RECT)
     }
@@ -84,17 +90,13 @@ impl<T: Coord> RectQuadTree<T> {
         bytes: &[u8],
     ) -> Result<Self, SerializationError>
     where
-        Self: for<'de> SchemaRead<'de, NativeDecodeConfig<LIMIT>, Dst = Self>,
+        Self: NativeBody<LIMIT>,
     {
         decode_native_with_preallocation_limit::<Self, LIMIT>(bytes, NATIVE_KIND_RECT)
     }
     pub fn from_bytes_unlimited(bytes: &[u8]) -> Result<Self, SerializationError>
     where
-        Self: for<'de> SchemaRead<
-            'de,
-            NativeDecodeConfig<{ wincode::config::PREALLOCATION_SIZE_LIMIT_DISABLED }>,
-            Dst = Self,
-        >,
+        Self: NativeBody<{ wincode::config::PREALLOCATION_SIZE_LIMIT_DISABLED }>,
     {
         decode_native_unlimited(bytes, NATIVE_KIND_RECT)
     }
@@ -4,7 +4,14 @@ use wincode::config::{Configuration, PREALLOCATION_SIZE_LIMIT_DISABLED};
 use wincode::{ReadError, SchemaRead, SchemaWrite, WriteError};
 
 pub const NATIVE_MAGIC: &[u8; 4] = b"FQTW";
-pub const NATIVE_FORMAT_VERSION: u16 = 1;
+// Version history:
+// 1: initial layout.
+// 2: point trees gained the expandable flag.
+// 3: point trees gained split/merge thresholds, the `insert_auto` id counter
+//    and the split strategy, and the expandable flag became growth limits.
+// Every version still decodes. Point trees read v1 and v2 bodies through
+// their legacy layouts (see `NativeBody`); rect trees are unchanged since v1.
+pub const NATIVE_FORMAT_VERSION: u16 = 3;
 pub const NATIVE_KIND_POINT: u8 = 1;
 pub const NATIVE_KIND_RECT: u8 = 2;
 pub const RUNTIME_PREALLOCATION_LIMIT_BUCKETS_BYTES: [usize; 7] = [
@@ -106,6 +113,20 @@ impl From<ReadError> for SerializationError {
     }
 }
 
+// How a tree type reads its body, given the header version. Types whose
+// layout never changed just decode the current schema.
+pub trait NativeBody<const LIMIT: usize>: Sized {
+    fn decode_body(version: u16, body: &[u8]) -> Result<Self, SerializationError>;
+}
+
+// Decodes `body` as exactly one `T` in the native layout.
+pub fn decode_body_exact<T, const LIMIT: usize>(body: &[u8]) -> Result<T, SerializationError>
+where
+    T: for<'de> SchemaRead<'de, NativeDecodeConfig<LIMIT>, Dst = T>,
+{
+    Ok(wincode::config::deserialize_exact(body, native_decode_config::<LIMIT>())?)
+}
+
 pub fn encode_native<T>(value: &T, kind: u8) -> Result<Vec<u8>, SerializationError>
 where
     T: SchemaWrite<NativeEncodingConfig, Src = T> + ?Sized,
@@ -122,11 +143,7 @@ where
 
 pub fn decode_native<T>(bytes: &[u8], expected_kind: u8) -> Result<T, SerializationError>
 where
-    T: for<'de> SchemaRead<
-        'de,
-        NativeDecodeConfig<{ DEFAULT_NATIVE_PREALLOCATION_LIMIT_BYTES }>,
-        Dst = T,
-    >,
+    T: NativeBody<{ DEFAULT_NATIVE_PREALLOCATION_LIMIT_BYTES }>,
 {
     decode_native_with_preallocation_limit::<T, { DEFAULT_NATIVE_PREALLOCATION_LIMIT_BYTES }>(
         bytes,
@@ -139,7 +156,7 @@ pub fn decode_native_with_preallocation_limit<T, const LIMIT: usize>(
     expected_kind: u8,
 ) -> Result<T, SerializationError>
 where
-    T: for<'de> SchemaRead<'de, NativeDecodeConfig<LIMIT>, Dst = T>,
+    T: NativeBody<LIMIT>,
 {
     if bytes.len() < NATIVE_HEADER_LEN {
         return Err(SerializationError::TruncatedHeader);
@@ -149,7 +166,7 @@ where
     }
 
     let version = u16::from_le_bytes([bytes[4], bytes[5]]);
-    if version != NATIVE_FORMAT_VERSION {
+    if !(1..=NATIVE_FORMAT_VERSION).contains(&version) {
         return Err(SerializationError::UnsupportedVersion(version));
     }
 
@@ -166,10 +183,7 @@ where
         return Err(SerializationError::UnsupportedFlags(flags));
     }
 
-    Ok(wincode::config::deserialize_exact(
-        &bytes[NATIVE_HEADER_LEN..],
-        native_decode_config::<LIMIT>(),
-    )?)
+    T::decode_body(version, &bytes[NATIVE_HEADER_LEN..])
 }
 
 pub fn decode_native_unlimited<T>(
@@ -177,11 +191,7 @@ pub fn decode_native_unlimited<T>(
     expected_kind: u8,
 ) -> Result<T, SerializationError>
 where
-    T: for<'de> SchemaRead<
-        'de,
-        NativeDecodeConfig<{ PREALLOCATION_SIZE_LIMIT_DISABLED }>,
-        Dst = T,
-    >,
+    T: NativeBody<{ PREALLOCATION_SIZE_LIMIT_DISABLED }>,
 {
     decode_native_with_preallocation_limit::<T, { PREALLOCATION_SIZE_LIMIT_DISABLED }>(
         bytes,
@@ -196,39 +206,14 @@ pub fn decode_native_with_runtime_preallocation_limit<T>(
     disable_preallocation_limit: bool,
 ) -> Result<T, SerializationError>
 where
-    T: for<'de> SchemaRead<
-            'de,
-            NativeDecodeConfig<1024>,
-            Dst = T,
-        > + for<'de> SchemaRead<
-            'de,
-            NativeDecodeConfig<{ 1024 * 1024 }>,
-            Dst = T,
-        > + for<'de> SchemaRead<
-            'de,
-            NativeDecodeConfig<{ 4 * 1024 * 1024 }>,
-            Dst = T,
-        > + for<'de> SchemaRead<
-            'de,
-            NativeDecodeConfig<{ 16 * 1024 * 1024 }>,
-            Dst = T,
-        > + for<'de> SchemaRead<
-            'de,
-            NativeDecodeConfig<{ DEFAULT_NATIVE_PREALLOCATION_LIMIT_BYTES }>,
-            Dst = T,
-        > + for<'de> SchemaRead<
-            'de,
-            NativeDecodeConfig<{ 256 * 1024 * 1024 }>,
-            Dst = T,
-        > + for<'de> SchemaRead<
-            'de,
-            NativeDecodeConfig<{ 1024 * 1024 * 1024 }>,
-            Dst = T,
-        > + for<'de> SchemaRead<
-            'de,
-            NativeDecodeConfig<{ PREALLOCATION_SIZE_LIMIT_DISABLED }>,
-            Dst = T,
-        >,
+    T: NativeBody<1024>
+        + NativeBody<{ 1024 * 1024 }>
+        + NativeBody<{ 4 * 1024 * 1024 }>
+        + NativeBody<{ 16 * 1024 * 1024 }>
+        + NativeBody<{ DEFAULT_NATIVE_PREALLOCATION_LIMIT_BYTES }>
+        + NativeBody<{ 256 * 1024 * 1024 }>
+        + NativeBody<{ 1024 * 1024 * 1024 }>
+        + NativeBody<{ PREALLOCATION_SIZE_LIMIT_DISABLED }>,
 {
     if disable_preallocation_limit {
         return decode_native_unlimited(bytes, expected_kind);
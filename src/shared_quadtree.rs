@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use crate::geom::{Coord, Point, Rect};
+use crate::quadtree::{Item, QuadTree};
+
+// A built QuadTree shared read-only across threads.
+//
+// QuadTree has no interior mutability, so it is Sync and every query that
+// takes `&self` can run from many threads at once without a lock. Cloning
+// a SharedQuadTree only bumps the Arc count. To change the tree, build a new
+// one and swap it in, or take it back with `into_inner` once the readers
+// are done.
+pub struct SharedQuadTree<T: Coord>(Arc<QuadTree<T>>);
+
+impl<T: Coord> SharedQuadTree<T> {
+    pub fn new(tree: QuadTree<T>) -> Self {
+        SharedQuadTree(Arc::new(tree))
+    }
+
+    pub fn query(&self, range: Rect<T>) -> Vec<(u64, T, T)> {
+        self.0.query(range)
+    }
+
+    pub fn nearest_neighbor(&self, point: Point<T>) -> Option<Item<T>> {
+        self.0.nearest_neighbor(point)
+    }
+
+    pub fn nearest_neighbors(&self, point: Point<T>, k: usize) -> Vec<Item<T>> {
+        self.0.nearest_neighbors(point, k)
+    }
+
+    // The shared tree, for any other `&self` query
+    pub fn tree(&self) -> &QuadTree<T> {
+        &self.0
+    }
+
+    // The tree back if this is the last handle, otherwise Err(self)
+    pub fn into_inner(self) -> Result<QuadTree<T>, Self> {
+        Arc::try_unwrap(self.0).map_err(SharedQuadTree)
+    }
+}
+
+impl<T: Coord> Clone for SharedQuadTree<T> {
+    fn clone(&self) -> Self {
+        SharedQuadTree(Arc::clone(&self.0))
+    }
+}
+
+impl<T: Coord> From<QuadTree<T>> for SharedQuadTree<T> {
+    fn from(tree: QuadTree<T>) -> Self {
+        SharedQuadTree::new(tree)
+    }
+}
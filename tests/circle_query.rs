@@ -0,0 +1,167 @@
+use fastquadtree::{dist_sq_point_to_rect, CircleCursor, Item, Metric, Point, QuadTree, Rect};
+
+fn r(x0: f32, y0: f32, x1: f32, y1: f32) -> Rect<f32> {
+    Rect { min_x: x0, min_y: y0, max_x: x1, max_y: y1 }
+}
+fn pt(x: f32, y: f32) -> Point<f32> { Point { x, y } }
+fn sorted_ids<I: IntoIterator<Item = Item<f32>>>(it: I) -> Vec<u64> {
+    let mut out: Vec<u64> = it.into_iter().map(|it| it.id).collect();
+    out.sort_unstable();
+    out
+}
+
+fn grid_tree() -> (QuadTree<f32>, Vec<Item<f32>>) {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 2, 8);
+    let mut items = Vec::new();
+    let mut id = 0;
+    for x in 0..20 {
+        for y in 0..20 {
+            let it = Item { id, point: pt(x as f32 * 5.0 + 1.0, y as f32 * 5.0 + 1.0) };
+            assert!(qt.insert(it));
+            items.push(it);
+            id += 1;
+        }
+    }
+    (qt, items)
+}
+
+#[test]
+fn iter_circle_matches_brute_force() {
+    let (qt, items) = grid_tree();
+    let center = pt(40.0, 60.0);
+    let radius = 17.0;
+
+    let expected = sorted_ids(items.into_iter().filter(|it| {
+        let dx = it.point.x - center.x;
+        let dy = it.point.y - center.y;
+        dx * dx + dy * dy <= radius * radius
+    }));
+    assert!(!expected.is_empty());
+    assert_eq!(sorted_ids(qt.iter_circle(center, radius)), expected);
+}
+
+#[test]
+fn iter_circle_stopping_early_visits_fewer_nodes() {
+    let (qt, _) = grid_tree();
+    let center = pt(50.0, 50.0);
+
+    let mut partial = CircleCursor::new(center, 40.0);
+    assert!(partial.next_item(&qt).is_some());
+
+    let mut full = CircleCursor::new(center, 40.0);
+    while full.next_item(&qt).is_some() {}
+
+    assert!(partial.nodes_visited() < full.nodes_visited());
+}
+
+#[test]
+fn query_circle_matches_brute_force() {
+    let (qt, items) = grid_tree();
+    for (center, radius) in [(pt(40.0, 60.0), 17.0), (pt(0.0, 0.0), 30.0), (pt(50.0, 50.0), 200.0)] {
+        let expected = sorted_ids(items.iter().copied().filter(|it| {
+            let dx = it.point.x - center.x;
+            let dy = it.point.y - center.y;
+            dx * dx + dy * dy <= radius * radius
+        }));
+        assert_eq!(sorted_ids(qt.query_circle(center, radius)), expected);
+    }
+}
+
+#[test]
+fn query_circle_includes_points_exactly_on_radius() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 1, 8);
+    qt.insert(Item { id: 1, point: pt(53.0, 54.0) }); // distance 5 from (50, 50)
+    qt.insert(Item { id: 2, point: pt(50.0, 45.0) }); // distance 5
+    qt.insert(Item { id: 3, point: pt(56.0, 50.0) }); // distance 6
+    assert_eq!(sorted_ids(qt.query_circle(pt(50.0, 50.0), 5.0)), vec![1, 2]);
+}
+
+#[test]
+fn query_circle_metric_matches_brute_force() {
+    let (qt, items) = grid_tree();
+    let c = pt(42.0, 57.0);
+    for (metric, radius) in [(Metric::Euclidean, 20.0f32), (Metric::Manhattan, 20.0), (Metric::Chebyshev, 13.0)] {
+        let expected = sorted_ids(items.iter().copied().filter(|it| {
+            let (dx, dy) = ((it.point.x - c.x).abs(), (it.point.y - c.y).abs());
+            match metric {
+                Metric::Euclidean => dx * dx + dy * dy <= radius * radius,
+                Metric::Manhattan => dx + dy <= radius,
+                Metric::Chebyshev => dx.max(dy) <= radius,
+            }
+        }));
+        assert!(!expected.is_empty());
+        assert_eq!(sorted_ids(qt.query_circle_metric(c, radius, metric)), expected);
+    }
+    assert_eq!(
+        sorted_ids(qt.query_circle_metric(c, 20.0, Metric::Euclidean)),
+        sorted_ids(qt.query_circle(c, 20.0))
+    );
+}
+
+#[test]
+fn nearest_neighbor_metric_picks_by_metric() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 2, 8);
+    // From (50, 50): a is 10 away on one axis; b is (7, 7) away diagonally
+    qt.insert(Item { id: 1, point: pt(60.0, 50.0) });
+    qt.insert(Item { id: 2, point: pt(57.0, 57.0) });
+    for i in 0..20u64 {
+        qt.insert(Item { id: 100 + i, point: pt(5.0 + i as f32, 95.0) });
+    }
+    let q = pt(50.0, 50.0);
+    // Euclidean: 10 vs 9.9; Manhattan: 10 vs 14; Chebyshev: 10 vs 7
+    assert_eq!(qt.nearest_neighbor_metric(q, Metric::Euclidean).unwrap().id, 2);
+    assert_eq!(qt.nearest_neighbor_metric(q, Metric::Manhattan).unwrap().id, 1);
+    assert_eq!(qt.nearest_neighbor_metric(q, Metric::Chebyshev).unwrap().id, 2);
+    assert_eq!(qt.nearest_neighbor_metric(q, Metric::Euclidean), qt.nearest_neighbor(q));
+
+    let empty: QuadTree<f32> = QuadTree::new(r(0.0, 0.0, 10.0, 10.0), 2, 8);
+    assert!(empty.nearest_neighbor_metric(q, Metric::Manhattan).is_none());
+}
+
+#[test]
+fn nearest_neighbor_metric_ties_go_to_the_lowest_id() {
+    // Capacity 1 spreads the tied points over different leaves, and the lowest
+    // id is inserted last, so neither traversal nor insert order decides
+    let q = pt(50.0, 50.0);
+    let cases = [
+        // All 10 away under Manhattan, at different Euclidean distances
+        (Metric::Manhattan, [(9, 60.0, 50.0), (6, 55.0, 55.0), (7, 50.0, 40.0), (5, 40.0, 50.0)]),
+        // All 8 away under Chebyshev
+        (Metric::Chebyshev, [(8, 58.0, 54.0), (6, 54.0, 42.0), (11, 46.0, 58.0), (3, 42.0, 43.0)]),
+    ];
+    for (metric, tied) in cases {
+        let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 1, 8);
+        assert!(qt.insert(Item { id: 1, point: pt(95.0, 5.0) }));
+        for &(id, x, y) in &tied {
+            assert!(qt.insert(Item { id, point: pt(x, y) }));
+        }
+        let lowest = tied.iter().map(|t| t.0).min();
+        for _ in 0..3 {
+            assert_eq!(qt.nearest_neighbor_metric(q, metric).map(|it| it.id), lowest);
+        }
+    }
+}
+
+#[test]
+fn dist_sq_point_to_rect_is_exact() {
+    let b = r(10.0, 20.0, 30.0, 40.0);
+
+    // Inside and on every edge or corner is exactly zero
+    assert_eq!(dist_sq_point_to_rect(&pt(15.0, 25.0), &b), 0.0);
+    assert_eq!(dist_sq_point_to_rect(&pt(10.0, 30.0), &b), 0.0);
+    assert_eq!(dist_sq_point_to_rect(&pt(30.0, 30.0), &b), 0.0);
+    assert_eq!(dist_sq_point_to_rect(&pt(20.0, 40.0), &b), 0.0);
+    assert_eq!(dist_sq_point_to_rect(&pt(30.0, 40.0), &b), 0.0);
+
+    // Beyond one axis only: gap along that axis
+    assert_eq!(dist_sq_point_to_rect(&pt(5.0, 25.0), &b), 25.0);
+    assert_eq!(dist_sq_point_to_rect(&pt(20.0, 43.0), &b), 9.0);
+
+    // Corner regions measure to the nearest corner
+    assert_eq!(dist_sq_point_to_rect(&pt(7.0, 16.0), &b), 9.0 + 16.0);
+    assert_eq!(dist_sq_point_to_rect(&pt(33.0, 44.0), &b), 9.0 + 16.0);
+
+    let i = Rect { min_x: -5i64, min_y: -5, max_x: 5, max_y: 5 };
+    assert_eq!(dist_sq_point_to_rect(&Point { x: 5, y: -5 }, &i), 0);
+    assert_eq!(dist_sq_point_to_rect(&Point { x: 8, y: 9 }, &i), 9 + 16);
+}
@@ -1,4 +1,4 @@
-use fastquadtree::{Point, Rect, Item, QuadTree};
+use fastquadtree::{ConfigError, Point, QuadTreeConfig, Rect, Item, QuadTree, SplitStrategy};
 
 fn r(min_x: f32, min_y: f32, max_x: f32, max_y: f32) -> Rect<f32> {
     Rect { min_x, min_y, max_x, max_y }
@@ -95,3 +95,700 @@ fn boundary_points_respect_half_open_rule() {
     assert!(!qt.insert(Item { id: 4, point: pt(0.0, 100.0) }));
     assert!(!qt.insert(Item { id: 5, point: pt(100.0, 100.0) }));
 }
+
+#[test]
+fn max_depth_leaves_become_unbounded_buckets() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 2, 3);
+
+    // Far more than capacity, all packed into one deepest cell
+    for id in 0..1000u64 {
+        let jitter = (id % 10) as f32 * 0.01;
+        assert!(qt.insert(Item { id, point: pt(1.0 + jitter, 1.0 + jitter) }));
+    }
+
+    assert_eq!(qt.count_items(), 1000);
+    assert_eq!(qt.query(r(0.0, 0.0, 100.0, 100.0)).len(), 1000);
+
+    let stats = qt.stats();
+    assert_eq!(stats.item_count, 1000);
+    assert_eq!(stats.max_depth_reached, 3);
+    assert_eq!(stats.over_capacity_leaves, 1);
+}
+
+#[test]
+fn autofit_bounds_enclose_points_with_padding() {
+    let items: Vec<Item<f32>> = [(3.0, -2.0), (10.0, 4.0), (-5.0, 7.5), (1.0, 1.0)]
+        .iter()
+        .enumerate()
+        .map(|(i, &(x, y))| Item { id: i as u64, point: pt(x, y) })
+        .collect();
+
+    let qt = QuadTree::from_points_autofit(&items, 2, 8, 0.5).unwrap();
+    assert_eq!(qt.count_items(), items.len());
+    assert_eq!(qt.boundary, r(-5.5, -2.5, 10.5, 8.0));
+}
+
+#[test]
+fn autofit_without_padding_keeps_max_edge_points() {
+    let items = vec![
+        Item { id: 1, point: pt(0.0, 0.0) },
+        Item { id: 2, point: pt(50.0, 20.0) },
+    ];
+    let qt = QuadTree::from_points_autofit(&items, 4, 8, 0.0).unwrap();
+    assert_eq!(qt.count_items(), 2);
+    assert!(qt.boundary.contains(&pt(50.0, 20.0)));
+}
+
+#[test]
+fn autofit_keeps_points_beyond_unit_precision() {
+    // 1e8 + 1 rounds back to 1e8 in f32, so the max edge has to move by an ulp
+    let same: Vec<Item<f32>> = (0..3).map(|i| Item { id: i, point: pt(1.0e8, -1.0e8) }).collect();
+    let qt = QuadTree::from_points_autofit(&same, 2, 8, 0.0).unwrap();
+    assert_eq!(qt.count_items(), 3);
+    assert!(qt.boundary.max_x > 1.0e8 && qt.boundary.max_y > -1.0e8);
+
+    let spread = vec![Item { id: 1, point: pt(0.0, 0.0) }, Item { id: 2, point: pt(1.0e8, 1.0e8) }];
+    let qt = QuadTree::from_points_autofit(&spread, 1, 8, 0.0).unwrap();
+    assert_eq!(qt.count_items(), 2);
+    assert_eq!(qt.nearest_neighbor(pt(1.0e8, 1.0e8)).map(|it| it.id), Some(2));
+
+    // Still one unit when that is representable
+    let small = vec![Item { id: 1, point: pt(2.0, 3.0) }];
+    let qt = QuadTree::from_points_autofit(&small, 1, 8, 0.0).unwrap();
+    assert_eq!(qt.boundary, r(2.0, 3.0, 3.0, 4.0));
+}
+
+#[test]
+fn autofit_rejects_bad_padding_points_and_spreads() {
+    let items = vec![Item { id: 1, point: pt(0.0, 0.0) }, Item { id: 2, point: pt(5.0, 5.0) }];
+    let err = |r: Result<QuadTree<f32>, ConfigError>| r.err();
+    assert_eq!(err(QuadTree::from_points_autofit(&items, 4, 8, -1.0)), Some(ConfigError::InvalidPadding));
+    assert_eq!(err(QuadTree::from_points_autofit(&items, 4, 8, f32::NAN)), Some(ConfigError::InvalidPadding));
+    assert_eq!(err(QuadTree::from_points_autofit(&items, 4, 8, f32::INFINITY)), Some(ConfigError::InvalidPadding));
+
+    let nan = vec![Item { id: 1, point: pt(0.0, 0.0) }, Item { id: 7, point: pt(f32::NAN, 1.0) }];
+    assert_eq!(err(QuadTree::from_points_autofit(&nan, 4, 8, 0.0)), Some(ConfigError::NonFinitePoint { id: 7 }));
+
+    // Width overflows f32, and nothing is above f32::MAX for the max edge
+    let wide = vec![Item { id: 1, point: pt(-3.0e38, 0.0) }, Item { id: 2, point: pt(3.0e38, 1.0) }];
+    assert_eq!(err(QuadTree::from_points_autofit(&wide, 4, 8, 0.0)), Some(ConfigError::InvalidBoundary));
+    let top = vec![Item { id: 1, point: pt(f32::MAX, 0.0) }];
+    assert_eq!(err(QuadTree::from_points_autofit(&top, 4, 8, 0.0)), Some(ConfigError::InvalidBoundary));
+    assert_eq!(err(QuadTree::from_points_autofit(&items, 4, 8, 3.0e38)), Some(ConfigError::InvalidBoundary));
+
+    let ints = vec![Item { id: 1, point: Point { x: i32::MAX, y: 0 } }];
+    assert!(QuadTree::from_points_autofit(&ints, 4, 8, 0).is_err());
+    let ints = vec![Item { id: 1, point: Point { x: i32::MAX - 1, y: i32::MIN } }];
+    let qt = QuadTree::from_points_autofit(&ints, 4, 8, 0).unwrap();
+    assert_eq!(qt.boundary, Rect { min_x: i32::MAX - 1, min_y: i32::MIN, max_x: i32::MAX, max_y: i32::MIN + 1 });
+    assert_eq!(qt.count_items(), 1);
+}
+
+#[test]
+fn distinct_ids_lists_repeated_id_once() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 1, 8);
+    assert!(qt.insert(Item { id: 7, point: pt(10.0, 10.0) }));
+    assert!(qt.insert(Item { id: 7, point: pt(80.0, 80.0) }));
+    assert!(qt.insert(Item { id: 3, point: pt(40.0, 60.0) }));
+
+    let mut all = qt.all_ids();
+    all.sort_unstable();
+    assert_eq!(all, vec![3, 7, 7]);
+    assert_eq!(qt.distinct_ids(), vec![3, 7]);
+}
+
+fn lcg(seed: &mut u64) -> f32 {
+    *seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+    ((*seed >> 40) as f32) / ((1u64 << 24) as f32)
+}
+
+#[test]
+fn bulk_load_matches_incremental_build() {
+    let bounds = r(0.0, 0.0, 1000.0, 1000.0);
+    let mut seed = 42u64;
+    let items: Vec<Item<f32>> = (0..20_000)
+        .map(|id| Item { id, point: pt(lcg(&mut seed) * 1000.0, lcg(&mut seed) * 1000.0) })
+        .collect();
+
+    let mut incremental = QuadTree::new(bounds, 16, 12);
+    for it in &items {
+        assert!(incremental.insert(*it));
+    }
+    let mut bulk = QuadTree::new(bounds, 16, 12);
+    assert_eq!(bulk.bulk_load(&items), items.len());
+
+    // Same splits, same contents
+    assert_eq!(bulk.count_items(), incremental.count_items());
+    assert_eq!(bulk.stats(), incremental.stats());
+    let mut a = bulk.get_all_node_boundaries();
+    let mut b = incremental.get_all_node_boundaries();
+    let key = |r: &Rect<f32>| (r.min_x.to_bits(), r.min_y.to_bits(), r.max_x.to_bits(), r.max_y.to_bits());
+    a.sort_by_key(key);
+    b.sort_by_key(key);
+    assert_eq!(a, b);
+    assert!(bulk.content_eq(&incremental));
+
+    for q in [r(100.0, 100.0, 300.0, 250.0), r(0.0, 900.0, 1000.0, 1000.0), r(499.0, 0.0, 501.0, 1000.0)] {
+        let mut x = bulk.query(q);
+        let mut y = incremental.query(q);
+        x.sort_by_key(|t| t.0);
+        y.sort_by_key(|t| t.0);
+        assert_eq!(x, y);
+    }
+}
+
+#[test]
+fn bulk_load_keeps_existing_items_and_skips_out_of_bounds() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 10.0, 10.0), 2, 8);
+    assert!(qt.insert(Item { id: 1, point: pt(1.0, 1.0) }));
+    let loaded = qt.bulk_load(&[
+        Item { id: 2, point: pt(2.0, 2.0) },
+        Item { id: 3, point: pt(8.0, 8.0) },
+        Item { id: 4, point: pt(12.0, 1.0) },
+    ]);
+    assert_eq!(loaded, 2);
+    assert_eq!(qt.distinct_ids(), vec![1, 2, 3]);
+}
+
+#[test]
+fn bulk_load_grows_expandable_roots_like_insert() {
+    let items = [
+        Item { id: 1, point: pt(1.0, 1.0) },
+        Item { id: 2, point: pt(35.0, -12.0) },
+        Item { id: 3, point: pt(-400.0, 250.0) },
+        Item { id: 4, point: pt(f32::NAN, 1.0) },
+        Item { id: 5, point: pt(8.0, 9.0) },
+    ];
+    let mut bulk = QuadTree::new_expandable(r(0.0, 0.0, 10.0, 10.0), 2, 8);
+    assert!(bulk.insert(Item { id: 0, point: pt(5.0, 5.0) }));
+    assert_eq!(bulk.bulk_load(&items), 4);
+    assert!(bulk.is_expandable());
+    assert!(bulk.boundary.contains(&pt(35.0, -12.0)));
+    assert!(bulk.boundary.contains(&pt(-400.0, 250.0)));
+    assert_eq!(bulk.distinct_ids(), vec![0, 1, 2, 3, 5]);
+
+    let mut incremental = QuadTree::new_expandable(r(0.0, 0.0, 10.0, 10.0), 2, 8);
+    assert!(incremental.insert(Item { id: 0, point: pt(5.0, 5.0) }));
+    for it in &items {
+        incremental.insert(*it);
+    }
+    assert_eq!(bulk.boundary, incremental.boundary);
+    assert!(bulk.content_eq(&incremental));
+
+    // Points beyond the growth limits are still skipped
+    let mut small = QuadTree::new_expandable(Rect { min_x: 0i32, min_y: 0, max_x: 4, max_y: 4 }, 1, 8);
+    let loaded = small.bulk_load(&[
+        Item { id: 1, point: Point { x: 1000, y: -1000 } },
+        Item { id: 2, point: Point { x: i32::MAX, y: 0 } },
+    ]);
+    assert_eq!(loaded, 1);
+    assert_eq!(small.count_items(), 1);
+}
+
+#[test]
+fn bulk_load_respects_max_depth_buckets() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 10.0, 10.0), 1, 3);
+    let items: Vec<Item<f32>> = (0..50).map(|id| Item { id, point: pt(5.0, 5.0) }).collect();
+    assert_eq!(qt.bulk_load(&items), 50);
+    assert_eq!(qt.stats().max_depth_reached, 3);
+    assert_eq!(qt.count_items(), 50);
+}
+
+#[test]
+fn rect_size_helpers() {
+    let a = r(2.0, 4.0, 10.0, 7.0);
+    assert_eq!(a.width(), 8.0);
+    assert_eq!(a.height(), 3.0);
+    assert_eq!(a.area(), 24.0);
+    assert_eq!(a.center(), pt(6.0, 5.5));
+
+    // Inverted on one or both axes clamps area to zero
+    assert_eq!(r(10.0, 0.0, 2.0, 5.0).area(), 0.0);
+    assert_eq!(r(10.0, 10.0, 2.0, 5.0).area(), 0.0);
+    assert_eq!(r(1.0, 1.0, 1.0, 5.0).area(), 0.0);
+
+    let i = Rect { min_x: 0i32, min_y: 0, max_x: 5, max_y: 3 };
+    assert_eq!(i.area(), 15);
+    assert_eq!(i.center(), Point { x: 2, y: 1 });
+}
+
+#[test]
+fn rect_union_and_intersection() {
+    let a = r(0.0, 0.0, 10.0, 10.0);
+    let b = r(5.0, -2.0, 15.0, 8.0);
+    assert_eq!(a.union(&b), r(0.0, -2.0, 15.0, 10.0));
+    assert_eq!(a.intersection(&b), Some(r(5.0, 0.0, 10.0, 8.0)));
+    assert_eq!(b.intersection(&a), a.intersection(&b));
+
+    // Containment
+    let inner = r(2.0, 3.0, 4.0, 5.0);
+    assert_eq!(a.union(&inner), a);
+    assert_eq!(a.intersection(&inner), Some(inner));
+
+    // Sharing only an edge or a corner is not an overlap, matching `intersects`
+    let right = r(10.0, 0.0, 20.0, 10.0);
+    assert!(!a.intersects(&right));
+    assert_eq!(a.intersection(&right), None);
+    assert_eq!(a.intersection(&r(10.0, 10.0, 20.0, 20.0)), None);
+    assert_eq!(a.union(&right), r(0.0, 0.0, 20.0, 10.0));
+
+    assert_eq!(a.intersection(&r(30.0, 30.0, 40.0, 40.0)), None);
+}
+
+#[test]
+fn point_ops_and_rect_transforms() {
+    let a = pt(1.0, 2.0);
+    let b = pt(4.0, -1.0);
+    assert_eq!(a + b, pt(5.0, 1.0));
+    assert_eq!(b - a, pt(3.0, -3.0));
+    assert_eq!(a * 2.0, pt(2.0, 4.0));
+    assert_eq!(a.translate(-1.0, 0.5), pt(0.0, 2.5));
+
+    let view = r(0.0, 0.0, 10.0, 4.0);
+    assert_eq!(view.translate(5.0, -2.0), r(5.0, -2.0, 15.0, 2.0));
+    assert_eq!(view.scale_from_center(2.0), r(-5.0, -2.0, 15.0, 6.0));
+    assert_eq!(view.scale_from_center(0.5), r(2.5, 1.0, 7.5, 3.0));
+    assert_eq!(view.scale_from_center(2.0).center(), view.center());
+}
+
+#[test]
+fn expandable_root_grows_toward_out_of_bounds_points() {
+    let mut qt = QuadTree::new_expandable(r(0.0, 0.0, 10.0, 10.0), 2, 8);
+    assert!(qt.is_expandable());
+    for (i, (x, y)) in [(1.0, 1.0), (2.0, 8.0), (9.0, 3.0), (5.0, 5.0)].into_iter().enumerate() {
+        assert!(qt.insert(Item { id: i as u64, point: pt(x, y) }));
+    }
+
+    // One step in each direction, then a far point needing several steps
+    assert!(qt.insert(Item { id: 10, point: pt(15.0, 12.0) }));
+    assert_eq!(qt.boundary, r(0.0, 0.0, 20.0, 20.0));
+    assert!(qt.insert(Item { id: 11, point: pt(-3.0, -7.0) }));
+    assert_eq!(qt.boundary, r(-20.0, -20.0, 20.0, 20.0));
+    assert!(qt.insert(Item { id: 12, point: pt(500.0, -300.0) }));
+    assert!(qt.boundary.contains(&pt(500.0, -300.0)));
+    assert!(qt.is_expandable());
+
+    assert_eq!(qt.count_items(), 7);
+    let mut ids: Vec<u64> = qt.query(qt.boundary).into_iter().map(|(id, _, _)| id).collect();
+    ids.sort_unstable();
+    assert_eq!(ids, vec![0, 1, 2, 3, 10, 11, 12]);
+    let near: Vec<u64> = qt.query(r(0.0, 0.0, 3.0, 3.0)).into_iter().map(|(id, _, _)| id).collect();
+    assert_eq!(near, vec![0]);
+
+    // Old leaves keep their split budget under the new roots
+    for i in 0..50u64 {
+        assert!(qt.insert(Item { id: 100 + i, point: pt(1.0 + i as f32 * 0.01, 1.0) }));
+    }
+    assert!(qt.delete(11, pt(-3.0, -7.0)));
+}
+
+#[test]
+fn expandable_root_rejects_non_finite_and_fixed_trees_still_reject() {
+    let mut qt = QuadTree::new_expandable(r(0.0, 0.0, 10.0, 10.0), 4, 8);
+    assert!(!qt.insert(Item { id: 1, point: pt(f32::NAN, 1.0) }));
+    assert!(!qt.insert(Item { id: 2, point: pt(1.0, f32::INFINITY) }));
+    assert_eq!(qt.boundary, r(0.0, 0.0, 10.0, 10.0));
+    assert_eq!(qt.count_items(), 0);
+
+    let mut fixed = QuadTree::new(r(0.0, 0.0, 10.0, 10.0), 4, 8);
+    assert!(!fixed.is_expandable());
+    assert!(!fixed.insert(Item { id: 1, point: pt(11.0, 1.0) }));
+    assert_eq!(fixed.boundary, r(0.0, 0.0, 10.0, 10.0));
+
+    // Integer trees stop growing once an edge no longer fits
+    let mut small = QuadTree::new_expandable(Rect { min_x: 0i32, min_y: 0, max_x: 4, max_y: 4 }, 1, 8);
+    assert!(small.insert(Item { id: 1, point: Point { x: 1000, y: -1000 } }));
+    assert!(small.insert(Item { id: 2, point: Point { x: 1, y: 1 } }));
+    assert!(!small.insert(Item { id: 3, point: Point { x: i32::MAX, y: 0 } }));
+    assert_eq!(small.count_items(), 2);
+
+    // Float trees grow up to the largest finite value, which stays outside
+    let mut wide = QuadTree::new_expandable(r(0.0, 0.0, 10.0, 10.0), 4, 8);
+    assert!(wide.insert(Item { id: 1, point: pt(3.0e38, -3.0e38) }));
+    assert!(!wide.insert(Item { id: 2, point: pt(f32::MAX, 0.0) }));
+    assert!(wide.insert(Item { id: 3, point: pt(f32::MIN, 1.0) }));
+    assert_eq!(wide.count_items(), 2);
+}
+
+#[test]
+fn update_position_grows_expandable_roots_like_insert() {
+    let mut qt = QuadTree::new_expandable(r(0.0, 0.0, 10.0, 10.0), 2, 8);
+    for (i, (x, y)) in [(1.0, 1.0), (2.0, 8.0), (9.0, 3.0)].into_iter().enumerate() {
+        assert!(qt.insert(Item { id: i as u64, point: pt(x, y) }));
+    }
+
+    assert!(qt.update_position(1, pt(2.0, 8.0), pt(35.0, -12.0)));
+    assert!(qt.boundary.contains(&pt(35.0, -12.0)));
+    assert!(qt.contains(1, pt(35.0, -12.0)));
+    assert!(!qt.contains(1, pt(2.0, 8.0)));
+    assert_eq!(qt.count_items(), 3);
+
+    // Missing items and unplaceable targets leave the tree alone
+    let grown = qt.boundary;
+    assert!(!qt.update_position(7, pt(1.0, 1.0), pt(500.0, 500.0)));
+    assert!(!qt.update_position(0, pt(1.0, 1.0), pt(f32::NAN, 1.0)));
+    assert_eq!(qt.boundary, grown);
+    assert!(qt.contains(0, pt(1.0, 1.0)));
+
+    // Fixed trees still reject out of bounds moves
+    let mut fixed = QuadTree::new(r(0.0, 0.0, 10.0, 10.0), 2, 8);
+    assert!(fixed.insert(Item { id: 1, point: pt(2.0, 2.0) }));
+    assert!(!fixed.update_position(1, pt(2.0, 2.0), pt(35.0, 2.0)));
+    assert!(fixed.contains(1, pt(2.0, 2.0)));
+}
+
+#[test]
+fn split_and_merge_thresholds_are_separate_from_capacity() {
+    let config = QuadTreeConfig { capacity: 4, split_threshold: 8, max_depth: 8, merge_threshold: 2 };
+    let mut qt = QuadTree::new_with_config(r(0.0, 0.0, 100.0, 100.0), config).unwrap();
+    assert_eq!(qt.config(), config);
+
+    // Holds past capacity until split_threshold
+    for id in 0..8u64 {
+        assert!(qt.insert(Item { id, point: pt(id as f32 * 10.0 + 1.0, id as f32 * 10.0 + 1.0) }));
+    }
+    assert_eq!(qt.node_count(), 1);
+    assert!(qt.insert(Item { id: 8, point: pt(95.0, 5.0) }));
+    assert!(qt.node_count() > 1);
+    assert_eq!(qt.stats().over_capacity_leaves, 0);
+    // Children inherit the config
+    assert_eq!(qt.children.as_ref().unwrap()[0].config(), config);
+
+    // Deleting down to capacity doesn't merge; merge_threshold does
+    for id in 0..6u64 {
+        assert!(qt.delete(id, pt(id as f32 * 10.0 + 1.0, id as f32 * 10.0 + 1.0)));
+    }
+    assert_eq!(qt.count_items(), 3);
+    assert!(qt.node_count() > 1);
+    assert!(qt.delete(6, pt(61.0, 61.0)));
+    assert_eq!(qt.node_count(), 1);
+}
+
+#[test]
+fn config_validation() {
+    let b = r(0.0, 0.0, 10.0, 10.0);
+    let bad_split = QuadTreeConfig { capacity: 4, split_threshold: 3, max_depth: 8, merge_threshold: 1 };
+    assert_eq!(
+        QuadTree::<f32>::new_with_config(b, bad_split).err(),
+        Some(ConfigError::SplitThresholdBelowCapacity { split_threshold: 3, capacity: 4 })
+    );
+    let bad_merge = QuadTreeConfig { capacity: 4, split_threshold: 4, max_depth: 8, merge_threshold: 5 };
+    assert_eq!(
+        QuadTree::<f32>::new_with_config(b, bad_merge).err(),
+        Some(ConfigError::MergeThresholdAboveCapacity { merge_threshold: 5, capacity: 4 })
+    );
+    assert_eq!(bad_merge.validate().unwrap_err().to_string(), "merge_threshold (5) must be <= capacity (4)");
+
+    // Plain `new` splits and merges at capacity, and that config is itself valid
+    for capacity in [0, 1, 4] {
+        let qt = QuadTree::<f32>::new(b, capacity, 8);
+        let config = qt.config();
+        assert_eq!(config, QuadTreeConfig { capacity, split_threshold: capacity, max_depth: 8, merge_threshold: capacity });
+        assert_eq!(config.validate(), Ok(()));
+        assert_eq!(QuadTree::new_with_config(b, config).unwrap().config(), config);
+    }
+}
+
+#[test]
+fn merge_threshold_reduces_split_merge_churn() {
+    // Fill a leaf to capacity, then alternate one insert and one delete across
+    // the boundary, counting how often the node count changes
+    fn flips(mut qt: QuadTree<f32>) -> usize {
+        for id in 0..4u64 {
+            qt.insert(Item { id, point: pt(10.0 + id as f32 * 20.0, 10.0 + id as f32 * 20.0) });
+        }
+        let mut flips = 0;
+        let mut last = qt.node_count();
+        for _ in 0..50 {
+            qt.insert(Item { id: 99, point: pt(90.0, 10.0) });
+            if qt.node_count() != last { flips += 1; last = qt.node_count(); }
+            qt.delete(99, pt(90.0, 10.0));
+            if qt.node_count() != last { flips += 1; last = qt.node_count(); }
+        }
+        flips
+    }
+
+    let b = r(0.0, 0.0, 100.0, 100.0);
+    // Default merges on every delete and re-splits on every insert
+    assert_eq!(flips(QuadTree::new(b, 4, 8)), 100);
+    // Merging only at <= capacity / 2 keeps the split after the first one
+    let config = QuadTreeConfig { capacity: 4, split_threshold: 4, max_depth: 8, merge_threshold: 2 };
+    assert_eq!(flips(QuadTree::new_with_config(b, config).unwrap()), 1);
+}
+
+#[test]
+fn extend_inserts_items_and_skips_rejected_ones() {
+    let items: Vec<Item<f32>> = (0..200u64)
+        .map(|id| Item { id, point: pt((id * 37 % 100) as f32 + 0.5, (id * 53 % 100) as f32 + 0.5) })
+        .collect();
+
+    // Empty tree takes the bulk path
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 4, 10);
+    qt.extend(items.iter().copied().chain([Item { id: 999, point: pt(150.0, 1.0) }]));
+    assert_eq!(qt.count_items(), 200);
+
+    let mut one_by_one = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 4, 10);
+    for it in &items {
+        one_by_one.insert(*it);
+    }
+    assert_eq!(qt.get_all_node_boundaries(), one_by_one.get_all_node_boundaries());
+
+    // Non-empty tree inserts one at a time; borrowed items work too
+    qt.extend(&[Item { id: 500, point: pt(1.0, 1.0) }, Item { id: 501, point: pt(f32::NAN, 1.0) }]);
+    assert_eq!(qt.count_items(), 201);
+    assert!(qt.contains(500, pt(1.0, 1.0)));
+
+    // Expandable trees still grow
+    let mut grow = QuadTree::new_expandable(r(0.0, 0.0, 10.0, 10.0), 4, 10);
+    grow.extend([Item { id: 1, point: pt(1.0, 1.0) }, Item { id: 2, point: pt(35.0, 2.0) }]);
+    assert_eq!(grow.count_items(), 2);
+}
+
+#[test]
+fn insert_auto_hands_out_increasing_ids() {
+    let mut qt = QuadTree::new_expandable(r(0.0, 0.0, 10.0, 10.0), 2, 8);
+    assert_eq!(qt.insert_auto(pt(1.0, 1.0)), Some(0));
+    assert_eq!(qt.insert_auto(pt(f32::NAN, 1.0)), None);
+    assert_eq!(qt.insert_auto(pt(2.0, 2.0)), Some(1));
+    assert!(qt.delete(1, pt(2.0, 2.0)));
+    // Growing the root must keep the counter
+    assert_eq!(qt.insert_auto(pt(50.0, 50.0)), Some(2));
+    assert_eq!(qt.count_items(), 2);
+
+    qt.clear();
+    assert_eq!(qt.insert_auto(pt(3.0, 3.0)), Some(0));
+}
+
+#[test]
+fn insert_depth_reports_landing_leaf() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 16.0, 16.0), 1, 3);
+    assert_eq!(qt.insert_depth(Item { id: 1, point: pt(1.0, 1.0) }), Some(0));
+    // Second point splits the root; both land one level down
+    assert_eq!(qt.insert_depth(Item { id: 2, point: pt(9.0, 9.0) }), Some(1));
+    // Close to id 1, so the split goes down to max_depth
+    assert_eq!(qt.insert_depth(Item { id: 3, point: pt(1.5, 1.5) }), Some(3));
+    assert_eq!(qt.insert_depth(Item { id: 4, point: pt(1.2, 1.2) }), Some(3));
+
+    assert_eq!(qt.insert_depth(Item { id: 5, point: pt(20.0, 1.0) }), None);
+    assert_eq!(qt.insert_depth(Item { id: 6, point: pt(f32::NAN, 1.0) }), None);
+    assert_eq!(qt.count_items(), 4);
+}
+
+#[test]
+fn capacity_zero_splits_to_max_depth() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 8.0, 8.0), 0, 3);
+    // Colliding points all share one max-depth leaf
+    for id in 0..5u64 {
+        assert_eq!(qt.insert_depth(Item { id, point: pt(1.0, 1.0) }), Some(3));
+    }
+    assert_eq!(qt.insert_depth(Item { id: 9, point: pt(7.0, 7.0) }), Some(3));
+    assert_eq!(qt.count_items(), 6);
+    assert_eq!(qt.items_at(pt(1.0, 1.0)).len(), 5);
+    assert_eq!(qt.query(r(0.0, 0.0, 2.0, 2.0)).len(), 5);
+
+    // Deleting everything merges back to a single leaf
+    for id in 0..5u64 {
+        assert!(qt.delete(id, pt(1.0, 1.0)));
+    }
+    assert!(qt.delete(9, pt(7.0, 7.0)));
+    assert_eq!(qt.get_all_node_boundaries().len(), 1);
+
+    let mut bulk = QuadTree::new(r(0.0, 0.0, 8.0, 8.0), 0, 2);
+    let items: Vec<Item<f32>> = (0..4u64).map(|id| Item { id, point: pt(3.0, 3.0) }).collect();
+    assert_eq!(bulk.bulk_load(&items), 4);
+    assert_eq!(bulk.insert_depth(Item { id: 4, point: pt(3.0, 3.0) }), Some(2));
+}
+
+#[test]
+fn retain_and_update_moves_drops_and_evicts() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 2, 8);
+    for id in 0..50u64 {
+        assert!(qt.insert(Item { id, point: pt(id as f32 * 2.0, 10.0) }));
+    }
+
+    // Shift everything right by 20, drop multiples of 5
+    let evicted = qt.retain_and_update(|it| {
+        it.point.x += 20.0;
+        it.id % 5 != 0
+    });
+
+    // x = 2 * id + 20 falls outside for id >= 40
+    let mut out: Vec<u64> = evicted.iter().map(|it| it.id).collect();
+    out.sort_unstable();
+    assert_eq!(out, vec![41, 42, 43, 44, 46, 47, 48, 49]);
+
+    let mut kept: Vec<u64> = qt.query(qt.boundary).into_iter().map(|(id, _, _)| id).collect();
+    kept.sort_unstable();
+    let expected: Vec<u64> = (0..40u64).filter(|id| id % 5 != 0).collect();
+    assert_eq!(kept, expected);
+    for &id in &expected {
+        assert!(qt.contains(id, pt(id as f32 * 2.0 + 20.0, 10.0)));
+    }
+    assert_eq!(qt.count_items(), expected.len());
+}
+
+#[test]
+fn builder_matches_constructors_and_validates() {
+    let bounds = r(0.0, 0.0, 100.0, 100.0);
+    let qt = QuadTree::builder(bounds).capacity(6).max_depth(9).build().unwrap();
+    assert_eq!(qt.config(), QuadTree::new(bounds, 6, 9).config());
+    assert!(!qt.is_expandable());
+
+    let defaults = QuadTree::<f32>::builder(bounds).build().unwrap();
+    assert_eq!((defaults.capacity(), defaults.max_depth()), (16, 32));
+
+    // Spelling the default thresholds out builds the same tree as leaving them unset
+    let explicit = QuadTree::<f32>::builder(bounds).split_threshold(16).merge_threshold(16).build().unwrap();
+    assert_eq!(explicit.config(), defaults.config());
+    for (split, merge) in [(Some(16), None), (None, Some(16))] {
+        let mut b = QuadTree::<f32>::builder(bounds);
+        if let Some(split) = split { b = b.split_threshold(split); }
+        if let Some(merge) = merge { b = b.merge_threshold(merge); }
+        assert_eq!(b.build().unwrap().config(), defaults.config());
+    }
+    let mut implicit = defaults.clone();
+    let mut spelled = explicit;
+    for id in 0..200u64 {
+        let p = pt((id * 37 % 100) as f32 + 0.5, (id * 53 % 100) as f32 + 0.5);
+        implicit.insert(Item { id, point: p });
+        spelled.insert(Item { id, point: p });
+    }
+    for id in (0..200u64).step_by(3) {
+        let p = pt((id * 37 % 100) as f32 + 0.5, (id * 53 % 100) as f32 + 0.5);
+        assert_eq!(implicit.delete(id, p), spelled.delete(id, p));
+    }
+    assert_eq!(implicit.get_all_node_boundaries(), spelled.get_all_node_boundaries());
+
+    // Setting one threshold leaves the other at capacity
+    let tuned = QuadTree::builder(bounds).capacity(4).split_threshold(8).expandable(true).build().unwrap();
+    let c = tuned.config();
+    assert_eq!((c.split_threshold, c.merge_threshold), (8, 4));
+    assert!(tuned.is_expandable());
+
+    assert_eq!(
+        QuadTree::builder(bounds).capacity(4).merge_threshold(5).build().err(),
+        Some(ConfigError::MergeThresholdAboveCapacity { merge_threshold: 5, capacity: 4 })
+    );
+    assert_eq!(
+        QuadTree::builder(r(5.0, 0.0, 5.0, 10.0)).build().err(),
+        Some(ConfigError::InvalidBoundary)
+    );
+}
+
+#[test]
+fn clone_is_independent_of_original() {
+    let mut tree = QuadTree::new(Rect { min_x: 0.0, min_y: 0.0, max_x: 100.0, max_y: 100.0 }, 2, 8);
+    for id in 0..6u64 {
+        tree.insert(Item { id, point: Point { x: id as f32 * 15.0, y: id as f32 * 15.0 } });
+    }
+    let nodes_before = tree.get_all_node_boundaries();
+
+    let mut fork = tree.clone();
+    assert_eq!(fork.get_all_node_boundaries(), nodes_before);
+    for id in 10..20u64 {
+        assert!(fork.insert(Item { id, point: Point { x: 5.0, y: id as f32 } }));
+    }
+    assert!(fork.delete(0, Point { x: 0.0, y: 0.0 }));
+    assert_eq!(fork.count_items(), 15);
+
+    assert_eq!(tree.count_items(), 6);
+    assert!(tree.contains(0, Point { x: 0.0, y: 0.0 }));
+    assert!(!tree.contains(10, Point { x: 5.0, y: 10.0 }));
+    assert_eq!(tree.get_all_node_boundaries(), nodes_before);
+}
+
+#[test]
+fn rect_from_center_and_corners() {
+    let c = Rect::from_center(Point { x: 10, y: 20 }, 3, 4);
+    assert_eq!(c, Rect { min_x: 7, min_y: 16, max_x: 13, max_y: 24 });
+    assert_eq!(c.center(), Point { x: 10, y: 20 });
+
+    let want = r(1.0, 2.0, 5.0, 6.0);
+    let (a, b) = (Point { x: 1.0, y: 6.0 }, Point { x: 5.0, y: 2.0 });
+    assert_eq!(Rect::from_corners(a, b), want);
+    assert_eq!(Rect::from_corners(b, a), want);
+    assert_eq!(Rect::from_corners(Point { x: 5.0, y: 6.0 }, Point { x: 1.0, y: 2.0 }), want);
+}
+
+#[test]
+fn longest_axis_split_cuts_only_the_long_side() {
+    let bounds = r(0.0, 0.0, 1000.0, 10.0);
+    let build = |split| {
+        QuadTree::builder(bounds).capacity(2).max_depth(8).split_strategy(split).build().unwrap()
+    };
+    let mut kd = build(SplitStrategy::LongestAxis);
+    let mut quad = build(SplitStrategy::Quadrant);
+    assert_eq!(kd.split_strategy(), SplitStrategy::LongestAxis);
+
+    let pts: Vec<Item<f32>> = (0..40u64)
+        .map(|id| Item { id, point: Point { x: id as f32 * 24.7, y: (id % 10) as f32 } })
+        .collect();
+    for it in &pts {
+        assert!(kd.insert(*it));
+        assert!(quad.insert(*it));
+    }
+
+    // The first split makes four full-height strips
+    let nodes = kd.get_all_node_boundaries();
+    for x in 0..4 {
+        let x = x as f32 * 250.0;
+        assert!(nodes.contains(&r(x, 0.0, x + 250.0, 10.0)));
+    }
+
+    // Same answers as the quadrant layout, which cut y on its first split
+    assert!(quad.get_all_node_boundaries().contains(&r(0.0, 0.0, 500.0, 5.0)));
+    for range in [r(100.0, 2.0, 600.0, 7.0), r(0.0, 0.0, 1000.0, 10.0), r(990.0, 0.0, 1000.0, 5.0)] {
+        let (mut a, mut b) = (kd.query(range), quad.query(range));
+        a.sort_by_key(|h| h.0);
+        b.sort_by_key(|h| h.0);
+        assert_eq!(a, b);
+    }
+    for it in &pts {
+        assert!(kd.contains(it.id, it.point));
+        assert_eq!(kd.nearest_neighbor(it.point).unwrap().id, it.id);
+    }
+
+    // Bulk loading follows the same child layout as incremental inserts
+    let mut bulk = build(SplitStrategy::LongestAxis);
+    assert_eq!(bulk.bulk_load(&pts), pts.len());
+    assert_eq!(bulk.get_all_node_boundaries(), kd.get_all_node_boundaries());
+
+    // Growing an expandable root keeps routing consistent
+    let mut grown = QuadTree::builder(bounds)
+        .capacity(2)
+        .expandable(true)
+        .split_strategy(SplitStrategy::LongestAxis)
+        .build()
+        .unwrap();
+    for it in &pts {
+        grown.insert(*it);
+    }
+    let far = Item { id: 99, point: Point { x: 2500.0, y: 35.0 } };
+    assert!(grown.insert(far));
+    for it in pts.iter().chain([&far]) {
+        assert!(grown.contains(it.id, it.point));
+    }
+
+    for it in &pts {
+        assert!(kd.delete(it.id, it.point));
+    }
+    assert_eq!(kd.get_all_node_boundaries(), vec![bounds]);
+}
+
+#[test]
+fn longest_axis_matches_quadrant_on_square_bounds() {
+    let bounds = r(0.0, 0.0, 64.0, 64.0);
+    let mut kd = QuadTree::builder(bounds)
+        .capacity(1)
+        .max_depth(6)
+        .split_strategy(SplitStrategy::LongestAxis)
+        .build()
+        .unwrap();
+    let mut quad = QuadTree::new(bounds, 1, 6);
+    for id in 0..30u64 {
+        let it = Item { id, point: Point { x: (id * 7 % 64) as f32, y: (id * 13 % 64) as f32 } };
+        kd.insert(it);
+        quad.insert(it);
+    }
+    assert_eq!(kd.get_all_node_boundaries(), quad.get_all_node_boundaries());
+}
@@ -0,0 +1,96 @@
+use fastquadtree::{dist_sq_rect_to_rect, Item, Point, QuadTree, Rect};
+
+fn r(x0: f32, y0: f32, x1: f32, y1: f32) -> Rect<f32> {
+    Rect { min_x: x0, min_y: y0, max_x: x1, max_y: y1 }
+}
+
+fn lcg(seed: &mut u64) -> f32 {
+    *seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+    ((*seed >> 40) as f32) / ((1u64 << 24) as f32)
+}
+
+fn random_items(seed: &mut u64, n: u64, first_id: u64) -> Vec<Item<f32>> {
+    (0..n)
+        .map(|i| Item { id: first_id + i, point: Point { x: lcg(seed) * 100.0, y: lcg(seed) * 100.0 } })
+        .collect()
+}
+
+fn build(items: &[Item<f32>], capacity: usize) -> QuadTree<f32> {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), capacity, 10);
+    for it in items {
+        assert!(qt.insert(*it));
+    }
+    qt
+}
+
+fn within(a: &Item<f32>, b: &Item<f32>, radius: f32) -> bool {
+    let (dx, dy) = (a.point.x - b.point.x, a.point.y - b.point.y);
+    dx * dx + dy * dy <= radius * radius
+}
+
+#[test]
+fn dist_sq_rect_to_rect_measures_the_gap() {
+    let a = r(0.0, 0.0, 10.0, 10.0);
+    assert_eq!(dist_sq_rect_to_rect(&a, &r(5.0, 5.0, 20.0, 20.0)), 0.0);
+    assert_eq!(dist_sq_rect_to_rect(&a, &r(10.0, 0.0, 20.0, 10.0)), 0.0);
+    assert_eq!(dist_sq_rect_to_rect(&a, &r(13.0, 2.0, 20.0, 8.0)), 9.0);
+    assert_eq!(dist_sq_rect_to_rect(&a, &r(-8.0, -9.0, -3.0, -4.0)), 9.0 + 16.0);
+    assert_eq!(dist_sq_rect_to_rect(&r(-8.0, -9.0, -3.0, -4.0), &a), 9.0 + 16.0);
+}
+
+#[test]
+fn join_within_matches_brute_force() {
+    let mut seed = 7;
+    let players = random_items(&mut seed, 300, 0);
+    let pickups = random_items(&mut seed, 200, 1000);
+    let a = build(&players, 4);
+    let b = build(&pickups, 2);
+
+    for radius in [0.0, 1.5, 6.0, 200.0] {
+        let mut expected: Vec<(u64, u64)> = Vec::new();
+        for p in &players {
+            for q in &pickups {
+                if within(p, q, radius) {
+                    expected.push((p.id, q.id));
+                }
+            }
+        }
+        let mut got = a.join_within(&b, radius);
+        got.sort_unstable();
+        assert_eq!(got, expected);
+    }
+
+    // Joining a tree with itself reports both orders and every self pair
+    let got = a.join_within(&a, 0.0);
+    assert!(got.len() >= players.len());
+}
+
+#[test]
+fn pairs_within_reports_each_pair_once() {
+    let mut seed = 11;
+    let items = random_items(&mut seed, 400, 0);
+    let qt = build(&items, 3);
+
+    for radius in [0.0, 2.0, 7.5, 200.0] {
+        let mut expected: Vec<(u64, u64)> = Vec::new();
+        for (i, p) in items.iter().enumerate() {
+            for q in &items[i + 1..] {
+                if within(p, q, radius) {
+                    expected.push((p.id, q.id));
+                }
+            }
+        }
+        let mut got = qt.pairs_within(radius);
+        got.sort_unstable();
+        assert_eq!(got, expected);
+    }
+
+    // Duplicate positions pair up; an id never pairs with itself
+    let mut dup = QuadTree::new(r(0.0, 0.0, 10.0, 10.0), 1, 4);
+    for (id, x) in [(5, 1.0), (3, 1.0), (5, 1.5)] {
+        assert!(dup.insert(Item { id, point: Point { x, y: 1.0 } }));
+    }
+    let mut got = dup.pairs_within(1.0);
+    got.sort_unstable();
+    assert_eq!(got, vec![(3, 5), (3, 5)]);
+}
@@ -1,4 +1,4 @@
-use fastquadtree::{Point, Rect, Item, QuadTree};
+use fastquadtree::{NearestCursor, Point, QueryScratch, Rect, Item, QuadTree};
 
 fn r(x0: f32, y0: f32, x1: f32, y1: f32) -> Rect<f32> {
     Rect { min_x: x0, min_y: y0, max_x: x1, max_y: y1 }
@@ -85,15 +85,15 @@ fn knn_respects_capacity_and_split() {
     qt.insert(Item { id: 4, point: pt(90.0, 90.0) });
     qt.insert(Item { id: 5, point: pt(12.0, 12.0) });
 
+    // ids 1 and 5 are exactly equidistant; the lower id wins the tie
     let q = pt(11.0, 11.0);
     let nn = qt.nearest_neighbor(q).unwrap();
-    assert_eq!(nn.id, 5);
+    assert_eq!(nn.id, 1);
 
     let res = qt.nearest_neighbors(q, 3);
     let order: Vec<u64> = res.iter().map(|it| it.id).collect();
-    // next nearest after id 5 should be id 1
-    assert_eq!(order[0], 5);
-    assert_eq!(order[1], 1);
+    assert_eq!(order[0], 1);
+    assert_eq!(order[1], 5);
 }
 
 #[test]
@@ -214,3 +214,407 @@ fn midline_and_center_points_are_handled() {
     let nn = qt.nearest_neighbor(pt(50.0, 12.0)).unwrap().id;
     assert!(nn == 2 || nn == 1);
 }
+
+#[test]
+fn nearest_distinct_ids_keeps_closest_position_per_id() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 2, 8);
+    // id 1 has a stale far position and a fresh near one
+    qt.insert(Item { id: 1, point: pt(90.0, 90.0) });
+    qt.insert(Item { id: 1, point: pt(11.0, 10.0) });
+    qt.insert(Item { id: 2, point: pt(13.0, 10.0) });
+    qt.insert(Item { id: 3, point: pt(20.0, 10.0) });
+
+    let q = pt(10.0, 10.0);
+    let res = qt.nearest_distinct_ids(q, 3);
+    assert_eq!(res.iter().map(|it| it.id).collect::<Vec<_>>(), vec![1, 2, 3]);
+    assert_eq!(res[0].point, pt(11.0, 10.0));
+
+    // The far duplicate of id 1 never takes a slot
+    let all = qt.nearest_distinct_ids(q, 10);
+    assert_eq!(all.len(), 3);
+    assert!(all.windows(2).all(|w| dist2(q, w[0].point) <= dist2(q, w[1].point)));
+}
+
+#[test]
+fn nearest_distinct_ids_matches_brute_force_with_many_copies() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 64.0, 64.0), 2, 10);
+    let mut all = Vec::new();
+    // Every id sits at several spots, some stacked on the same point
+    for i in 0..120u64 {
+        let id = i % 17;
+        let p = pt(((i * 37) % 64) as f32, ((i * 11) % 64) as f32);
+        all.push(Item { id, point: p });
+        all.push(Item { id: (id + 5) % 17, point: p });
+    }
+    for it in &all {
+        assert!(qt.insert(*it));
+    }
+
+    let q = pt(30.0, 33.0);
+    let mut best: Vec<(f32, u64)> = Vec::new();
+    for id in 0..17u64 {
+        let d = all.iter().filter(|it| it.id == id).map(|it| dist2(q, it.point)).fold(f32::INFINITY, f32::min);
+        best.push((d, id));
+    }
+    best.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap().then(a.1.cmp(&b.1)));
+
+    for k in [0, 1, 5, 17, 40] {
+        let res = qt.nearest_distinct_ids(q, k);
+        let want: Vec<(f32, u64)> = best.iter().take(k).copied().collect();
+        let got: Vec<(f32, u64)> = res.iter().map(|it| (dist2(q, it.point), it.id)).collect();
+        assert_eq!(got, want, "k = {k}");
+    }
+}
+
+#[test]
+fn top_k_scored_prefers_distant_high_attribute_item() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 2, 8);
+    qt.insert(Item { id: 1, point: pt(51.0, 50.0) });
+    qt.insert(Item { id: 2, point: pt(50.0, 52.0) });
+    qt.insert(Item { id: 3, point: pt(46.5, 50.0) });
+    qt.insert(Item { id: 4, point: pt(90.0, 50.0) });
+    let attr = |id: u64| if id == 3 { 5.0 } else { 0.0 };
+
+    let q = pt(50.0, 50.0);
+    let res = qt.top_k_scored(q, 2, |it, d| 10.0 * attr(it.id) - d);
+    assert_eq!(res.iter().map(|it| it.id).collect::<Vec<_>>(), vec![3, 1]);
+
+    // A pure proximity score matches plain kNN
+    let by_dist = qt.top_k_scored(q, 3, |_, d| -d);
+    assert_eq!(
+        by_dist.iter().map(|it| it.id).collect::<Vec<_>>(),
+        qt.nearest_neighbors(q, 3).iter().map(|it| it.id).collect::<Vec<_>>()
+    );
+    assert!(qt.top_k_scored(q, 0, |_, d| -d).is_empty());
+}
+
+#[test]
+fn within_sparse_tree_excludes_far_items_and_sorts() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 1000.0, 1000.0), 1, 10);
+    qt.insert(Item { id: 1, point: pt(103.0, 100.0) });
+    qt.insert(Item { id: 2, point: pt(100.0, 101.0) });
+    qt.insert(Item { id: 3, point: pt(96.0, 100.0) });
+    qt.insert(Item { id: 4, point: pt(900.0, 900.0) });
+    qt.insert(Item { id: 5, point: pt(10.0, 950.0) });
+
+    let q = pt(100.0, 100.0);
+    let res = qt.nearest_neighbors_within(q, 10, 5.0);
+    assert_eq!(res.iter().map(|it| it.id).collect::<Vec<_>>(), vec![2, 1, 3]);
+    assert_eq!(qt.nearest_neighbors_within(q, 2, 5.0).len(), 2);
+    assert!(qt.nearest_neighbors_within(pt(500.0, 500.0), 3, 50.0).is_empty());
+}
+
+#[test]
+fn nearest_neighbor_filter_skips_rejected_items() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 2, 8);
+    let mut id = 0;
+    for x in 0..10 {
+        for y in 0..10 {
+            qt.insert(Item { id, point: pt(x as f32 * 10.0 + 5.0, y as f32 * 10.0 + 5.0) });
+            id += 1;
+        }
+    }
+    // Even ids are allies, odd ids enemies
+    let q = pt(41.0, 52.0);
+    let enemy = qt.nearest_neighbor_filter(q, |it| it.id % 2 == 1).unwrap();
+    let brute = (0..100u64)
+        .filter(|i| i % 2 == 1)
+        .map(|i| pt((i / 10) as f32 * 10.0 + 5.0, (i % 10) as f32 * 10.0 + 5.0))
+        .map(|p| dist2(q, p))
+        .fold(f32::INFINITY, f32::min);
+    assert_eq!(enemy.id % 2, 1);
+    assert_eq!(dist2(q, enemy.point), brute);
+
+    // Accept-all matches plain nearest_neighbor, reject-all finds nothing
+    assert_eq!(qt.nearest_neighbor_filter(q, |_| true), qt.nearest_neighbor(q));
+    assert!(qt.nearest_neighbor_filter(q, |_| false).is_none());
+}
+
+#[test]
+fn nearest_neighbors_with_dist_reports_euclidean_distance() {
+    let mut qt: QuadTree<f64> = QuadTree::new(
+        Rect { min_x: 0.0, min_y: 0.0, max_x: 100.0, max_y: 100.0 },
+        2,
+        8,
+    );
+    qt.insert(Item { id: 1, point: Point { x: 13.0, y: 14.0 } }); // 5 from (10, 10)
+    qt.insert(Item { id: 2, point: Point { x: 10.0, y: 11.0 } }); // 1
+    qt.insert(Item { id: 3, point: Point { x: 70.0, y: 90.0 } }); // 100
+
+    let res = qt.nearest_neighbors_with_dist(Point { x: 10.0, y: 10.0 }, 3);
+    let got: Vec<(u64, f64)> = res.iter().map(|(it, d)| (it.id, *d)).collect();
+    assert_eq!(got, vec![(2, 1.0), (1, 5.0), (3, 100.0)]);
+}
+
+#[test]
+fn nearest_neighbor_batch_matches_single_queries() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 3, 8);
+    for id in 0..60u64 {
+        qt.insert(Item { id, point: pt((id * 37 % 100) as f32, (id * 53 % 100) as f32) });
+    }
+    let queries: Vec<Point<f32>> = (0..25).map(|i| pt(i as f32 * 4.0, 100.0 - i as f32 * 3.5)).collect();
+    let batch = qt.nearest_neighbor_batch(&queries);
+    assert_eq!(batch.len(), queries.len());
+    for (q, nn) in queries.iter().zip(&batch) {
+        assert_eq!(*nn, qt.nearest_neighbor(*q));
+    }
+
+    let empty = QuadTree::<f32>::new(r(0.0, 0.0, 1.0, 1.0), 4, 4);
+    assert_eq!(empty.nearest_neighbor_batch(&queries[..2]), vec![None, None]);
+}
+
+#[test]
+fn knn_contract_length_and_ordering() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 2, 8);
+    assert!(qt.nearest_neighbors(pt(5.0, 5.0), 3).is_empty());
+
+    for id in 0..25u64 {
+        let v = (id * 13 % 25) as f32 * 4.0 + 0.5;
+        qt.insert(Item { id, point: pt(v, 100.0 - v) });
+    }
+    let q = pt(40.0, 40.0);
+    assert!(qt.nearest_neighbors(q, 0).is_empty());
+
+    for k in [1usize, 5, 25, 26, 100] {
+        let res = qt.nearest_neighbors(q, k);
+        assert_eq!(res.len(), k.min(qt.count_items()));
+        assert!(res.windows(2).all(|w| dist2(q, w[0].point) <= dist2(q, w[1].point)));
+    }
+
+    // A repeated id takes a slot per copy, so the length still tracks count_items
+    qt.insert(Item { id: 3, point: pt(41.0, 41.0) });
+    qt.insert(Item { id: 3, point: pt(41.0, 41.0) });
+    let res = qt.nearest_neighbors(q, 100);
+    assert_eq!(res.len(), 27);
+    assert_eq!(res.len(), qt.count_items());
+    assert_eq!((res[0].id, res[0].point), (3, pt(41.0, 41.0)));
+    assert_eq!((res[1].id, res[1].point), (3, pt(41.0, 41.0)));
+    assert_eq!(res.iter().filter(|it| it.id == 3).count(), 3);
+    assert!(res.windows(2).all(|w| dist2(q, w[0].point) <= dist2(q, w[1].point)));
+    assert_eq!(qt.nearest_neighbors(q, 2).len(), 2);
+    assert_eq!(qt.nearest_distinct_ids(q, 100).len(), 25);
+}
+
+#[test]
+fn nearest_neighbors_approx_bounds_error_by_epsilon() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 1000.0, 1000.0), 4, 12);
+    let mut state = 12345u64;
+    let mut next = || {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (state >> 40) as f32 / (1u64 << 24) as f32 * 1000.0
+    };
+    for id in 0..3000u64 {
+        qt.insert(Item { id, point: pt(next(), next()) });
+    }
+    // A repeated id takes a slot per copy, as in nearest_neighbors
+    qt.insert(Item { id: 7, point: pt(500.5, 500.5) });
+
+    for q in [pt(500.0, 500.0), pt(3.0, 997.0), pt(250.0, 760.0)] {
+        for k in [1usize, 5, 20] {
+            let exact = qt.nearest_neighbors(q, k);
+            assert_eq!(qt.nearest_neighbors_approx(q, k, 0.0), exact);
+
+            for eps in [0.1f64, 0.5, 2.0] {
+                let approx = qt.nearest_neighbors_approx(q, k, eps);
+                assert_eq!(approx.len(), k);
+                assert!(approx.windows(2).all(|w| dist2(q, w[0].point) <= dist2(q, w[1].point)));
+                let true_kth = (dist2(q, exact[k - 1].point) as f64).sqrt();
+                let got_kth = (dist2(q, approx[k - 1].point) as f64).sqrt();
+                assert!(got_kth <= true_kth * (1.0 + eps) + 1e-4);
+            }
+        }
+    }
+    assert!(qt.nearest_neighbors_approx(pt(1.0, 1.0), 0, 0.5).is_empty());
+    assert_eq!(qt.nearest_neighbors_approx(pt(500.0, 500.0), 1, -3.0), qt.nearest_neighbors(pt(500.0, 500.0), 1));
+}
+
+#[test]
+fn equidistant_ties_go_to_the_lowest_id() {
+    // Capacity 1 spreads the tied points over different leaves, so traversal
+    // order alone would not pick the lowest id
+    let mut qt = QuadTree::new(Rect { min_x: 0.0, min_y: 0.0, max_x: 100.0, max_y: 100.0 }, 1, 8);
+    let q = Point { x: 50.0, y: 50.0 };
+    let tied = [(9, 60.0, 50.0), (4, 40.0, 50.0), (7, 50.0, 60.0), (5, 50.0, 40.0)];
+    for &(id, x, y) in &tied {
+        assert!(qt.insert(Item { id, point: Point { x, y } }));
+    }
+    assert!(qt.insert(Item { id: 1, point: Point { x: 90.0, y: 90.0 } }));
+
+    for _ in 0..3 {
+        assert_eq!(qt.nearest_neighbor(q).map(|it| it.id), Some(4));
+    }
+    let ids: Vec<u64> = qt.nearest_neighbors(q, 5).into_iter().map(|it| it.id).collect();
+    assert_eq!(ids, vec![4, 5, 7, 9, 1]);
+
+    // The cap stays exclusive for ties at exactly max_distance
+    assert!(qt.nearest_neighbors_within(q, 2, 10.0).is_empty());
+}
+
+#[test]
+fn nearest_iter_yields_everything_in_distance_order() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 2, 8);
+    let mut seed = 3u64;
+    let mut items = Vec::new();
+    for id in 0..300u64 {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        let p = pt(((seed >> 40) % 1000) as f32 / 10.0, ((seed >> 16) % 1000) as f32 / 10.0);
+        assert!(qt.insert(Item { id, point: p }));
+        items.push(Item { id, point: p });
+    }
+    let q = pt(37.0, 61.0);
+
+    let mut expected = items.clone();
+    expected.sort_by(|a, b| dist2(q, a.point).partial_cmp(&dist2(q, b.point)).unwrap().then(a.id.cmp(&b.id)));
+    let got: Vec<u64> = qt.nearest_iter(q).map(|it| it.id).collect();
+    assert_eq!(got, expected.iter().map(|it| it.id).collect::<Vec<_>>());
+
+    // Prefixes agree with the fixed-k searches
+    assert_eq!(qt.nearest_iter(q).next().map(|it| it.id), qt.nearest_neighbor(q).map(|it| it.id));
+    let k10: Vec<u64> = qt.nearest_iter(q).take(10).map(|it| it.id).collect();
+    assert_eq!(k10, qt.nearest_neighbors(q, 10).into_iter().map(|it| it.id).collect::<Vec<_>>());
+
+    // Stop on a data-dependent condition
+    let first_far = qt.nearest_iter(q).find(|it| dist2(q, it.point) > 400.0).unwrap();
+    assert!(dist2(q, first_far.point) > 400.0);
+
+    let empty = QuadTree::<f32>::new(r(0.0, 0.0, 1.0, 1.0), 2, 4);
+    assert!(empty.nearest_iter(q).next().is_none());
+}
+
+#[test]
+fn query_ordered_by_sorts_rect_matches_by_focus_distance() {
+    let mut qt = QuadTree::new(Rect { min_x: 0.0, min_y: 0.0, max_x: 100.0, max_y: 100.0 }, 2, 8);
+    let mut id = 0u64;
+    for x in (5..100).step_by(10) {
+        for y in (5..100).step_by(10) {
+            qt.insert(Item { id, point: Point { x: x as f32, y: y as f32 } });
+            id += 1;
+        }
+    }
+    let range = Rect { min_x: 20.0, min_y: 30.0, max_x: 70.0, max_y: 90.0 };
+    let focus = Point { x: 0.0, y: 100.0 };
+
+    let got = qt.query_ordered_by(range, focus);
+    let mut want: Vec<Item<f32>> = qt
+        .query(range)
+        .into_iter()
+        .map(|(id, x, y)| Item { id, point: Point { x, y } })
+        .collect();
+    let d2 = |p: Point<f32>| (p.x - focus.x).powi(2) + (p.y - focus.y).powi(2);
+    want.sort_by(|a, b| d2(a.point).partial_cmp(&d2(b.point)).unwrap().then(a.id.cmp(&b.id)));
+    assert_eq!(got.len(), 30);
+    assert_eq!(got, want);
+    assert_eq!(got[0].point, Point { x: 25.0, y: 85.0 });
+
+    // A cursor over the same rect yields the same prefix lazily
+    let mut cursor = NearestCursor::in_rect(focus, range);
+    let first: Vec<Item<f32>> = (0..5).map_while(|_| cursor.next_item(&qt)).collect();
+    assert_eq!(first, got[..5]);
+
+    let outside = Rect { min_x: 200.0, min_y: 0.0, max_x: 300.0, max_y: 10.0 };
+    assert!(qt.query_ordered_by(outside, focus).is_empty());
+}
+
+#[test]
+fn nearest_neighbors_with_scratch_matches_fresh_search() {
+    let mut qt = QuadTree::new(Rect { min_x: 0.0, min_y: 0.0, max_x: 100.0, max_y: 100.0 }, 4, 10);
+    // Deterministic scatter with some duplicate positions to exercise tie-breaks
+    let mut s = 12345u32;
+    let mut next = || {
+        s = s.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        ((s >> 8) % 1000) as f32 / 10.0
+    };
+    for id in 0..500u64 {
+        let point = Point { x: next(), y: next() };
+        qt.insert(Item { id, point });
+        if id % 50 == 0 {
+            qt.insert(Item { id: id + 10_000, point });
+        }
+    }
+
+    let mut scratch = QueryScratch::new();
+    for i in 0..200 {
+        let p = Point { x: next(), y: next() };
+        let k = i % 12;
+        assert_eq!(qt.nearest_neighbors_with_scratch(p, k, &mut scratch), qt.nearest_neighbors(p, k));
+    }
+}
+
+#[test]
+fn query_scratch_is_shared_across_trees_and_mutations() {
+    let mut a = QuadTree::new(Rect { min_x: 0.0, min_y: 0.0, max_x: 100.0, max_y: 100.0 }, 2, 8);
+    let mut b = QuadTree::new(Rect { min_x: -50.0, min_y: -50.0, max_x: 50.0, max_y: 50.0 }, 3, 8);
+    for id in 0..60u64 {
+        let x = (id * 37 % 100) as f32;
+        let y = (id * 53 % 100) as f32;
+        a.insert(Item { id, point: Point { x, y } });
+        b.insert(Item { id, point: Point { x: x - 50.0, y: y - 50.0 } });
+    }
+
+    // One scratch, no borrow held between calls: alternate trees and mutate
+    let mut scratch = QueryScratch::new();
+    let p = Point { x: 20.0, y: 30.0 };
+    for round in 0..5u64 {
+        assert_eq!(a.nearest_neighbors_with_scratch(p, 4, &mut scratch), a.nearest_neighbors(p, 4));
+        assert_eq!(b.nearest_neighbors_with_scratch(p, 4, &mut scratch), b.nearest_neighbors(p, 4));
+        let nearest = a.nearest_neighbors_with_scratch(p, 1, &mut scratch)[0];
+        assert!(a.delete(nearest.id, nearest.point));
+        b.insert(Item { id: 100 + round, point: Point { x: 20.0 + round as f32, y: 30.0 } });
+    }
+}
+
+#[test]
+fn query_scratch_keeps_its_stack_capacity_between_searches() {
+    let mut qt = QuadTree::new(Rect { min_x: 0.0, min_y: 0.0, max_x: 100.0, max_y: 100.0 }, 2, 10);
+    for id in 0..400u64 {
+        let point = Point { x: (id * 37 % 100) as f32 + 0.5, y: (id * 61 % 100) as f32 + 0.25 };
+        qt.insert(Item { id, point });
+    }
+
+    let mut scratch = QueryScratch::new();
+    assert_eq!(scratch.stack_capacity(), 0);
+    let p = Point { x: 42.0, y: 17.0 };
+    let first = qt.nearest_neighbors_with_scratch(p, 8, &mut scratch);
+    let cap = scratch.stack_capacity();
+    assert!(cap > 0);
+
+    // Repeating the search neither frees nor grows the stack
+    for _ in 0..20 {
+        assert_eq!(qt.nearest_neighbors_with_scratch(p, 8, &mut scratch), first);
+        assert_eq!(scratch.stack_capacity(), cap);
+    }
+
+    // Nor does a search on another tree that fits in the same stack
+    let small = QuadTree::new(Rect { min_x: 0.0, min_y: 0.0, max_x: 10.0, max_y: 10.0 }, 4, 4);
+    assert!(small.nearest_neighbors_with_scratch(p, 3, &mut scratch).is_empty());
+    assert_eq!(scratch.stack_capacity(), cap);
+}
+
+#[test]
+fn nearest_neighbors_approx_at_zero_epsilon_breaks_ties_like_nearest_neighbors() {
+    // Rings of equidistant points spread over many leaves, inserted with the
+    // higher ids first, plus repeated ids
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 1, 10);
+    let q = pt(50.0, 50.0);
+    let mut id = 40u64;
+    for ring in 1..=4 {
+        let d = ring as f32 * 5.0;
+        for (dx, dy) in [(d, 0.0), (0.0, d), (-d, 0.0), (0.0, -d)] {
+            qt.insert(Item { id, point: pt(50.0 + dx, 50.0 + dy) });
+            id -= 1;
+        }
+    }
+    qt.insert(Item { id: 2, point: pt(55.0, 50.0) });
+    qt.insert(Item { id: 2, point: pt(50.0, 40.0) });
+    qt.insert(Item { id: 90, point: pt(45.0, 50.0) });
+
+    for k in 1..=qt.count_items() + 1 {
+        let exact = qt.nearest_neighbors(q, k);
+        assert_eq!(qt.nearest_neighbors_approx(q, k, 0.0), exact, "k = {k}");
+        assert!(exact.windows(2).all(|w| {
+            let (a, b) = (dist2(q, w[0].point), dist2(q, w[1].point));
+            a < b || (a == b && w[0].id <= w[1].id)
+        }));
+    }
+    assert_eq!(qt.nearest_neighbors_approx(q, 100, 0.0).len(), qt.count_items());
+}
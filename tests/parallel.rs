@@ -0,0 +1,39 @@
+#![cfg(feature = "rayon")]
+
+use fastquadtree::{Item, Point, QuadTree, Rect};
+
+fn lcg(seed: &mut u64) -> f32 {
+    *seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+    ((*seed >> 40) as f32) / ((1u64 << 24) as f32)
+}
+
+fn random_tree(seed: &mut u64) -> QuadTree<f32> {
+    let mut qt = QuadTree::new(Rect { min_x: 0.0, min_y: 0.0, max_x: 1000.0, max_y: 1000.0 }, 8, 12);
+    for id in 0..5000 {
+        let point = Point { x: lcg(seed) * 1000.0, y: lcg(seed) * 1000.0 };
+        assert!(qt.insert(Item { id, point }));
+    }
+    qt
+}
+
+#[test]
+fn parallel_batches_match_sequential() {
+    let mut seed = 2024u64;
+    let qt = random_tree(&mut seed);
+
+    let rects: Vec<Rect<f32>> = (0..200)
+        .map(|_| {
+            let (x, y) = (lcg(&mut seed) * 900.0, lcg(&mut seed) * 900.0);
+            let (w, h) = (lcg(&mut seed) * 100.0, lcg(&mut seed) * 100.0);
+            Rect { min_x: x, min_y: y, max_x: x + w, max_y: y + h }
+        })
+        .collect();
+    let par = qt.query_batch_par(&rects);
+    let seq: Vec<_> = rects.iter().map(|&r| qt.query(r)).collect();
+    assert_eq!(par, seq);
+
+    let points: Vec<Point<f32>> = (0..500)
+        .map(|_| Point { x: lcg(&mut seed) * 1000.0, y: lcg(&mut seed) * 1000.0 })
+        .collect();
+    assert_eq!(qt.nearest_neighbor_batch_par(&points), qt.nearest_neighbor_batch(&points));
+}
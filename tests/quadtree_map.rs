@@ -0,0 +1,53 @@
+use fastquadtree::{ConfigError, Point, QuadTreeMap, Rect};
+
+fn r(x0: f32, y0: f32, x1: f32, y1: f32) -> Rect<f32> {
+    Rect { min_x: x0, min_y: y0, max_x: x1, max_y: y1 }
+}
+fn pt(x: f32, y: f32) -> Point<f32> { Point { x, y } }
+
+#[test]
+fn values_follow_their_points() {
+    let mut map: QuadTreeMap<f32, String> = QuadTreeMap::new(r(0.0, 0.0, 100.0, 100.0), 2, 8).unwrap();
+    for (id, x, y) in [(1, 10.0, 10.0), (2, 20.0, 15.0), (3, 80.0, 80.0), (4, 12.0, 90.0)] {
+        assert_eq!(map.insert(id, pt(x, y), format!("agent-{id}")), Ok(None));
+    }
+    assert_eq!(map.len(), 4);
+    assert_eq!(map.get(2).map(String::as_str), Some("agent-2"));
+
+    let mut hits = map.query(r(0.0, 0.0, 50.0, 50.0));
+    hits.sort_by_key(|h| h.0);
+    assert_eq!(hits.len(), 2);
+    assert_eq!((hits[0].0, hits[0].1, hits[0].2.as_str()), (1, pt(10.0, 10.0), "agent-1"));
+    assert_eq!(hits[1].2, "agent-2");
+
+    let nn = map.nearest_neighbors(pt(79.0, 79.0), 1);
+    assert_eq!(nn[0].2, "agent-3");
+}
+
+#[test]
+fn reinserting_an_id_moves_it_and_replaces_the_value() {
+    let mut map: QuadTreeMap<f32, u32> = QuadTreeMap::new(r(0.0, 0.0, 100.0, 100.0), 4, 8).unwrap();
+    assert_eq!(map.insert(7, pt(5.0, 5.0), 1), Ok(None));
+    assert_eq!(map.insert(7, pt(95.0, 95.0), 2), Ok(Some(1)));
+    assert_eq!(map.len(), 1);
+    assert_eq!(map.tree().count_items(), 1);
+    assert_eq!(map.position(7), Some(pt(95.0, 95.0)));
+    assert!(map.query(r(0.0, 0.0, 10.0, 10.0)).is_empty());
+
+    // Out of bounds hands the value back and keeps the old entry
+    assert_eq!(map.insert(7, pt(150.0, 5.0), 3), Err(3));
+    assert_eq!(map.get(7), Some(&2));
+
+    *map.get_mut(7).unwrap() += 10;
+    assert_eq!(map.remove(7), Some(12));
+    assert!(map.is_empty());
+    assert_eq!(map.tree().count_items(), 0);
+    assert_eq!(map.remove(7), None);
+}
+
+#[test]
+fn new_rejects_degenerate_boundaries() {
+    for b in [r(5.0, 0.0, 5.0, 10.0), r(0.0, 10.0, 10.0, 0.0), r(f32::NAN, 0.0, 10.0, 10.0)] {
+        assert_eq!(QuadTreeMap::<f32, u32>::new(b, 4, 8).err(), Some(ConfigError::InvalidBoundary));
+    }
+}
@@ -1,4 +1,4 @@
-use fastquadtree::{Point, Rect, Item, QuadTree};
+use fastquadtree::{Point, Rect, Item, QuadTree, RectCursor};
 
 fn r(x0: f32, y0: f32, x1: f32, y1: f32) -> Rect<f32> {
     Rect { min_x: x0, min_y: y0, max_x: x1, max_y: y1 }
@@ -134,3 +134,415 @@ fn triple_insert_query() {
     let hits = qt.query(r(0.0, 0.0, 84.0, 84.0));
     assert_eq!(ids(&hits), vec![1,2,3]);
 }
+
+fn lcg(seed: &mut u64) -> f32 {
+    *seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+    ((*seed >> 40) as f32) / ((1u64 << 24) as f32)
+}
+
+fn path_len(v: &[Item<f32>]) -> f32 {
+    v.windows(2)
+        .map(|w| {
+            let dx = w[0].point.x - w[1].point.x;
+            let dy = w[0].point.y - w[1].point.y;
+            (dx * dx + dy * dy).sqrt()
+        })
+        .sum()
+}
+
+#[test]
+fn morton_ordered_query_is_permutation_with_better_locality() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 4, 8);
+    let mut seed = 42u64;
+    let mut inserted = Vec::new();
+    for id in 0..500 {
+        let it = Item { id, point: pt(lcg(&mut seed) * 100.0, lcg(&mut seed) * 100.0) };
+        assert!(qt.insert(it));
+        inserted.push(it);
+    }
+
+    let range = r(10.0, 10.0, 90.0, 90.0);
+    let ordered = qt.query_morton_ordered(range);
+    let plain = qt.query(range);
+    assert_eq!(ordered.len(), plain.len());
+    let mut ordered_ids: Vec<u64> = ordered.iter().map(|it| it.id).collect();
+    ordered_ids.sort_unstable();
+    assert_eq!(ordered_ids, ids(&plain));
+
+    // Same matches, but in insertion order
+    let by_insertion: Vec<Item<f32>> = inserted
+        .into_iter()
+        .filter(|it| range.contains(&it.point))
+        .collect();
+    assert!(path_len(&ordered) < path_len(&by_insertion));
+}
+
+#[test]
+fn count_in_rect_filtered_matches_query_then_filter() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 2, 8);
+    let mut seed = 7u64;
+    for id in 0..300 {
+        assert!(qt.insert(Item { id, point: pt(lcg(&mut seed) * 100.0, lcg(&mut seed) * 100.0) }));
+    }
+    let allowed: std::collections::HashSet<u64> = (0..300).filter(|id| id % 3 == 0).collect();
+
+    for range in [r(0.0, 0.0, 100.0, 100.0), r(20.0, 35.0, 70.0, 60.0), r(90.0, 90.0, 95.0, 95.0)] {
+        let expected = qt
+            .query(range)
+            .into_iter()
+            .filter(|(id, _, _)| allowed.contains(id))
+            .count();
+        assert_eq!(qt.count_in_rect_filtered(range, &allowed), expected);
+    }
+    assert_eq!(qt.count_in_rect_filtered(r(0.0, 0.0, 100.0, 100.0), &Default::default()), 0);
+}
+
+#[test]
+fn halfplane_query_diagonal() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 2, 8);
+    let mut seed = 11u64;
+    let mut items = Vec::new();
+    for id in 0..400 {
+        let it = Item { id, point: pt(lcg(&mut seed) * 100.0, lcg(&mut seed) * 100.0) };
+        assert!(qt.insert(it));
+        items.push(it);
+    }
+    // Points on the line x + y = 100 are included
+    assert!(qt.insert(Item { id: 1000, point: pt(30.0, 70.0) }));
+    items.push(Item { id: 1000, point: pt(30.0, 70.0) });
+
+    let hits = qt.query_halfplane(pt(50.0, 50.0), pt(1.0, 1.0));
+    let mut got: Vec<u64> = hits.iter().map(|it| it.id).collect();
+    got.sort_unstable();
+    let mut expected: Vec<u64> = items
+        .iter()
+        .filter(|it| it.point.x + it.point.y >= 100.0)
+        .map(|it| it.id)
+        .collect();
+    expected.sort_unstable();
+    assert_eq!(got, expected);
+    assert!(got.contains(&1000));
+
+    // Flipping the normal selects the complement plus the points on the line
+    let below = qt.query_halfplane(pt(50.0, 50.0), pt(-1.0, -1.0));
+    let on_line = items.iter().filter(|it| it.point.x + it.point.y == 100.0).count();
+    assert_eq!(below.len() + hits.len(), items.len() + on_line);
+}
+
+#[test]
+fn reduce_tag_in_rect_matches_manual() {
+    use fastquadtree::ReduceOp;
+    use std::collections::HashMap;
+
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 2, 8);
+    let mut seed = 5u64;
+    let mut items = Vec::new();
+    let mut tags = HashMap::new();
+    for id in 0..300u64 {
+        let it = Item { id, point: pt(lcg(&mut seed) * 100.0, lcg(&mut seed) * 100.0) };
+        assert!(qt.insert(it));
+        items.push(it);
+        // Every third item is untagged
+        if id % 3 != 0 {
+            tags.insert(id, (id % 17) as f64 - 4.0);
+        }
+    }
+
+    let range = r(15.0, 30.0, 70.0, 90.0);
+    let vals: Vec<f64> = items
+        .iter()
+        .filter(|it| range.contains(&it.point))
+        .filter_map(|it| tags.get(&it.id).copied())
+        .collect();
+    assert!(!vals.is_empty());
+    let tag = |id: u64| tags.get(&id).copied();
+
+    let sum: f64 = vals.iter().sum();
+    assert_eq!(qt.reduce_tag_in_rect(range, ReduceOp::Sum, tag), Some(sum));
+    assert_eq!(qt.reduce_tag_in_rect(range, ReduceOp::Mean, tag), Some(sum / vals.len() as f64));
+    assert_eq!(
+        qt.reduce_tag_in_rect(range, ReduceOp::Min, tag),
+        vals.iter().copied().reduce(f64::min)
+    );
+    assert_eq!(
+        qt.reduce_tag_in_rect(range, ReduceOp::Max, tag),
+        vals.iter().copied().reduce(f64::max)
+    );
+    assert_eq!(qt.reduce_tag_in_rect(r(0.0, 0.0, 100.0, 100.0), ReduceOp::Sum, |_| None), None);
+}
+
+#[test]
+fn iter_yields_every_item_depth_first() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 2, 8);
+    let mut seed = 23u64;
+    for id in 0..500 {
+        assert!(qt.insert(Item { id, point: pt(lcg(&mut seed) * 100.0, lcg(&mut seed) * 100.0) }));
+    }
+
+    let first: Vec<Item<f32>> = qt.iter().copied().collect();
+    assert_eq!(first.len(), qt.count_items());
+    let mut ids: Vec<u64> = first.iter().map(|it| it.id).collect();
+    ids.sort_unstable();
+    assert_eq!(ids, (0..500).collect::<Vec<_>>());
+
+    // Deterministic, and matches the unbounded cursor used by the Python iterator
+    assert_eq!(qt.iter().copied().collect::<Vec<_>>(), first);
+    let mut cursor = fastquadtree::CircleCursor::unbounded();
+    let via_cursor: Vec<Item<f32>> = std::iter::from_fn(|| cursor.next_item(&qt)).collect();
+    assert_eq!(via_cursor, first);
+
+    // Depth-first with child 0 first: the first item lies in the lower-left quadrant
+    assert!(first[0].point.x < 50.0 && first[0].point.y < 50.0);
+    assert!(QuadTree::<f32>::new(r(0.0, 0.0, 1.0, 1.0), 4, 4).iter().next().is_none());
+}
+
+#[test]
+fn count_in_rect_matches_query_len() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 4, 10);
+    let mut seed = 99u64;
+    for id in 0..2000 {
+        assert!(qt.insert(Item { id, point: pt(lcg(&mut seed) * 100.0, lcg(&mut seed) * 100.0) }));
+    }
+    // A heatmap grid of cells plus a few odd shapes
+    for gx in 0..10 {
+        for gy in 0..10 {
+            let cell = r(gx as f32 * 10.0, gy as f32 * 10.0, gx as f32 * 10.0 + 10.0, gy as f32 * 10.0 + 10.0);
+            assert_eq!(qt.count_in_rect(cell), qt.query(cell).len());
+        }
+    }
+    for q in [r(0.0, 0.0, 100.0, 100.0), r(-50.0, -50.0, 200.0, 200.0), r(13.3, 7.1, 61.9, 88.8), r(200.0, 200.0, 300.0, 300.0)] {
+        assert_eq!(qt.count_in_rect(q), qt.query(q).len());
+    }
+}
+
+#[test]
+fn for_each_in_rect_visits_query_results_in_order() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 3, 10);
+    let mut seed = 314u64;
+    for id in 0..800 {
+        assert!(qt.insert(Item { id, point: pt(lcg(&mut seed) * 100.0, lcg(&mut seed) * 100.0) }));
+    }
+
+    let range = r(20.0, 10.0, 75.0, 60.0);
+    let mut seen = Vec::new();
+    let (mut sx, mut sy, mut n) = (0.0f64, 0.0f64, 0usize);
+    qt.for_each_in_rect(range, |it| {
+        seen.push((it.id, it.point.x, it.point.y));
+        sx += it.point.x as f64;
+        sy += it.point.y as f64;
+        n += 1;
+    });
+    let expected = qt.query(range);
+    assert_eq!(seen, expected);
+
+    let ex = expected.iter().map(|t| t.1 as f64).sum::<f64>() / n as f64;
+    let ey = expected.iter().map(|t| t.2 as f64).sum::<f64>() / n as f64;
+    assert_eq!((sx / n as f64, sy / n as f64), (ex, ey));
+}
+
+#[test]
+fn query_into_reuses_buffer_with_identical_results() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 4, 10);
+    let mut seed = 99u64;
+    for id in 0..500 {
+        assert!(qt.insert(Item { id, point: pt(lcg(&mut seed) * 100.0, lcg(&mut seed) * 100.0) }));
+    }
+
+    let mut buf = Vec::new();
+    qt.query_into(r(0.0, 0.0, 100.0, 100.0), &mut buf);
+    assert_eq!(buf.len(), 500);
+    let cap = buf.capacity();
+
+    for q in [r(10.0, 10.0, 20.0, 20.0), r(50.0, 0.0, 51.0, 100.0), r(200.0, 200.0, 300.0, 300.0)] {
+        qt.query_into(q, &mut buf);
+        assert_eq!(buf, qt.query(q));
+        assert_eq!(buf.capacity(), cap);
+    }
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn query_ids_matches_query_order() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 4, 10);
+    let mut seed = 7u64;
+    for id in 0..300 {
+        assert!(qt.insert(Item { id, point: pt(lcg(&mut seed) * 100.0, lcg(&mut seed) * 100.0) }));
+    }
+    for q in [r(0.0, 0.0, 100.0, 100.0), r(30.0, 30.0, 60.0, 45.0), r(-10.0, -10.0, -1.0, -1.0)] {
+        let expected: Vec<u64> = qt.query(q).into_iter().map(|t| t.0).collect();
+        assert_eq!(qt.query_ids(q), expected);
+    }
+}
+
+#[test]
+fn query_limit_stops_at_limit() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 4, 8);
+    for i in 0..50u64 {
+        let f = i as f32;
+        assert!(qt.insert(Item { id: i, point: pt(f, f) }));
+    }
+    let range = r(10.0, 10.0, 40.0, 40.0);
+    let all = ids(&qt.query(range));
+    assert_eq!(all.len(), 30);
+
+    let got = ids(&qt.query_limit(range, 5));
+    assert_eq!(got.len(), 5);
+    assert!(got.iter().all(|id| all.contains(id)));
+
+    assert!(qt.query_limit(range, 0).is_empty());
+    assert_eq!(qt.query_limit(range, 100).len(), 30);
+}
+
+#[test]
+fn query_sorted_orders_by_id_then_position() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 2, 8);
+    let pts = [(7, 80.0, 80.0), (3, 10.0, 90.0), (7, 20.0, 5.0), (1, 60.0, 10.0), (3, 10.0, 20.0)];
+    for &(id, x, y) in &pts {
+        assert!(qt.insert(Item { id, point: pt(x, y) }));
+    }
+    let got: Vec<(u64, f32, f32)> = qt
+        .query_sorted(r(0.0, 0.0, 100.0, 100.0))
+        .into_iter()
+        .map(|it| (it.id, it.point.x, it.point.y))
+        .collect();
+    assert_eq!(
+        got,
+        vec![(1, 60.0, 10.0), (3, 10.0, 20.0), (3, 10.0, 90.0), (7, 20.0, 5.0), (7, 80.0, 80.0)]
+    );
+}
+
+#[test]
+fn rect_cursor_chunks_cover_query_results() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 2, 8);
+    let mut id = 0;
+    for x in 0..20 {
+        for y in 0..20 {
+            assert!(qt.insert(Item { id, point: pt(x as f32 * 5.0, y as f32 * 5.0) }));
+            id += 1;
+        }
+    }
+    let range = r(12.0, 7.0, 61.0, 48.0);
+    let expected = ids(&qt.query(range));
+
+    let mut cursor = RectCursor::new(range);
+    let mut chunk = Vec::new();
+    let mut got = Vec::new();
+    let mut chunks = 0;
+    loop {
+        chunk.clear();
+        let n = cursor.next_chunk(&qt, 7, &mut chunk);
+        assert_eq!(n, chunk.len());
+        assert!(n <= 7);
+        if n == 0 {
+            break;
+        }
+        chunks += 1;
+        got.extend(chunk.iter().map(|it| (it.id, it.point.x, it.point.y)));
+    }
+    assert_eq!(ids(&got), expected);
+    assert_eq!(chunks, expected.len().div_ceil(7));
+    assert!(cursor.next_item(&qt).is_none());
+}
+
+#[test]
+fn try_for_each_in_rect_stops_on_break() {
+    use std::ops::ControlFlow;
+
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 2, 8);
+    for i in 0..40u64 {
+        let f = i as f32 * 2.5;
+        assert!(qt.insert(Item { id: i, point: pt(f, 100.0 - f - 1.0) }));
+    }
+    let range = r(0.0, 0.0, 100.0, 100.0);
+
+    let mut seen = 0;
+    let flow = qt.try_for_each_in_rect(range, |it| {
+        seen += 1;
+        if it.id == 17 { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+    });
+    assert!(flow.is_break());
+    // Visits follow `query` order and nothing after the break is seen
+    let pos = qt.query(range).iter().position(|it| it.0 == 17).unwrap();
+    assert_eq!(seen, pos + 1);
+
+    let mut all = 0;
+    assert!(qt.try_for_each_in_rect(range, |_| { all += 1; ControlFlow::Continue(()) }).is_continue());
+    assert_eq!(all, 40);
+
+    assert!(qt.any_in_rect(range, |it| it.id == 17));
+    assert!(!qt.any_in_rect(range, |it| it.id == 99));
+    assert!(!qt.any_in_rect(r(0.0, 0.0, 1.0, 1.0), |_| true));
+}
+
+#[test]
+fn bounds_intersect_short_circuits_outside_queries() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 4, 8);
+    assert!(qt.insert(Item { id: 1, point: pt(50.0, 50.0) }));
+
+    assert!(qt.bounds_intersect(&r(90.0, 90.0, 200.0, 200.0)));
+    assert!(qt.bounds_intersect(&r(-10.0, -10.0, 500.0, 500.0)));
+    // Sharing only an edge is outside
+    assert!(!qt.bounds_intersect(&r(100.0, 0.0, 200.0, 100.0)));
+    assert!(!qt.bounds_intersect(&r(-50.0, -50.0, -1.0, -1.0)));
+
+    let outside = r(150.0, 150.0, 200.0, 200.0);
+    assert!(qt.query(outside).is_empty());
+    assert_eq!(qt.count_in_rect(outside), 0);
+    assert_eq!(qt.count_in_rect(r(0.0, 0.0, 100.0, 100.0)), 1);
+}
+
+#[test]
+fn centroid_in_rect_averages_matching_points() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 2, 8);
+    for (id, x, y) in [(1, 10.0, 10.0), (2, 20.0, 30.0), (3, 30.0, 20.0), (4, 90.0, 90.0)] {
+        assert!(qt.insert(Item { id, point: pt(x, y) }));
+    }
+    assert_eq!(qt.centroid_in_rect(r(0.0, 0.0, 50.0, 50.0)), Some(Point { x: 20.0, y: 20.0 }));
+    assert_eq!(qt.centroid_in_rect(r(60.0, 60.0, 70.0, 70.0)), None);
+
+    // Integer coords keep the fractional mean
+    let mut qi = QuadTree::new(Rect { min_x: 0i32, min_y: 0, max_x: 10, max_y: 10 }, 4, 4);
+    assert!(qi.insert(Item { id: 1, point: Point { x: 1, y: 2 } }));
+    assert!(qi.insert(Item { id: 2, point: Point { x: 2, y: 2 } }));
+    assert_eq!(qi.centroid_in_rect(qi.boundary), Some(Point { x: 1.5, y: 2.0 }));
+}
+
+#[test]
+fn extent_in_rect_bounds_matching_points() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 2, 8);
+    for (id, x, y) in [(1, 12.0, 40.0), (2, 35.0, 5.0), (3, 20.0, 22.0), (4, 90.0, 90.0)] {
+        assert!(qt.insert(Item { id, point: pt(x, y) }));
+    }
+    assert_eq!(qt.extent_in_rect(r(0.0, 0.0, 50.0, 50.0)), Some(r(12.0, 5.0, 35.0, 40.0)));
+    assert_eq!(qt.extent_in_rect(r(80.0, 80.0, 100.0, 100.0)), Some(r(90.0, 90.0, 90.0, 90.0)));
+    assert_eq!(qt.extent_in_rect(r(60.0, 0.0, 70.0, 10.0)), None);
+}
+
+#[test]
+fn query_inclusive_keeps_points_on_max_edges() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 1, 6);
+    let pts = [
+        (1, 10.0, 10.0),
+        (2, 50.0, 20.0),
+        (3, 20.0, 50.0),
+        (4, 50.0, 50.0),
+        (5, 50.1, 30.0),
+        (6, 9.9, 30.0),
+    ];
+    for &(id, x, y) in &pts {
+        assert!(qt.insert(Item { id, point: pt(x, y) }));
+    }
+    let range = r(10.0, 10.0, 50.0, 50.0);
+
+    // Half-open query drops everything sitting on max_x or max_y
+    assert_eq!(ids(&qt.query(range)), vec![1]);
+
+    let mut got: Vec<u64> = qt.query_inclusive(range).iter().map(|it| it.id).collect();
+    got.sort_unstable();
+    assert_eq!(got, vec![1, 2, 3, 4]);
+
+    // A degenerate range picks exactly one point
+    let pick = qt.query_inclusive(r(50.0, 50.0, 50.0, 50.0));
+    assert_eq!(pick, vec![Item { id: 4, point: pt(50.0, 50.0) }]);
+    assert!(qt.query_inclusive(r(60.0, 60.0, 70.0, 70.0)).is_empty());
+}
@@ -0,0 +1,136 @@
+use fastquadtree::{Item, Point, QuadTree, Rect};
+
+fn r(x0: f32, y0: f32, x1: f32, y1: f32) -> Rect<f32> {
+    Rect { min_x: x0, min_y: y0, max_x: x1, max_y: y1 }
+}
+fn pt(x: f32, y: f32) -> Point<f32> { Point { x, y } }
+
+fn sorted_items(qt: &QuadTree<f32>) -> Vec<Item<f32>> {
+    let b = qt.get_all_node_boundaries()[0];
+    let mut out: Vec<Item<f32>> = qt
+        .query(b)
+        .into_iter()
+        .map(|(id, x, y)| Item { id, point: pt(x, y) })
+        .collect();
+    out.sort_unstable_by_key(|it| it.id);
+    out
+}
+
+#[test]
+fn rotate_full_turn_returns_to_original_positions() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 3, 10);
+    let mut id = 0;
+    for i in 0..10 {
+        for j in 0..10 {
+            assert!(qt.insert(Item { id, point: pt(i as f32 * 9.0 + 2.0, j as f32 * 9.0 + 3.0) }));
+            id += 1;
+        }
+    }
+    let before = sorted_items(&qt);
+
+    let evicted = qt.rotate_rebuild(pt(50.0, 50.0), std::f64::consts::TAU);
+    assert!(evicted.is_empty());
+
+    let after = sorted_items(&qt);
+    assert_eq!(after.len(), before.len());
+    for (a, b) in before.iter().zip(&after) {
+        assert_eq!(a.id, b.id);
+        assert!((a.point.x - b.point.x).abs() < 1e-3);
+        assert!((a.point.y - b.point.y).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn rotate_grows_bounds_to_fit() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 10.0, 10.0), 2, 8);
+    assert!(qt.insert(Item { id: 1, point: pt(9.0, 1.0) }));
+    assert!(qt.insert(Item { id: 2, point: pt(1.0, 1.0) }));
+
+    // Quarter turn about the origin sends (9, 1) to (-1, 9)
+    let evicted = qt.rotate_rebuild(pt(0.0, 0.0), std::f64::consts::FRAC_PI_2);
+    assert!(evicted.is_empty());
+    assert_eq!(qt.count_items(), 2);
+
+    let root = qt.get_all_node_boundaries()[0];
+    assert!(root.min_x <= -1.0 && root.max_x >= 10.0);
+
+    let hit = qt.nearest_neighbor(pt(-1.0, 9.0)).unwrap();
+    assert_eq!(hit.id, 1);
+    assert!((hit.point.x + 1.0).abs() < 1e-4 && (hit.point.y - 9.0).abs() < 1e-4);
+}
+
+#[test]
+fn rotate_integer_coords_round() {
+    let mut qt: QuadTree<i32> = QuadTree::new(Rect { min_x: 0, min_y: 0, max_x: 100, max_y: 100 }, 4, 8);
+    assert!(qt.insert(Item { id: 1, point: Point { x: 10, y: 20 } }));
+    assert!(qt.rotate_rebuild(Point { x: 50, y: 50 }, std::f64::consts::TAU).is_empty());
+    assert_eq!(qt.nearest_neighbor(Point { x: 10, y: 20 }).unwrap().point, Point { x: 10, y: 20 });
+}
+
+#[test]
+fn rotate_bounds_stay_in_range_at_i32_max() {
+    let top = i32::MAX;
+    let bounds = Rect { min_x: top - 100, min_y: -10, max_x: top - 5, max_y: 10 };
+    let mut qt: QuadTree<i32> = QuadTree::new(bounds, 1, 8);
+    assert!(qt.insert(Item { id: 1, point: Point { x: top - 19, y: 0 } }));
+    assert!(qt.insert(Item { id: 2, point: Point { x: top - 20, y: 0 } }));
+    assert!(qt.insert(Item { id: 3, point: Point { x: top - 15, y: 5 } }));
+
+    // A half turn about (top - 10, 0) sends id 1 to top - 1 and id 2 to top,
+    // which no exclusive max edge can cover
+    let evicted = qt.rotate_rebuild(Point { x: top - 10, y: 0 }, std::f64::consts::PI);
+    assert_eq!(evicted, vec![Item { id: 2, point: Point { x: top - 20, y: 0 } }]);
+    assert_eq!(qt.boundary.max_x, top);
+    assert!(qt.contains(1, Point { x: top - 1, y: 0 }));
+    assert!(qt.contains(3, Point { x: top - 5, y: -5 }));
+    assert_eq!(qt.count_items(), 2);
+}
+
+#[test]
+fn rotate_bounds_cover_large_f32_coordinates() {
+    // Near 1e9 the f32 spacing is 64, so adding 1 to an edge does nothing
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 2, 8);
+    assert!(qt.insert(Item { id: 1, point: pt(10.0, 0.0) }));
+    assert!(qt.insert(Item { id: 2, point: pt(20.0, 50.0) }));
+
+    let evicted = qt.rotate_rebuild(pt(5.0e8, 0.0), std::f64::consts::PI);
+    assert!(evicted.is_empty());
+    assert_eq!(qt.count_items(), 2);
+    let far = qt.nearest_neighbor(pt(1.0e9, 0.0)).unwrap();
+    assert_eq!(far.id, 1);
+    assert!(qt.boundary.contains(&far.point));
+    assert!(qt.boundary.max_x > far.point.x);
+}
+
+#[test]
+fn rebuild_matches_fresh_bulk_load() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 2, 10);
+    // Empty split nodes that no delete path will ever merge
+    qt.pre_subdivide(4);
+    let mut points = Vec::new();
+    for id in 0..200u64 {
+        let p = pt((id * 37 % 200) as f32 * 0.5, (id * 91 % 200) as f32 * 0.5);
+        assert!(qt.insert(Item { id, point: p }));
+        points.push(p);
+    }
+    for id in 0..200u64 {
+        if id % 10 != 0 {
+            assert!(qt.delete(id, points[id as usize]));
+        }
+    }
+    let before = sorted_items(&qt);
+    let degraded_nodes = qt.node_count();
+
+    qt.rebuild();
+    assert_eq!(sorted_items(&qt), before);
+    assert!(qt.node_count() < degraded_nodes);
+
+    let mut fresh = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 2, 10);
+    fresh.bulk_load(&before);
+    assert_eq!(qt.node_count(), fresh.node_count());
+    assert_eq!(qt.get_all_node_boundaries(), fresh.get_all_node_boundaries());
+
+    // Idempotent
+    qt.rebuild();
+    assert_eq!(qt.node_count(), fresh.node_count());
+}
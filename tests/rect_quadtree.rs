@@ -324,3 +324,32 @@ fn nearest_neighbors_with_deep_tree() {
         assert!(item.id < 20);
     }
 }
+
+#[test]
+fn broad_phase_query_matches_brute_force() {
+    // Deterministic boxes of mixed sizes, so some straddle split lines and
+    // stay at internal nodes while others sink to leaves
+    let mut state = 0x2545_f491u64;
+    let mut next = || {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        ((state >> 33) % 1000) as f32 / 10.0
+    };
+    let mut qt = RectQuadTree::new(r(0.0, 0.0, 100.0, 100.0), 4, 10);
+    let mut all = Vec::new();
+    for id in 0..300u64 {
+        let (x, y) = (next(), next());
+        let (w, h) = (next() / 8.0, next() / 8.0);
+        let it = item(id, x, y, (x + w).min(100.0), (y + h).min(100.0));
+        assert!(qt.insert(it));
+        all.push(it);
+    }
+
+    let overlaps = |a: &Rect<f32>, b: &Rect<f32>| {
+        a.min_x <= b.max_x && a.max_x >= b.min_x && a.min_y <= b.max_y && a.max_y >= b.min_y
+    };
+    for q in [r(10.0, 10.0, 30.0, 25.0), r(49.0, 0.0, 51.0, 100.0), r(0.0, 0.0, 100.0, 100.0), r(70.0, 70.0, 70.5, 70.5)] {
+        let mut expected: Vec<u64> = all.iter().filter(|it| overlaps(&it.rect, &q)).map(|it| it.id).collect();
+        expected.sort_unstable();
+        assert_eq!(ids(&qt.query(q)), expected);
+    }
+}
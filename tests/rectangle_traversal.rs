@@ -95,4 +95,37 @@ fn test_get_all_node_boundaries_same_quadrant_deep_split() {
         assert!(rectangles.contains(grandchild), 
                 "Missing expected grandchild rectangle: {:?}", grandchild);
     }
-}
\ No newline at end of file
+}
+#[test]
+fn test_pre_subdivide_uniform_grid() {
+    let boundary = Rect { min_x: 0.0, min_y: 0.0, max_x: 80.0, max_y: 80.0 };
+    let mut qt = QuadTree::new(boundary, 4, 8);
+    qt.insert(Item { id: 1, point: Point { x: 5.0, y: 5.0 } });
+    qt.pre_subdivide(2);
+
+    // root + 4 + 16 nodes, every leaf a 20x20 cell
+    let rectangles = qt.get_all_node_boundaries();
+    assert_eq!(rectangles.len(), 21);
+    for gx in 0..4 {
+        for gy in 0..4 {
+            let cell = Rect {
+                min_x: gx as f32 * 20.0,
+                min_y: gy as f32 * 20.0,
+                max_x: gx as f32 * 20.0 + 20.0,
+                max_y: gy as f32 * 20.0 + 20.0,
+            };
+            assert!(rectangles.contains(&cell), "missing cell {:?}", cell);
+        }
+    }
+
+    // Inserts land in the pre-made cells without restructuring
+    qt.insert(Item { id: 2, point: Point { x: 65.0, y: 45.0 } });
+    qt.insert(Item { id: 3, point: Point { x: 25.0, y: 75.0 } });
+    assert_eq!(qt.get_all_node_boundaries().len(), 21);
+
+    let mut ids: Vec<u64> = qt.query(boundary).into_iter().map(|(id, _, _)| id).collect();
+    ids.sort_unstable();
+    assert_eq!(ids, vec![1, 2, 3]);
+    let hit = qt.query(Rect { min_x: 60.0, min_y: 40.0, max_x: 70.0, max_y: 50.0 });
+    assert_eq!(hit, vec![(2, 65.0, 45.0)]);
+}
@@ -2,7 +2,7 @@ use fastquadtree::serialization::{
     NATIVE_FORMAT_VERSION, NATIVE_KIND_POINT, NATIVE_KIND_RECT, NATIVE_MAGIC,
     RUNTIME_PREALLOCATION_LIMIT_BUCKETS_BYTES, SerializationError,
 };
-use fastquadtree::{Item, Point, QuadTree, Rect, RectItem, RectQuadTree};
+use fastquadtree::{Item, Point, QuadTree, Rect, RectItem, RectQuadTree, SplitStrategy};
 
 #[test]
 fn quadtree_roundtrip_bytes() {
@@ -94,7 +94,7 @@ fn quadtree_rejects_invalid_serialization_payloads() {
     assert!(QuadTree::<f64>::from_bytes(b"not-fqtw").is_err());
 
     let mut unsupported_version = bytes.clone();
-    unsupported_version[4..6].copy_from_slice(&2u16.to_le_bytes());
+    unsupported_version[4..6].copy_from_slice(&(NATIVE_FORMAT_VERSION + 1).to_le_bytes());
     assert!(QuadTree::<f64>::from_bytes(&unsupported_version).is_err());
 
     let mut wrong_kind = bytes.clone();
@@ -149,3 +149,192 @@ fn runtime_preallocation_error_uses_bucket_constant() {
         expected
     );
 }
+
+#[test]
+fn quadtree_roundtrip_preserves_split_structure() {
+    let mut qt = QuadTree::new(
+        Rect { min_x: 0.0, min_y: 0.0, max_x: 100.0, max_y: 100.0 },
+        2,
+        6,
+    );
+    for id in 0..200u64 {
+        let x = (id * 37 % 100) as f64 + 0.25;
+        let y = (id * 61 % 100) as f64 + 0.75;
+        qt.insert(Item { id, point: Point { x, y } });
+    }
+
+    let bytes = qt.to_bytes().expect("serialize quadtree");
+    let qt2 = QuadTree::<f64>::from_bytes(&bytes).expect("deserialize quadtree");
+
+    // Same nodes, same items in the same order, same config
+    assert_eq!(qt.structural_hash(), qt2.structural_hash());
+    assert_eq!(qt.get_all_node_boundaries(), qt2.get_all_node_boundaries());
+    assert_eq!(qt.get_max_depth(), qt2.get_max_depth());
+    let rect = Rect { min_x: 20.0, min_y: 10.0, max_x: 70.0, max_y: 55.0 };
+    assert_eq!(qt.query(rect), qt2.query(rect));
+
+    let mut bad_magic = bytes.clone();
+    bad_magic[..4].copy_from_slice(b"NOPE");
+    assert!(matches!(
+        QuadTree::<f64>::from_bytes(&bad_magic),
+        Err(SerializationError::InvalidMagic)
+    ));
+
+    let mut bad_version = bytes;
+    bad_version[4..6].copy_from_slice(&(NATIVE_FORMAT_VERSION + 1).to_le_bytes());
+    assert!(matches!(
+        QuadTree::<f64>::from_bytes(&bad_version),
+        Err(SerializationError::UnsupportedVersion(v)) if v == NATIVE_FORMAT_VERSION + 1
+    ));
+}
+
+#[test]
+fn quadtree_rejects_unknown_versions_with_exact_error() {
+    let mut qt = QuadTree::new(
+        Rect { min_x: 0.0, min_y: 0.0, max_x: 10.0, max_y: 10.0 },
+        4,
+        8,
+    );
+    qt.insert(Item { id: 1, point: Point { x: 1.0, y: 1.0 } });
+    let bytes = qt.to_bytes().expect("serialize quadtree");
+
+    for version in [0, NATIVE_FORMAT_VERSION + 1] {
+        let mut other = bytes.clone();
+        other[4..6].copy_from_slice(&version.to_le_bytes());
+        let err = QuadTree::<f64>::from_bytes(&other).err().expect("unknown version");
+        assert!(matches!(err, SerializationError::UnsupportedVersion(v) if v == version));
+        assert_eq!(
+            err.to_string(),
+            format!("unsupported fastquadtree native serialization version {version}")
+        );
+    }
+}
+
+// Both blobs hold the same f64 tree over (0, 0, 100, 100) with capacity 2 and
+// max_depth 8: an empty root split once, with ids 1 and 2 at (10, 10) and
+// (20, 20) in the lower-left child, 3 at (75, 25) in the lower-right one and
+// 4 at (90, 90) in the upper-right one. The v2 blob marks the root expandable.
+#[test]
+fn quadtree_loads_checked_in_legacy_payloads() {
+    let blobs: [(u16, &[u8]); 2] = [
+        (1, include_bytes!("data/quadtree_f64_v1.bin")),
+        (2, include_bytes!("data/quadtree_f64_v2.bin")),
+    ];
+    for (version, blob) in blobs {
+        assert_eq!(u16::from_le_bytes([blob[4], blob[5]]), version);
+        let mut qt = QuadTree::<f64>::from_bytes(blob).expect("decode legacy payload");
+
+        let all = Rect { min_x: 0.0, min_y: 0.0, max_x: 100.0, max_y: 100.0 };
+        let mut hits = qt.query(all);
+        hits.sort_by_key(|h| h.0);
+        assert_eq!(hits, vec![(1, 10.0, 10.0), (2, 20.0, 20.0), (3, 75.0, 25.0), (4, 90.0, 90.0)]);
+        assert_eq!(qt.get_all_node_boundaries().len(), 5);
+
+        // New fields come back as those versions behaved
+        let c = qt.config();
+        assert_eq!((c.capacity, c.split_threshold, c.max_depth, c.merge_threshold), (2, 2, 8, 2));
+        assert_eq!(qt.split_strategy(), SplitStrategy::Quadrant);
+        assert!(!qt.is_expandable());
+
+        // Re-saving writes the current version and loads back unchanged
+        let bytes = qt.to_bytes().expect("re-serialize quadtree");
+        assert_eq!(u16::from_le_bytes([bytes[4], bytes[5]]), NATIVE_FORMAT_VERSION);
+        let qt2 = QuadTree::<f64>::from_bytes(&bytes).expect("decode current payload");
+        assert_eq!(qt.structural_hash(), qt2.structural_hash());
+        assert_eq!(qt.get_all_node_boundaries(), qt2.get_all_node_boundaries());
+
+        // Merging at capacity, as before: two deletes fold the root back
+        assert!(qt.delete(3, Point { x: 75.0, y: 25.0 }));
+        assert!(qt.delete(4, Point { x: 90.0, y: 90.0 }));
+        assert_eq!(qt.get_all_node_boundaries().len(), 1);
+    }
+}
+
+#[test]
+fn rectquadtree_decodes_payloads_from_every_version() {
+    let mut qt = RectQuadTree::new(
+        Rect { min_x: 0.0, min_y: 0.0, max_x: 10.0, max_y: 10.0 },
+        2,
+        8,
+    );
+    for i in 0..20u64 {
+        let x = (i % 9) as f64;
+        let y = (i / 3) as f64 * 1.25;
+        qt.insert(RectItem { id: i, rect: Rect { min_x: x, min_y: y, max_x: x + 0.5, max_y: y + 0.5 } });
+    }
+
+    // The rect body layout has not changed since v1, so only the header differs
+    let bytes = qt.to_bytes().expect("serialize quadtree");
+    let all = Rect { min_x: 0.0, min_y: 0.0, max_x: 10.0, max_y: 10.0 };
+    for version in 1..=NATIVE_FORMAT_VERSION {
+        let mut old = bytes.clone();
+        old[4..6].copy_from_slice(&version.to_le_bytes());
+        let qt2 = RectQuadTree::<f64>::from_bytes(&old).expect("decode older rect payload");
+        assert_eq!(qt.count_items(), qt2.count_items());
+        assert_eq!(qt.query(all), qt2.query(all));
+        assert_eq!(qt.get_all_node_boundaries(), qt2.get_all_node_boundaries());
+    }
+
+    let mut newer = bytes;
+    newer[4..6].copy_from_slice(&(NATIVE_FORMAT_VERSION + 1).to_le_bytes());
+    assert!(matches!(
+        RectQuadTree::<f64>::from_bytes(&newer),
+        Err(SerializationError::UnsupportedVersion(v)) if v == NATIVE_FORMAT_VERSION + 1
+    ));
+}
+
+#[test]
+fn roundtrip_at_current_version() {
+    let mut qt = QuadTree::new(
+        Rect { min_x: 0.0, min_y: 0.0, max_x: 10.0, max_y: 10.0 },
+        2,
+        8,
+    );
+    for id in 0..30u64 {
+        qt.insert(Item { id, point: Point { x: (id % 10) as f64, y: (id / 3) as f64 } });
+    }
+    let bytes = qt.to_bytes().expect("serialize quadtree");
+    assert_eq!(u16::from_le_bytes([bytes[4], bytes[5]]), NATIVE_FORMAT_VERSION);
+    let qt2 = QuadTree::<f64>::from_bytes(&bytes).expect("deserialize quadtree");
+    assert_eq!(qt.structural_hash(), qt2.structural_hash());
+    assert_eq!(qt2.to_bytes().expect("re-serialize quadtree"), bytes);
+
+    let mut rqt = RectQuadTree::new(
+        Rect { min_x: 0.0, min_y: 0.0, max_x: 10.0, max_y: 10.0 },
+        2,
+        8,
+    );
+    rqt.insert(RectItem { id: 7, rect: Rect { min_x: 1.0, min_y: 1.0, max_x: 2.0, max_y: 2.0 } });
+    let rbytes = rqt.to_bytes().expect("serialize rect quadtree");
+    assert_eq!(u16::from_le_bytes([rbytes[4], rbytes[5]]), NATIVE_FORMAT_VERSION);
+    let rqt2 = RectQuadTree::<f64>::from_bytes(&rbytes).expect("deserialize rect quadtree");
+    assert_eq!(rqt2.to_bytes().expect("re-serialize rect quadtree"), rbytes);
+}
+
+#[test]
+fn quadtree_roundtrip_preserves_version_3_fields() {
+    let mut qt = QuadTree::builder(Rect { min_x: 0.0, min_y: 0.0, max_x: 40.0, max_y: 10.0 })
+        .capacity(4)
+        .max_depth(8)
+        .split_threshold(6)
+        .merge_threshold(2)
+        .split_strategy(SplitStrategy::LongestAxis)
+        .expandable(true)
+        .build()
+        .expect("valid config");
+    for i in 0..12 {
+        qt.insert_auto(Point { x: i as f64 * 3.0, y: (i % 5) as f64 });
+    }
+
+    let bytes = qt.to_bytes().expect("serialize quadtree");
+    let mut qt2 = QuadTree::<f64>::from_bytes(&bytes).expect("deserialize quadtree");
+    assert_eq!(qt.structural_hash(), qt2.structural_hash());
+    assert_eq!(qt2.split_strategy(), SplitStrategy::LongestAxis);
+    assert!(qt2.is_expandable());
+
+    // The id counter and growth carry over, so both trees keep evolving alike
+    let far = Point { x: 95.0, y: -30.0 };
+    assert_eq!(qt.insert_auto(far), qt2.insert_auto(far));
+    assert_eq!(qt.boundary, qt2.boundary);
+    assert_eq!(qt.structural_hash(), qt2.structural_hash());
+}
@@ -0,0 +1,43 @@
+use std::thread;
+
+use fastquadtree::{Item, Point, QuadTree, Rect, SharedQuadTree};
+
+fn assert_sync<T: Send + Sync>() {}
+
+#[test]
+fn quadtree_is_send_and_sync() {
+    assert_sync::<QuadTree<f32>>();
+    assert_sync::<QuadTree<i64>>();
+    assert_sync::<SharedQuadTree<f64>>();
+}
+
+#[test]
+fn readers_share_one_tree_across_threads() {
+    let mut qt = QuadTree::new(Rect { min_x: 0.0, min_y: 0.0, max_x: 100.0, max_y: 100.0 }, 4, 8);
+    for id in 0..100u64 {
+        let point = Point { x: (id % 10) as f32 * 10.0, y: (id / 10) as f32 * 10.0 };
+        assert!(qt.insert(Item { id, point }));
+    }
+    let shared = SharedQuadTree::new(qt);
+
+    let handles: Vec<_> = (0..4)
+        .map(|i| {
+            let reader = shared.clone();
+            thread::spawn(move || {
+                let x = i as f32 * 20.0;
+                let hits = reader.query(Rect { min_x: x, min_y: 0.0, max_x: x + 20.0, max_y: 100.0 });
+                let nn = reader.nearest_neighbor(Point { x: x + 1.0, y: 1.0 }).map(|it| it.id);
+                (hits.len(), nn)
+            })
+        })
+        .collect();
+    for (i, h) in handles.into_iter().enumerate() {
+        let (hits, nn) = h.join().unwrap();
+        assert_eq!(hits, 20);
+        assert_eq!(nn, Some(i as u64 * 2));
+    }
+
+    assert_eq!(shared.nearest_neighbors(Point { x: 0.0, y: 0.0 }, 3).len(), 3);
+    let tree = shared.into_inner().ok().expect("last handle");
+    assert_eq!(tree.count_items(), 100);
+}
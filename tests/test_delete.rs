@@ -1,4 +1,4 @@
-use fastquadtree::{QuadTree, Item, Point, Rect};
+use fastquadtree::{DeleteOutcome, QuadTree, Item, Point, Rect};
 
 #[test]
 fn test_delete_simple() {
@@ -349,3 +349,229 @@ fn deep_chain_collapses_to_leaf() {
     assert!(tree.children.is_none(), "deep path should have collapsed back to a single leaf");
     assert_eq!(tree.count_items(), 1);
 }
+
+#[test]
+fn test_drain_circle_removes_only_inside_points() {
+    let mut tree = QuadTree::new(Rect { min_x: 0.0, min_y: 0.0, max_x: 100.0, max_y: 100.0 }, 2, 8);
+    let center = Point { x: 50.0, y: 50.0 };
+    let radius = 12.0f32;
+
+    let mut inside = Vec::new();
+    let mut outside = Vec::new();
+    let mut id = 0;
+    for x in 0..10 {
+        for y in 0..10 {
+            let p = Point { x: x as f32 * 10.0 + 5.0, y: y as f32 * 10.0 + 5.0 };
+            tree.insert(Item { id, point: p });
+            let (dx, dy) = (p.x - center.x, p.y - center.y);
+            if dx * dx + dy * dy <= radius * radius { inside.push(id) } else { outside.push(id) }
+            id += 1;
+        }
+    }
+    let nodes_before = tree.get_all_node_boundaries().len();
+
+    let mut drained: Vec<u64> = tree.drain_circle(center, radius).into_iter().map(|it| it.id).collect();
+    drained.sort_unstable();
+    assert_eq!(drained, inside);
+
+    let mut remaining = tree.all_ids();
+    remaining.sort_unstable();
+    assert_eq!(remaining, outside);
+    assert!(tree.query_circle(center, radius).is_empty());
+    assert!(tree.get_all_node_boundaries().len() <= nodes_before);
+
+    // Draining everything collapses back to a single leaf
+    tree.drain_circle(center, 200.0);
+    assert_eq!(tree.count_items(), 0);
+    assert_eq!(tree.get_all_node_boundaries().len(), 1);
+}
+
+#[test]
+fn test_delete_in_rect_removes_only_inside_points() {
+    let mut tree = QuadTree::new(Rect { min_x: 0.0, min_y: 0.0, max_x: 100.0, max_y: 100.0 }, 2, 8);
+    let mut id = 0;
+    for x in 0..10 {
+        for y in 0..10 {
+            tree.insert(Item { id, point: Point { x: x as f32 * 10.0, y: y as f32 * 10.0 } });
+            id += 1;
+        }
+    }
+    // Half-open like query: x and y in {20, 30}
+    let rect = Rect { min_x: 20.0, min_y: 20.0, max_x: 40.0, max_y: 40.0 };
+    let mut expected: Vec<u64> = tree.query(rect).into_iter().map(|(id, _, _)| id).collect();
+    expected.sort_unstable();
+    assert_eq!(expected.len(), 4);
+
+    let mut removed: Vec<u64> = tree.delete_in_rect(rect).into_iter().map(|it| it.id).collect();
+    removed.sort_unstable();
+    assert_eq!(removed, expected);
+    assert_eq!(tree.count_items(), 96);
+    assert!(tree.query(rect).is_empty());
+
+    // Clearing the whole boundary collapses back to a single leaf
+    assert_eq!(tree.delete_in_rect(Rect { min_x: 0.0, min_y: 0.0, max_x: 100.0, max_y: 100.0 }).len(), 96);
+    assert_eq!(tree.get_all_node_boundaries().len(), 1);
+}
+
+#[test]
+fn test_update_position_same_leaf_and_cross_leaf() {
+    let mut tree = QuadTree::new(Rect { min_x: 0.0, min_y: 0.0, max_x: 100.0, max_y: 100.0 }, 2, 8);
+    tree.insert(Item { id: 1, point: Point { x: 10.0, y: 10.0 } });
+    tree.insert(Item { id: 2, point: Point { x: 12.0, y: 12.0 } });
+    tree.insert(Item { id: 3, point: Point { x: 80.0, y: 80.0 } });
+    let nodes_before = tree.get_all_node_boundaries();
+
+    // Same leaf: structure is untouched
+    assert!(tree.update_position(1, Point { x: 10.0, y: 10.0 }, Point { x: 11.0, y: 14.0 }));
+    assert_eq!(tree.get_all_node_boundaries(), nodes_before);
+    assert_eq!(tree.nearest_neighbor(Point { x: 11.0, y: 14.0 }).unwrap().id, 1);
+
+    // Cross leaf
+    assert!(tree.update_position(2, Point { x: 12.0, y: 12.0 }, Point { x: 70.0, y: 20.0 }));
+    let hits = tree.query(Rect { min_x: 60.0, min_y: 10.0, max_x: 80.0, max_y: 30.0 });
+    assert_eq!(hits, vec![(2, 70.0, 20.0)]);
+    assert_eq!(tree.count_items(), 3);
+
+    // Unknown pair or out-of-bounds target leaves the tree unchanged
+    assert!(!tree.update_position(2, Point { x: 12.0, y: 12.0 }, Point { x: 1.0, y: 1.0 }));
+    assert!(!tree.update_position(3, Point { x: 80.0, y: 80.0 }, Point { x: 150.0, y: 1.0 }));
+    assert_eq!(tree.nearest_neighbor(Point { x: 80.0, y: 80.0 }).unwrap().id, 3);
+    assert_eq!(tree.count_items(), 3);
+}
+
+#[test]
+fn test_clear_resets_to_root() {
+    let boundary = Rect { min_x: 0.0, min_y: 0.0, max_x: 100.0, max_y: 100.0 };
+    let mut tree = QuadTree::new(boundary, 2, 5);
+    for id in 0..50u64 {
+        tree.insert(Item { id, point: Point { x: (id * 7 % 100) as f32, y: (id * 3 % 100) as f32 } });
+    }
+    assert!(tree.get_all_node_boundaries().len() > 1);
+
+    tree.clear();
+    assert_eq!(tree.count_items(), 0);
+    assert_eq!(tree.get_all_node_boundaries(), vec![boundary]);
+    assert_eq!(tree.get_max_depth(), 5);
+
+    // Still usable with the same config
+    for id in 0..3u64 {
+        assert!(tree.insert(Item { id, point: Point { x: 10.0 * id as f32 + 1.0, y: 1.0 } }));
+    }
+    assert_eq!(tree.count_items(), 3);
+    assert!(tree.get_all_node_boundaries().len() > 1);
+}
+
+#[test]
+fn test_contains_by_id_and_point() {
+    let mut tree = QuadTree::new(Rect { min_x: 0.0, min_y: 0.0, max_x: 100.0, max_y: 100.0 }, 2, 8);
+    for id in 0..30u64 {
+        tree.insert(Item { id, point: Point { x: (id * 3) as f32 + 0.5, y: (id * 3) as f32 + 0.5 } });
+    }
+    let p = Point { x: 42.5, y: 42.5 };
+    assert!(tree.contains(14, p));
+    assert!(!tree.contains(15, p));
+    assert!(!tree.contains(14, Point { x: 42.5, y: 43.0 }));
+    assert!(!tree.contains(14, Point { x: 420.0, y: 42.5 }));
+
+    assert!(tree.delete(14, p));
+    assert!(!tree.contains(14, p));
+}
+
+#[test]
+fn test_items_at_lists_stacked_items() {
+    let mut tree = QuadTree::new(Rect { min_x: 0.0, min_y: 0.0, max_x: 100.0, max_y: 100.0 }, 2, 8);
+    let p = Point { x: 25.0, y: 25.0 };
+    for id in [7u64, 3, 7, 9] {
+        assert!(tree.insert(Item { id, point: p }));
+    }
+    for id in 0..20u64 {
+        tree.insert(Item { id: 100 + id, point: Point { x: 25.0 + 0.001 * (id + 1) as f32, y: 25.0 } });
+    }
+
+    let mut ids: Vec<u64> = tree.items_at(p).into_iter().map(|it| it.id).collect();
+    ids.sort_unstable();
+    assert_eq!(ids, vec![3, 7, 7, 9]);
+    assert!(tree.items_at(p).iter().all(|it| it.point == p));
+
+    assert!(tree.items_at(Point { x: 26.0, y: 25.0 }).is_empty());
+    assert!(tree.items_at(Point { x: 125.0, y: 25.0 }).is_empty());
+
+    assert!(tree.delete(7, p));
+    assert_eq!(tree.items_at(p).len(), 3);
+}
+
+#[test]
+fn test_delete_by_id_without_point() {
+    let mut tree = QuadTree::new(Rect { min_x: 0.0, min_y: 0.0, max_x: 100.0, max_y: 100.0 }, 2, 8);
+    for id in 0..40u64 {
+        let v = (id * 37 % 97) as f32 + 0.5;
+        tree.insert(Item { id, point: Point { x: v, y: 99.0 - v } });
+    }
+    let before = tree.get_all_node_boundaries().len();
+
+    let p = tree.delete_by_id(17).expect("id 17 is stored");
+    assert_eq!(p, Point { x: (17 * 37 % 97) as f32 + 0.5, y: 99.0 - ((17 * 37 % 97) as f32 + 0.5) });
+    assert!(!tree.contains(17, p));
+    assert_eq!(tree.count_items(), 39);
+    assert_eq!(tree.delete_by_id(17), None);
+    assert_eq!(tree.delete_by_id(1000), None);
+
+    // Emptying the tree merges nodes back up
+    for id in 0..40u64 {
+        if id != 17 {
+            assert!(tree.delete_by_id(id).is_some());
+        }
+    }
+    assert_eq!(tree.count_items(), 0);
+    assert!(tree.get_all_node_boundaries().len() < before);
+    assert_eq!(tree.get_all_node_boundaries().len(), 1);
+}
+
+#[test]
+fn test_reassign_id_keeps_position_and_structure() {
+    let mut tree = QuadTree::new(Rect { min_x: 0.0, min_y: 0.0, max_x: 100.0, max_y: 100.0 }, 2, 8);
+    for id in 0..10u64 {
+        tree.insert(Item { id, point: Point { x: id as f32 * 9.0, y: 50.0 } });
+    }
+    let nodes_before = tree.get_all_node_boundaries();
+    let p = Point { x: 27.0, y: 50.0 };
+
+    assert!(tree.reassign_id(3, 42, p));
+    assert!(!tree.contains(3, p));
+    assert!(tree.contains(42, p));
+    assert_eq!(tree.get_all_node_boundaries(), nodes_before);
+    assert_eq!(tree.count_items(), 10);
+
+    // Wrong id, wrong point, or out of bounds: nothing changes
+    assert!(!tree.reassign_id(3, 7, p));
+    assert!(!tree.reassign_id(42, 7, Point { x: 28.0, y: 50.0 }));
+    assert!(!tree.reassign_id(42, 7, Point { x: 500.0, y: 50.0 }));
+    assert!(tree.contains(42, p));
+}
+
+#[test]
+fn test_delete_reporting_flags_merges() {
+    let mut tree = QuadTree::new(Rect { min_x: 0.0, min_y: 0.0, max_x: 100.0, max_y: 100.0 }, 2, 8);
+    tree.insert(Item { id: 1, point: Point { x: 10.0, y: 10.0 } });
+    tree.insert(Item { id: 2, point: Point { x: 60.0, y: 10.0 } });
+    tree.insert(Item { id: 3, point: Point { x: 10.0, y: 60.0 } });
+    tree.insert(Item { id: 4, point: Point { x: 60.0, y: 60.0 } });
+    assert_eq!(tree.get_all_node_boundaries().len(), 5);
+
+    // Misses report nothing
+    let miss = tree.delete_reporting(9, Point { x: 10.0, y: 10.0 });
+    assert_eq!(miss, DeleteOutcome { removed: false, merged: false });
+    let outside = tree.delete_reporting(1, Point { x: 500.0, y: 10.0 });
+    assert_eq!(outside, DeleteOutcome::default());
+
+    // Three items still exceed the merge threshold
+    let out = tree.delete_reporting(4, Point { x: 60.0, y: 60.0 });
+    assert_eq!(out, DeleteOutcome { removed: true, merged: false });
+    assert_eq!(tree.get_all_node_boundaries().len(), 5);
+
+    // Down to two, the root folds its children back in
+    let out = tree.delete_reporting(3, Point { x: 10.0, y: 60.0 });
+    assert_eq!(out, DeleteOutcome { removed: true, merged: true });
+    assert_eq!(tree.get_all_node_boundaries().len(), 1);
+    assert_eq!(tree.count_items(), 2);
+}
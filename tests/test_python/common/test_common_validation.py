@@ -11,6 +11,8 @@ from fastquadtree._common import (
     code_to_dtype,
     dtype_to_code,
     pack_bounds,
+    rect_from_center,
+    rect_from_corners,
     unpack_bounds,
     validate_bounds,
     validate_np_dtype,
@@ -108,3 +110,13 @@ def test_pack_and_unpack_bounds_round_trip(dtype: QuadTreeDType):
 def test_pack_bounds_rejects_unknown_dtype():
     with pytest.raises(SerializationError):
         pack_bounds((0, 0, 1, 1), "bad")  # type: ignore[arg-type]
+
+
+def test_rect_from_center_and_corners():
+    assert rect_from_center((10, 20), 3, 4) == (7, 16, 13, 24)
+    assert rect_from_center((0.5, 0.5), 0.5, 0.25) == (0.0, 0.25, 1.0, 0.75)
+
+    expected = (1.0, 2.0, 5.0, 6.0)
+    assert rect_from_corners((1.0, 2.0), (5.0, 6.0)) == expected
+    assert rect_from_corners((5.0, 6.0), (1.0, 2.0)) == expected
+    assert rect_from_corners((1.0, 6.0), (5.0, 2.0)) == expected
@@ -0,0 +1,26 @@
+import pytest
+
+from fastquadtree._native import dist_sq_point_to_rect, dist_sq_points
+
+
+def test_dist_sq_points():
+    assert dist_sq_points((0.0, 0.0), (3.0, 4.0)) == 25.0
+    assert dist_sq_points((3.0, 4.0), (0.0, 0.0)) == 25.0
+    assert dist_sq_points((-1.5, 2.0), (-1.5, 2.0)) == 0.0
+    # Ints are accepted and computed in f64
+    assert dist_sq_points((1, 1), (2, 3)) == 5.0
+
+
+@pytest.mark.parametrize(
+    ("xy", "expected"),
+    [
+        ((5.0, 5.0), 0.0),  # inside
+        ((0.0, 0.0), 0.0),  # on the min corner
+        ((10.0, 5.0), 0.0),  # on the max edge
+        ((13.0, 5.0), 9.0),  # right of the rect
+        ((5.0, -2.0), 4.0),  # below
+        ((-3.0, 14.0), 25.0),  # past the top-left corner
+    ],
+)
+def test_dist_sq_point_to_rect(xy, expected):
+    assert dist_sq_point_to_rect(xy, (0.0, 0.0, 10.0, 10.0)) == expected
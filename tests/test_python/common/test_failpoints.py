@@ -0,0 +1,21 @@
+import pytest
+
+from fastquadtree._native import QuadTree as NativeQuadTree
+
+requires_failpoints = pytest.mark.skipif(
+    not hasattr(NativeQuadTree, "_failpoint_panic"),
+    reason="native module built without the failpoints feature",
+)
+
+
+@requires_failpoints
+@pytest.mark.parametrize("detached", [False, True])
+def test_internal_panic_raises_runtime_error(detached):
+    qt = NativeQuadTree((0, 0, 10, 10), 4)
+    qt.insert(1, (1, 1))
+    with pytest.raises(RuntimeError, match="internal error: failpoint"):
+        qt._failpoint_panic(detached)
+
+    # The tree is still usable after the caught panic
+    assert qt.insert(2, (2, 2))
+    assert qt.count_items() == 2
@@ -55,7 +55,7 @@ class StubNative:
     def insert(self, id_: int, geom: Any) -> bool:
         return self.insert_result
 
-    def insert_many(self, start_id: int, geoms: Iterable[Any]) -> int:
+    def insert_many_auto(self, start_id: int, geoms: Iterable[Any]) -> int:
         if self.insert_many_result is not None:
             return self.insert_many_result
         return start_id + len(list(geoms)) - 1
@@ -647,7 +647,7 @@ def test_obj_store_edges_and_reverse_mapping_cleanup():
     rebuilt = ObjStore.from_dict(payload, PointItem)
     assert len(rebuilt) == len(store)
 
-    with pytest.raises(AssertionError):
+    with pytest.raises(ValueError):
         store.add(PointItem(5, (5.0, 5.0)))
 
     store.add(PointItem(2, (1.0, 1.0), obj="a"))
@@ -707,6 +707,27 @@ def test_obj_store_replaces_object_and_clears_reverse_map():
     assert store.contains_obj("second") is True
 
 
+def test_obj_store_memory_bytes_grows_and_shrinks():
+    store = ObjStore()
+    empty = store.memory_bytes()
+    assert empty > 0
+
+    objs = [object() for _ in range(200)]
+    for i, obj in enumerate(objs):
+        store.add(PointItem(i, (float(i), 0.0), obj=obj))
+    filled = store.memory_bytes()
+    assert filled > empty
+
+    for i in range(200):
+        store.pop_id(i)
+    # Reverse-map sets are released but the free-list now holds every id
+    assert store._obj_to_ids == {}
+    assert store.memory_bytes() > empty
+
+    store.clear()
+    assert store.memory_bytes() < filled
+
+
 def test_obj_store_replace_branch_with_missing_reverse_entry():
     store = ObjStore()
     store.add(PointItem(0, (0.0, 0.0), obj="keep"))
@@ -1,5 +1,14 @@
+import pytest
+
 from fastquadtree._item import Item
-from fastquadtree._obj_store import ObjStore
+from fastquadtree._obj_store import (
+    MAX_ID,
+    IdDoesNotFitError,
+    IdOutOfBoundsError,
+    ObjStore,
+    OutOfOrderIdError,
+    StoreError,
+)
 
 
 def _mk(id_: int, geom=(0.0, 0.0), obj=None) -> Item:
@@ -108,3 +117,113 @@ def test_replace_with_none_removes_old_object_mapping():
     store.add(_mk(0, obj=None))
     assert store.contains_obj(obj) is False
     assert store._objs[0] is None
+
+
+def test_export_and_import_layout_restores_ids_and_holes():
+    store = ObjStore[Item]()
+    for i in range(6):
+        store.add(_mk(store.alloc_id(), geom=(float(i), float(i)), obj=f"o{i}"))
+    store.pop_id(1)
+    store.pop_id(4)
+
+    layout = store.export_layout()
+    assert layout == [(0, (0.0, 0.0)), (2, (2.0, 2.0)), (3, (3.0, 3.0)), (5, (5.0, 5.0))]
+
+    rebuilt = ObjStore.import_layout(reversed(layout), Item)
+    assert len(rebuilt) == 4
+    assert rebuilt.export_layout() == layout
+    assert all(it.obj is None for it in rebuilt.items())
+
+    # Holes are reused deterministically, then ids continue past the end
+    again = ObjStore.import_layout(layout, Item)
+    allocs = [rebuilt.alloc_id(), rebuilt.alloc_id(), rebuilt.alloc_id()]
+    assert allocs == [again.alloc_id(), again.alloc_id(), again.alloc_id()]
+    assert sorted(allocs[:2]) == [1, 4]
+    assert allocs[2] == 6
+
+
+def test_shrink_to_fit_trims_trailing_holes():
+    store = ObjStore[Item]()
+    for i in range(5):
+        store.add(_mk(store.alloc_id(), obj=f"o{i}"))
+    store.pop_id(1)
+    store.pop_id(3)
+    store.pop_id(4)
+    assert store.dense_len() == 5
+
+    store.shrink_to_fit()
+    assert store.dense_len() == 3
+    assert len(store) == 2
+    assert store._free == [1]
+    assert store.alloc_id() == 1
+    assert store.alloc_id() == 3
+
+    for i in (0, 2):
+        store.pop_id(i)
+    store.shrink_to_fit()
+    assert store.dense_len() == 0
+    assert store._free == []
+    assert store.alloc_id() == 0
+
+
+def test_alloc_id_skips_freed_id_refilled_by_add():
+    store = ObjStore[Item]()
+    for i in range(4):
+        store.add(_mk(store.alloc_id(), obj=f"o{i}"))
+    store.pop_id(2)
+
+    # Fill the hole directly instead of through alloc_id
+    store.add(_mk(2, obj="again"))
+    assert store.by_id(2).obj == "again"
+
+    assert store.alloc_id() == 4
+    store.add(_mk(4, obj="tail"))
+    assert len(store) == 5
+    assert store.by_id(2).obj == "again"
+
+
+def test_free_ids_and_hole_count_track_pops():
+    store = ObjStore[Item]()
+    for i in range(5):
+        store.add(_mk(store.alloc_id(), obj=f"o{i}"))
+    assert store.hole_count() == 0
+    assert store.free_ids() == []
+
+    store.pop_id(3)
+    store.pop_id(1)
+    assert store.hole_count() == 2
+    assert store.free_ids() == [1, 3]
+
+    # Refilling a hole directly leaves a stale free-list entry that is hidden
+    store.add(_mk(3, obj="again"))
+    assert store.hole_count() == 1
+    assert store.free_ids() == [1]
+
+    store.add(_mk(store.alloc_id(), obj="reuse"))
+    assert store.hole_count() == 0
+    assert store.free_ids() == []
+
+
+def test_add_raises_typed_store_errors():
+    store = ObjStore[Item]()
+    store.add(_mk(store.alloc_id(), obj="a"))
+
+    with pytest.raises(IndexError) as neg:
+        store.add(_mk(-1))
+    assert isinstance(neg.value, IdOutOfBoundsError)
+
+    with pytest.raises(OverflowError) as big:
+        store.add(_mk(MAX_ID + 1), handle_out_of_order=True)
+    assert isinstance(big.value, IdDoesNotFitError)
+
+    with pytest.raises(ValueError) as gap:
+        store.add(_mk(5))
+    assert isinstance(gap.value, OutOfOrderIdError)
+
+    for err in (neg.value, big.value, gap.value):
+        assert isinstance(err, StoreError)
+
+    # Nothing was stored by the failed adds
+    assert len(store) == 1
+    assert store.dense_len() == 1
+    assert store.by_id(0) is not None
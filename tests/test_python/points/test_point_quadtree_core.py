@@ -1,6 +1,7 @@
+import pytest
 from tests.test_python.conftest import get_bounds_for_dtype
 
-from fastquadtree.point_quadtree import QuadTree
+from fastquadtree.point_quadtree import DTYPE_MAP, QuadTree
 
 
 def test_insert_query_len_contains_and_iter(bounds, dtype):
@@ -41,6 +42,68 @@ def test_query_empty_region_and_outside_bounds(bounds, dtype):
     assert qt.query(outside) == []
 
 
+def test_query_normalizes_inverted_rect(bounds, dtype):
+    bounds_use = get_bounds_for_dtype(bounds, dtype)
+    qt = QuadTree(bounds_use, capacity=4, dtype=dtype)
+    for pt in [(1, 1), (2, 3), (8, 8)]:
+        qt.insert(pt if dtype.startswith("i") else (float(pt[0]), float(pt[1])))
+
+    rect = (0, 0, 5, 5)
+    inverted = (5, 5, 0, 0)
+    assert sorted(qt.query(inverted)) == sorted(qt.query(rect))
+    assert len(qt.query(inverted)) == 2
+    assert qt._native.count_in_rect(inverted) == 2
+
+    with pytest.raises(ValueError):
+        qt.query(inverted, strict=True)
+    with pytest.raises(ValueError):
+        qt._native.count_in_rect(inverted, strict=True)
+
+
+def test_native_rect_methods_all_normalize_inverted_rects(bounds, dtype):
+    bounds_use = get_bounds_for_dtype(bounds, dtype)
+    qt = QuadTree(bounds_use, capacity=2, dtype=dtype)
+    for pt in [(1, 1), (2, 3), (4, 1), (8, 8), (3, 9)]:
+        qt.insert(pt if dtype.startswith("i") else (float(pt[0]), float(pt[1])))
+    native = qt._native
+
+    rect = (0, 0, 5, 5)
+    inverted = (5, 5, 0, 0)
+    calls = {
+        "query": (),
+        "count_in_rect": (),
+        "query_ids": (),
+        "query_limit": (10,),
+        "query_inclusive": (),
+        "query_sorted": (),
+        "query_morton_ordered": (),
+        "centroid_in_rect": (),
+        "extent_in_rect": (),
+        "bounds_intersect": (),
+        "count_in_rect_filtered": ({0, 2},),
+        "reduce_tag_in_rect": ("sum", {0: 1.0, 1: 2.0, 3: 4.0}),
+        "query_ordered_by": ((0, 0),),
+    }
+    for name, extra in calls.items():
+        method = getattr(native, name)
+        want = method(rect, *extra)
+        assert want  # every call has something to find in rect
+        assert method(inverted, *extra) == want, name
+        with pytest.raises(ValueError, match="inverted"):
+            method(inverted, *extra, strict=True)
+
+    chunks = list(native.query_chunked(inverted, 2))
+    assert sorted(it for chunk in chunks for it in chunk) == sorted(native.query(rect))
+    with pytest.raises(ValueError, match="inverted"):
+        native.query_chunked(inverted, 2, strict=True)
+
+    inside = sorted(native.query(rect))
+    with pytest.raises(ValueError, match="inverted"):
+        native.delete_in_rect(inverted, strict=True)
+    assert sorted(native.delete_in_rect(inverted)) == inside
+    assert native.query(rect) == []
+
+
 def test_custom_id_insertion(bounds, dtype):
     bounds_use = get_bounds_for_dtype(bounds, dtype)
     pt = (5, 5) if dtype.startswith("i") else (5.0, 5.0)
@@ -188,3 +251,141 @@ def test_contains_robust(bounds, dtype):
     for pt in edge_pts:
         qt.insert(pt)
         assert pt in qt, f"Edge point {pt} should be found in tree"
+
+
+def test_native_len_and_bool(bounds, dtype):
+    bounds_use = get_bounds_for_dtype(bounds, dtype)
+    qt = QuadTree(bounds_use, capacity=4, dtype=dtype)
+    native = qt._native
+    assert len(native) == 0
+    assert not native
+
+    pt = (1, 1) if dtype.startswith("i") else (1.0, 1.0)
+    id_ = qt.insert(pt)
+    assert len(native) == 1
+    assert native
+
+    qt.delete(id_, *pt)
+    assert not native
+
+
+def test_native_longest_axis_split_strategy(dtype):
+    native_cls = DTYPE_MAP[dtype]
+    wide = (0, 0, 1000, 10)
+    qt = native_cls(wide, 1, 4, split_strategy="longest_axis")
+    assert qt.split_strategy == "longest_axis"
+    assert native_cls(wide, 1).split_strategy == "quadrant"
+
+    qt.insert(0, (10, 5))
+    qt.insert(1, (900, 5))
+    # One split of a 100:1 node gives four full-height strips
+    assert sorted(qt.get_all_node_boundaries()) == [
+        (0, 0, 1000, 10),
+        (0, 0, 250, 10),
+        (250, 0, 500, 10),
+        (500, 0, 750, 10),
+        (750, 0, 1000, 10),
+    ]
+
+    with pytest.raises(ValueError):
+        native_cls(wide, 1, split_strategy="octant")
+
+
+def test_native_autofit_rejects_bad_input(dtype):
+    native_cls = DTYPE_MAP[dtype]
+    qt = native_cls.from_points_autofit([1, 2], [0, 50], [0, 20], 4)
+    assert qt.count_items() == 2
+
+    with pytest.raises(ValueError):
+        native_cls.from_points_autofit([1, 2], [0, 50], [0, 20], 4, padding=-1)
+    if dtype.startswith("f"):
+        with pytest.raises(ValueError):
+            native_cls.from_points_autofit([1], [float("nan")], [0.0], 4)
+        with pytest.raises(ValueError):
+            native_cls.from_points_autofit([1], [0.0], [0.0], 4, padding=float("inf"))
+
+
+def test_native_eq_compares_content_and_is_unhashable(dtype):
+    native_cls = DTYPE_MAP[dtype]
+    a = native_cls((0, 0, 100, 100), 1)
+    b = native_cls((0, 0, 100, 100), 8)
+    for id_, xy in [(1, (10, 10)), (2, (60, 20)), (3, (30, 90))]:
+        a.insert(id_, xy)
+    for id_, xy in [(3, (30, 90)), (1, (10, 10)), (2, (60, 20))]:
+        b.insert(id_, xy)
+    assert a == b
+    b.insert(4, (5, 5))
+    assert a != b
+
+    with pytest.raises(TypeError, match="unhashable"):
+        hash(a)
+    with pytest.raises(TypeError):
+        _ = {a}
+
+
+def test_native_insert_many_matches_incremental_inserts(dtype):
+    native_cls = DTYPE_MAP[dtype]
+    ids = list(range(200))
+    xs = [(i * 37) % 100 for i in ids]
+    ys = [(i * 11) % 100 for i in ids]
+
+    loop = native_cls((0, 0, 100, 100), 4)
+    for id_, x, y in zip(ids, xs, ys):
+        loop.insert(id_, (x, y))
+    bulk = native_cls((0, 0, 100, 100), 4)
+    assert bulk.insert_many(ids, xs, ys) == 200
+
+    for rect in [(0, 0, 100, 100), (10, 20, 55, 70), (90, 90, 100, 100)]:
+        assert sorted(bulk.query(rect)) == sorted(loop.query(rect))
+
+    # Out-of-bounds points are skipped and mismatched lengths are refused
+    assert native_cls((0, 0, 100, 100), 4).insert_many([1, 2], [5, 500], [5, 5]) == 1
+    with pytest.raises(ValueError):
+        native_cls((0, 0, 100, 100), 4).insert_many([1, 2], [5], [5, 6])
+
+    # Expandable trees grow to fit instead of skipping
+    grow = native_cls((0, 0, 100, 100), 4, expandable=True)
+    assert grow.insert_many([1, 2], [5, 500], [5, 5]) == 2
+    assert grow.contains(2, (500, 5))
+
+
+def test_native_all_items_tracks_inserts_and_deletes(dtype):
+    native = DTYPE_MAP[dtype]((0, 0, 100, 100), 2)
+    assert native.all_items() == []
+
+    points = {i: ((i * 37) % 100, (i * 11) % 100) for i in range(20)}
+    for id_, xy in points.items():
+        native.insert(id_, xy)
+    # Enough items to split, so the walk covers more than the root
+    assert native.get_max_depth() > 0
+    assert sorted(native.all_items()) == sorted(
+        (id_, x, y) for id_, (x, y) in points.items()
+    )
+
+    for id_ in range(0, 20, 2):
+        assert native.delete(id_, points[id_])
+    assert sorted(native.all_items()) == sorted(
+        (id_, x, y) for id_, (x, y) in points.items() if id_ % 2
+    )
+
+    for id_ in range(1, 20, 2):
+        assert native.delete(id_, points[id_])
+    assert native.all_items() == []
+
+
+def test_native_nearest_neighbors_within_validates_radius(dtype):
+    native = DTYPE_MAP[dtype]((0, 0, 100, 100), 2)
+    for id_, xy in enumerate([(10, 10), (13, 14), (60, 60)]):
+        native.insert(id_, xy)
+
+    # Strictly closer than the radius, nearest first
+    assert [t[0] for t in native.nearest_neighbors_within((10, 10), 5, 6)] == [0, 1]
+    assert [t[0] for t in native.nearest_neighbors_within((10, 10), 5, 5)] == [0]
+
+    # 0 used to mean unbounded; it and negative radii now raise
+    for bad in (0, -1, -50):
+        with pytest.raises(ValueError, match="max_radius must be positive"):
+            native.nearest_neighbors_within((10, 10), 5, bad)
+    if dtype.startswith("f"):
+        with pytest.raises(ValueError, match="max_radius must be positive"):
+            native.nearest_neighbors_within((10, 10), 5, float("nan"))
@@ -63,3 +63,40 @@ def test_clear_resets_count_and_next_id(bounds, dtype):
     qt.clear()
     assert len(qt) == 0
     assert qt._next_id == 0
+
+
+def test_close_and_context_manager(bounds, dtype):
+    bounds_use = get_bounds_for_dtype(bounds, dtype)
+    with QuadTree(bounds_use, capacity=4, dtype=dtype) as qt:
+        assert qt.closed is False
+        qt.insert((1, 1) if dtype.startswith("i") else (1.0, 1.0))
+        assert len(qt) == 1
+    assert qt.closed is True
+
+    for call in (
+        lambda: len(qt),
+        lambda: qt.insert((1, 1) if dtype.startswith("i") else (1.0, 1.0)),
+        lambda: qt.query(bounds_use),
+        lambda: list(qt),
+        qt.clear,
+        qt.to_bytes,
+    ):
+        with pytest.raises(RuntimeError, match="QuadTree is closed"):
+            call()
+
+    qt.close()  # idempotent
+
+
+def test_iter_is_a_snapshot_under_mutation(bounds, dtype):
+    bounds_use = get_bounds_for_dtype(bounds, dtype)
+    qt = QuadTree(bounds_use, capacity=2, dtype=dtype)
+    ids = [qt.insert((i + 1, i + 1)) for i in range(10)]
+
+    seen = []
+    for id_, x, y in qt:
+        seen.append(id_)
+        # Deleting and inserting mid-loop must not skip, repeat or add items
+        assert qt.delete(id_, x, y) is True
+        qt.insert((x, y))
+    assert sorted(seen) == ids
+    assert len(qt) == 10
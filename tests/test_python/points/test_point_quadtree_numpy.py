@@ -44,3 +44,22 @@ def test_insert_many_np_empty_array(bounds, dtype):
     assert res.count == 0
     assert res.start_id == 0
     assert res.end_id == -1
+
+
+def test_query_np_normalizes_inverted_rect(bounds, dtype):
+    bounds_use = get_bounds_for_dtype(bounds, dtype)
+    qt = QuadTree(bounds_use, capacity=2, dtype=dtype)
+    qt.insert_many_np(make_np_coords(dtype, [(1.0, 1.0), (2.0, 3.0), (8.0, 8.0)]))
+
+    rect = (0, 0, 5, 5)
+    inverted = (5, 5, 0, 0)
+    ids, coords = qt.query_np(inverted)
+    want_ids, want_coords = qt.query_np(rect)
+    assert ids.tolist() == want_ids.tolist() == [0, 1]
+    assert np.array_equal(coords, want_coords)
+    assert qt._native.query_ids_np(inverted).tolist() == [0, 1]
+
+    with pytest.raises(ValueError, match="inverted"):
+        qt.query_np(inverted, strict=True)
+    with pytest.raises(ValueError, match="inverted"):
+        qt._native.query_ids_np(inverted, strict=True)
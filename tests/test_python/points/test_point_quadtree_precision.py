@@ -45,3 +45,28 @@ def test_f32_can_round_bbox_min_up_and_exclude_integer_point():
     qt32 = QuadTree(bbox32, 1, max_depth=8, dtype="f32")
     with pytest.raises(ValueError):
         qt32.insert((np.float32(x), np.float32(5.0)))  # should fail (x < min_x32)
+
+
+def test_f64_round_trips_large_coordinates_exactly():
+    # Web-Mercator-scale meters: f32 would round these to the nearest 0.5 m
+    bounds = (6_000_000.0, 4_000_000.0, 7_000_000.0, 5_000_000.0)
+    a = (6_378_137.123456, 4_500_000.000001)
+    b = (6_378_137.123457, 4_500_000.000002)
+
+    qt = QuadTree(bounds, 2, max_depth=40, dtype="f64")
+    id_a = qt.insert(a)
+    id_b = qt.insert(b)
+    qt.insert((6_900_000.5, 4_100_000.25))
+
+    got = sorted(qt.query((6_378_137.0, 4_499_999.0, 6_378_138.0, 4_500_001.0)))
+    assert got == [(id_a, *a), (id_b, *b)]
+    assert qt.nearest_neighbor(a) == (id_a, *a)
+    assert qt.nearest_neighbor(b) == (id_b, *b)
+
+    # The same values in an f32 tree collapse onto one position
+    qt32 = QuadTree(bounds, 2, max_depth=40, dtype="f32")
+    qt32.insert(a)
+    qt32.insert(b)
+    (_, x0, y0), (_, x1, y1) = qt32.query(bounds)
+    assert (x0, y0) == (x1, y1)
+    assert (x0, y0) != a
@@ -125,3 +125,70 @@ def test_contains_robust(bounds, dtype):
     for pt in edge_pts:
         qt.insert(pt)
         assert pt in qt, f"Edge point {pt} should be found in tree"
+
+
+def test_query_objects_returns_tuples_with_objects(bounds, dtype):
+    bounds_use = get_bounds_for_dtype(bounds, dtype)
+    qt = QuadTreeObjects(bounds_use, capacity=4, dtype=dtype)
+    p1 = (1, 1) if dtype.startswith("i") else (1.0, 1.0)
+    p2 = (2, 2) if dtype.startswith("i") else (2.0, 2.0)
+    payload = {"name": "a"}
+    rid1 = qt.insert(p1, obj=payload)
+    rid2 = qt.insert(p2)
+
+    rect = (0, 0, 5, 5) if dtype.startswith("i") else (0.0, 0.0, 5.0, 5.0)
+    rows = sorted(qt.query_objects(rect), key=lambda row: row[0])
+    assert rows == [(rid1, *p1, payload), (rid2, *p2, None)]
+    assert rows[0][3] is payload
+
+    empty = (50, 50, 60, 60) if dtype.startswith("i") else (50.0, 50.0, 60.0, 60.0)
+    assert qt.query_objects(empty) == []
+
+    qt.delete(rid1)
+    assert [row[0] for row in qt.query_objects(rect)] == [rid2]
+
+
+def test_iter_objects_yields_every_item_with_object(bounds, dtype):
+    bounds_use = get_bounds_for_dtype(bounds, dtype)
+    qt = QuadTreeObjects(bounds_use, capacity=4, dtype=dtype)
+    p1 = (1, 1) if dtype.startswith("i") else (1.0, 1.0)
+    p2 = (7, 3) if dtype.startswith("i") else (7.0, 3.0)
+    payload = ["x"]
+    rid1 = qt.insert(p1, obj=payload)
+    rid2 = qt.insert(p2)
+
+    rows = list(qt.iter_objects())
+    assert rows == [(rid1, *p1, payload), (rid2, *p2, None)]
+    assert rows[0][3] is payload
+
+    qt.delete(rid1)
+    assert list(qt.iter_objects()) == [(rid2, *p2, None)]
+
+
+def test_iter_objects_raises_on_call_when_closed(bounds, dtype):
+    bounds_use = get_bounds_for_dtype(bounds, dtype)
+    qt = QuadTreeObjects(bounds_use, capacity=4, dtype=dtype)
+    qt.insert((1, 1) if dtype.startswith("i") else (1.0, 1.0))
+    qt.close()
+
+    # The error comes from the call, before any iteration starts
+    with pytest.raises(RuntimeError, match="QuadTreeObjects is closed"):
+        qt.iter_objects()
+
+
+def test_queries_normalize_inverted_rect(bounds, dtype):
+    bounds_use = get_bounds_for_dtype(bounds, dtype)
+    qt = QuadTreeObjects(bounds_use, capacity=2, dtype=dtype)
+    rid1 = qt.insert((1, 1) if dtype.startswith("i") else (1.0, 1.0), obj="a")
+    rid2 = qt.insert((2, 3) if dtype.startswith("i") else (2.0, 3.0), obj="b")
+    qt.insert((8, 8) if dtype.startswith("i") else (8.0, 8.0), obj="c")
+
+    rect = (0, 0, 5, 5)
+    inverted = (5, 5, 0, 0)
+    assert sorted(it.id_ for it in qt.query(inverted)) == [rid1, rid2]
+    assert sorted(qt.query_ids(inverted)) == [rid1, rid2]
+    assert sorted(qt.query_objects(inverted)) == sorted(qt.query_objects(rect))
+
+    for method in (qt.query, qt.query_ids, qt.query_objects):
+        with pytest.raises(ValueError, match="inverted"):
+            method(inverted, strict=True)
@@ -1,3 +1,7 @@
+import gc
+import weakref
+
+import pytest
 from tests.test_python.conftest import get_bounds_for_dtype
 
 from fastquadtree.point_quadtree_objects import QuadTreeObjects
@@ -43,6 +47,25 @@ def test_delete_by_object_variants(bounds, dtype):
     assert qt.delete_by_object("missing") == 0
 
 
+def test_pop_by_object_returns_removed_ids(bounds, dtype):
+    bounds_use = get_bounds_for_dtype(bounds, dtype)
+    qt = QuadTreeObjects(bounds_use, capacity=6, dtype=dtype)
+    sprite = object()
+    ids = [
+        qt.insert((i, i) if dtype.startswith("i") else (float(i), float(i)), obj=sprite)
+        for i in range(1, 5)
+    ]
+    other = qt.insert((9, 9) if dtype.startswith("i") else (9.0, 9.0), obj="other")
+
+    assert qt.pop_one_by_object(sprite) == ids[0]
+    assert qt.pop_by_object(sprite) == ids[1:]
+    assert len(qt) == 1
+    assert qt.query_ids(bounds_use) == [other]
+
+    assert qt.pop_by_object(sprite) == []
+    assert qt.pop_one_by_object(sprite) is None
+
+
 def test_attach_replaces_object_and_update_paths(bounds, dtype):
     bounds_use = get_bounds_for_dtype(bounds, dtype)
     qt = QuadTreeObjects(bounds_use, capacity=4, dtype=dtype)
@@ -66,3 +89,22 @@ def test_attach_replaces_object_and_update_paths(bounds, dtype):
 
     missing_obj = object()
     assert qt.update_by_object(missing_obj, 4.0, 4.0) is False
+
+
+def test_close_releases_objects(bounds, dtype):
+    class Payload:
+        pass
+
+    bounds_use = get_bounds_for_dtype(bounds, dtype)
+    payload = Payload()
+    ref = weakref.ref(payload)
+    with QuadTreeObjects(bounds_use, capacity=4, dtype=dtype) as qt:
+        qt.insert((1, 1) if dtype.startswith("i") else (1.0, 1.0), obj=payload)
+        qt.insert((2, 2) if dtype.startswith("i") else (2.0, 2.0), obj=payload)
+    del payload
+    gc.collect()
+    assert ref() is None
+
+    for call in (lambda: len(qt), lambda: qt.get(0), lambda: list(qt), qt.get_all_objects):
+        with pytest.raises(RuntimeError, match="QuadTreeObjects is closed"):
+            call()
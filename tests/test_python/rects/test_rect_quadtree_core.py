@@ -1,3 +1,4 @@
+import pytest
 from tests.test_python.conftest import get_bounds_for_dtype
 
 from fastquadtree._common import Bounds
@@ -241,3 +242,21 @@ def test_contains_robust(bounds, dtype):
             62.0,
             62.0,
         ) not in rqt, "Non-matching overlapping rectangle should not be found"
+
+
+def test_query_normalizes_inverted_rect(bounds, dtype):
+    bounds_use = get_bounds_for_dtype(bounds, dtype)
+    rqt = RectQuadTree(bounds_use, capacity=2, dtype=dtype)
+    for r in [(1, 1, 2, 2), (3, 3, 4, 4), (8, 8, 9, 9)]:
+        rqt.insert(r if dtype.startswith("i") else tuple(map(float, r)))
+
+    rect = (0, 0, 5, 5)
+    inverted = (5, 5, 0, 0)
+    assert sorted(rqt.query(inverted)) == sorted(rqt.query(rect))
+    assert len(rqt.query(inverted)) == 2
+    assert sorted(rqt._native.query_ids(inverted)) == [0, 1]
+
+    with pytest.raises(ValueError, match="inverted"):
+        rqt.query(inverted, strict=True)
+    with pytest.raises(ValueError, match="inverted"):
+        rqt._native.query_ids(inverted, strict=True)
@@ -58,3 +58,23 @@ def test_insert_many_np_empty(bounds, dtype):
     assert res.count == 0
     assert res.start_id == 0
     assert res.end_id == -1
+
+
+def test_query_np_normalizes_inverted_rect(bounds, dtype):
+    bounds_use = get_bounds_for_dtype(bounds, dtype)
+    rqt = RectQuadTree(bounds_use, capacity=2, dtype=dtype)
+    for r in [(1.0, 1.0, 2.0, 2.0), (3.0, 3.0, 4.0, 4.0), (8.0, 8.0, 9.0, 9.0)]:
+        rqt.insert(rect_for_dtype(dtype, r))
+
+    rect = (0, 0, 5, 5)
+    inverted = (5, 5, 0, 0)
+    ids, rects = rqt.query_np(inverted)
+    want_ids, want_rects = rqt.query_np(rect)
+    assert sorted(ids.tolist()) == sorted(want_ids.tolist()) == [0, 1]
+    assert np.array_equal(rects, want_rects)
+    assert sorted(rqt._native.query_ids_np(inverted).tolist()) == [0, 1]
+
+    with pytest.raises(ValueError, match="inverted"):
+        rqt.query_np(inverted, strict=True)
+    with pytest.raises(ValueError, match="inverted"):
+        rqt._native.query_ids_np(inverted, strict=True)
@@ -200,3 +200,31 @@ def test_contains_robust(bounds, dtype):
             62.0,
             62.0,
         ) not in rqt, "Non-matching overlapping rectangle should not be found"
+
+
+def test_query_objects_returns_rect_tuples_with_objects(bounds, dtype):
+    bounds_use = get_bounds_for_dtype(bounds, dtype)
+    rqt = RectQuadTreeObjects(bounds_use, capacity=4, dtype=dtype)
+    rect1 = rect_for_dtype(dtype, (1.0, 1.0, 2.0, 2.0))
+    rid1 = rqt.insert(rect1, obj="a")
+
+    rows = rqt.query_objects(rect_for_dtype(dtype, (0.0, 0.0, 5.0, 5.0)))
+    assert rows == [(rid1, *rect1, "a")]
+
+
+def test_queries_normalize_inverted_rect(bounds, dtype):
+    bounds_use = get_bounds_for_dtype(bounds, dtype)
+    qt = RectQuadTreeObjects(bounds_use, capacity=2, dtype=dtype)
+    rid1 = qt.insert(rect_for_dtype(dtype, (1.0, 1.0, 2.0, 2.0)), obj="a")
+    rid2 = qt.insert(rect_for_dtype(dtype, (3.0, 3.0, 4.0, 4.0)), obj="b")
+    qt.insert(rect_for_dtype(dtype, (8.0, 8.0, 9.0, 9.0)), obj="c")
+
+    rect = (0, 0, 5, 5)
+    inverted = (5, 5, 0, 0)
+    assert sorted(it.id_ for it in qt.query(inverted)) == [rid1, rid2]
+    assert sorted(qt.query_ids(inverted)) == [rid1, rid2]
+    assert sorted(qt.query_objects(inverted)) == sorted(qt.query_objects(rect))
+
+    for method in (qt.query, qt.query_ids, qt.query_objects):
+        with pytest.raises(ValueError, match="inverted"):
+            method(inverted, strict=True)
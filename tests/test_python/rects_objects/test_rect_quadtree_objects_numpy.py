@@ -72,3 +72,17 @@ def test_insert_many_np_empty(bounds, dtype):
     assert res.count == 0
     assert res.start_id == 0
     assert res.end_id == -1
+
+
+def test_query_np_normalizes_inverted_rect(bounds, dtype):
+    bounds_use = get_bounds_for_dtype(bounds, dtype)
+    qt = RectQuadTreeObjects(bounds_use, capacity=2, dtype=dtype)
+    rects = [(1.0, 1.0, 2.0, 2.0), (3.0, 3.0, 4.0, 4.0), (8.0, 8.0, 9.0, 9.0)]
+    qt.insert_many_np(make_np_coords(dtype, rects))
+
+    ids, geoms = qt.query_np((5, 5, 0, 0))
+    want_ids, want_geoms = qt.query_np((0, 0, 5, 5))
+    assert sorted(ids.tolist()) == sorted(want_ids.tolist()) == [0, 1]
+    assert np.array_equal(geoms, want_geoms)
+    with pytest.raises(ValueError, match="inverted"):
+        qt.query_np((5, 5, 0, 0), strict=True)
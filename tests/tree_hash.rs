@@ -0,0 +1,83 @@
+use fastquadtree::{Item, Point, QuadTree, Rect};
+
+fn r(x0: f32, y0: f32, x1: f32, y1: f32) -> Rect<f32> {
+    Rect { min_x: x0, min_y: y0, max_x: x1, max_y: y1 }
+}
+fn item(id: u64, x: f32, y: f32) -> Item<f32> {
+    Item { id, point: Point { x, y } }
+}
+
+fn sample_items() -> Vec<Item<f32>> {
+    (0..40)
+        .map(|i| item(i, (i * 7 % 97) as f32 + 0.5, (i * 13 % 89) as f32 + 0.25))
+        .collect()
+}
+
+fn build(capacity: usize, items: &[Item<f32>]) -> QuadTree<f32> {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), capacity, 8);
+    for it in items {
+        assert!(qt.insert(*it));
+    }
+    qt
+}
+
+#[test]
+fn identical_builds_hash_equally() {
+    let items = sample_items();
+    let a = build(4, &items);
+    let b = build(4, &items);
+    assert_eq!(a.content_hash(), b.content_hash());
+    assert_eq!(a.structural_hash(), b.structural_hash());
+}
+
+#[test]
+fn restructuring_changes_structural_hash_only() {
+    let items = sample_items();
+    let shallow = build(64, &items);
+    let deep = build(1, &items);
+    assert_eq!(shallow.content_hash(), deep.content_hash());
+    assert_ne!(shallow.structural_hash(), deep.structural_hash());
+
+    // Insertion order is irrelevant to content
+    let mut reversed = items.clone();
+    reversed.reverse();
+    assert_eq!(build(4, &reversed).content_hash(), shallow.content_hash());
+}
+
+#[test]
+fn content_hash_tracks_items() {
+    let items = sample_items();
+    let a = build(4, &items);
+    let mut b = build(4, &items);
+    assert!(b.delete(3, items[3].point));
+    assert_ne!(a.content_hash(), b.content_hash());
+    assert!(b.insert(item(3, items[3].point.x + 0.5, items[3].point.y)));
+    assert_ne!(a.content_hash(), b.content_hash());
+}
+
+#[test]
+fn content_eq_ignores_insert_order_and_structure() {
+    let items = sample_items();
+    let a = build(4, &items);
+    let mut reversed = items.clone();
+    reversed.reverse();
+    let b = build(1, &reversed);
+    assert!(a.content_eq(&b));
+    assert!(b.content_eq(&a));
+}
+
+#[test]
+fn content_eq_detects_single_item_difference() {
+    let items = sample_items();
+    let a = build(4, &items);
+
+    let mut moved = items.clone();
+    moved[5].point.x += 1.0;
+    assert!(!a.content_eq(&build(4, &moved)));
+
+    let mut renamed = items.clone();
+    renamed[5].id = 1000;
+    assert!(!a.content_eq(&build(4, &renamed)));
+
+    assert!(!a.content_eq(&build(4, &items[1..])));
+}
@@ -0,0 +1,231 @@
+use fastquadtree::{Item, Point, QuadTree, Rect};
+
+fn r(x0: f32, y0: f32, x1: f32, y1: f32) -> Rect<f32> {
+    Rect { min_x: x0, min_y: y0, max_x: x1, max_y: y1 }
+}
+fn pt(x: f32, y: f32) -> Point<f32> { Point { x, y } }
+
+#[test]
+fn uniform_grid_is_well_formed() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 8, 16);
+    let mut id = 0;
+    for x in 0..16 {
+        for y in 0..16 {
+            assert!(qt.insert(Item { id, point: pt(x as f32 * 6.25 + 1.0, y as f32 * 6.25 + 1.0) }));
+            id += 1;
+        }
+    }
+    assert!(qt.is_well_formed(4, 2.0));
+}
+
+#[test]
+fn clustered_chain_is_not_well_formed() {
+    // A tight cluster with capacity 1 drives one branch deep and leaves
+    // mostly empty siblings behind
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 1, 16);
+    for id in 0..8u64 {
+        let d = id as f32 * 0.001;
+        assert!(qt.insert(Item { id, point: pt(10.0 + d, 10.0 + d) }));
+    }
+    let stats = qt.stats();
+    assert!(stats.max_depth_reached > 4);
+    assert!(stats.mean_leaf_occupancy() < 1.0);
+    assert!(!qt.is_well_formed(4, 1.0));
+}
+
+#[test]
+fn leaf_counts_sum_to_item_count() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 64.0, 64.0), 3, 10);
+    let mut id = 0u64;
+    for i in 0..20 {
+        for j in 0..7 {
+            assert!(qt.insert(Item { id, point: pt(i as f32 * 3.1 + 0.5, j as f32 * 0.7 + 0.25) }));
+            id += 1;
+        }
+    }
+
+    let (rects, counts) = qt.leaf_counts();
+    assert_eq!(rects.len(), counts.len());
+    assert_eq!(rects.len(), qt.stats().leaf_count);
+    assert_eq!(counts.iter().sum::<usize>(), qt.count_items());
+}
+
+#[test]
+fn depth_diagnostics() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 64, 8);
+    assert_eq!(qt.max_depth_reached(), 0);
+    assert_eq!(qt.node_count(), 1);
+    assert_eq!(qt.items_per_depth(), vec![0]);
+
+    qt.pre_subdivide(2);
+    for id in 0..40u64 {
+        assert!(qt.insert(Item { id, point: pt(id as f32 * 2.3 + 1.0, 50.0 - id as f32) }));
+    }
+    assert_eq!(qt.max_depth_reached(), 2);
+    assert_eq!(qt.node_count(), 21);
+    assert_eq!(qt.items_per_depth(), vec![0, 0, 40]);
+
+    // A tight cluster piles into one deep branch
+    let mut clustered = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 1, 16);
+    for id in 0..8u64 {
+        let d = id as f32 * 0.001;
+        assert!(clustered.insert(Item { id, point: pt(10.0 + d, 10.0 + d) }));
+    }
+    let per_depth = clustered.items_per_depth();
+    assert_eq!(per_depth.len(), clustered.max_depth_reached() + 1);
+    assert_eq!(per_depth.iter().sum::<usize>(), 8);
+    assert!(per_depth[..4].iter().all(|&n| n == 0));
+}
+
+#[test]
+fn memory_bytes_tracks_nodes_and_item_capacity() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 4, 8);
+    let item_size = std::mem::size_of::<Item<f32>>();
+    let empty = qt.memory_bytes();
+    // Root struct plus its preallocated item buffer
+    assert_eq!(empty, std::mem::size_of::<QuadTree<f32>>() + 4 * item_size);
+
+    for id in 0..4u64 {
+        qt.insert(Item { id, point: pt(id as f32 + 0.5, 0.5) });
+    }
+    assert_eq!(qt.memory_bytes(), empty);
+
+    // Splitting allocates four children, each with its own buffer
+    qt.insert(Item { id: 4, point: pt(90.0, 90.0) });
+    assert!(qt.node_count() > 1);
+    assert!(qt.memory_bytes() >= empty + 4 * std::mem::size_of::<QuadTree<f32>>() + 16 * item_size);
+
+    qt.clear();
+    assert_eq!(qt.memory_bytes(), empty);
+}
+
+#[test]
+fn get_nodes_reports_depth_counts_and_leaves() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 2, 8);
+    for (id, (x, y)) in [(10.0, 10.0), (20.0, 20.0), (30.0, 30.0), (80.0, 80.0), (5.0, 5.0)].into_iter().enumerate() {
+        qt.insert(Item { id: id as u64, point: pt(x, y) });
+    }
+
+    let nodes = qt.get_nodes();
+    let rects: Vec<Rect<f32>> = nodes.iter().map(|n| n.rect).collect();
+    assert_eq!(rects, qt.get_all_node_boundaries());
+    assert_eq!(nodes.len(), qt.node_count());
+
+    assert_eq!(nodes[0].rect, r(0.0, 0.0, 100.0, 100.0));
+    assert_eq!(nodes[0].depth, 0);
+    assert!(!nodes[0].is_leaf);
+    assert_eq!(nodes[0].item_count, 0);
+
+    assert_eq!(nodes.iter().map(|n| n.item_count).sum::<usize>(), 5);
+    assert!(nodes.iter().filter(|n| !n.is_leaf).all(|n| n.item_count == 0));
+    assert_eq!(nodes.iter().map(|n| n.depth).max(), Some(qt.max_depth_reached()));
+    let (_, leaf_counts) = qt.leaf_counts();
+    assert_eq!(nodes.iter().filter(|n| n.is_leaf).count(), leaf_counts.len());
+}
+
+#[test]
+fn to_json_dumps_nested_nodes() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 8.0, 8.0), 1, 4);
+    assert_eq!(qt.to_json(), r#"{"rect":[0,0,8,8],"items":[],"children":[]}"#);
+
+    assert!(qt.insert(Item { id: 1, point: pt(1.0, 1.0) }));
+    assert!(qt.insert(Item { id: 2, point: pt(6.5, 2.0) }));
+    assert_eq!(
+        qt.to_json(),
+        concat!(
+            r#"{"rect":[0,0,8,8],"items":[],"children":["#,
+            r#"{"rect":[0,0,4,4],"items":[{"id":1,"x":1,"y":1}],"children":[]},"#,
+            r#"{"rect":[4,0,8,4],"items":[{"id":2,"x":6.5,"y":2}],"children":[]},"#,
+            r#"{"rect":[0,4,4,8],"items":[],"children":[]},"#,
+            r#"{"rect":[4,4,8,8],"items":[],"children":[]}"#,
+            "]}"
+        )
+    );
+
+    let mut qi = QuadTree::new(Rect { min_x: -5i64, min_y: 0, max_x: 5, max_y: 10 }, 4, 4);
+    assert!(qi.insert(Item { id: 7, point: Point { x: -3, y: 9 } }));
+    assert_eq!(qi.to_json(), r#"{"rect":[-5,0,5,10],"items":[{"id":7,"x":-3,"y":9}],"children":[]}"#);
+}
+
+#[test]
+fn getters_report_construction_settings() {
+    let mut qt = QuadTree::new_expandable(r(0.0, 0.0, 10.0, 10.0), 3, 6);
+    assert_eq!(qt.capacity(), 3);
+    assert_eq!(qt.max_depth(), 6);
+    assert_eq!(qt.bounds(), r(0.0, 0.0, 10.0, 10.0));
+
+    // Growth widens the bounds and the depth budget
+    assert!(qt.insert(Item { id: 1, point: pt(15.0, 5.0) }));
+    assert_eq!(qt.bounds(), r(0.0, 0.0, 20.0, 20.0));
+    assert_eq!(qt.max_depth(), 7);
+    assert_eq!(qt.capacity(), 3);
+}
+
+#[test]
+fn density_grid_matches_per_cell_counts() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 90.0, 60.0), 3, 10);
+    let mut seed = 5u64;
+    let mut pts = Vec::new();
+    for id in 0..500u64 {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        let x = ((seed >> 40) % 900) as f32 / 10.0;
+        let y = ((seed >> 20) % 600) as f32 / 10.0;
+        assert!(qt.insert(Item { id, point: pt(x, y) }));
+        pts.push(pt(x, y));
+    }
+
+    for (rows, cols) in [(1, 1), (2, 4), (3, 3), (6, 9), (7, 5)] {
+        let grid = qt.density_grid(rows, cols);
+        assert_eq!(grid.len(), rows * cols);
+        let (cw, ch) = (90.0 / cols as f32, 60.0 / rows as f32);
+        for row in 0..rows {
+            for col in 0..cols {
+                let cell = r(col as f32 * cw, row as f32 * ch, (col + 1) as f32 * cw, (row + 1) as f32 * ch);
+                assert_eq!(grid[row * cols + col] as usize, qt.count_in_rect(cell), "cell ({row}, {col})");
+            }
+        }
+        assert_eq!(grid.iter().sum::<u32>(), 500);
+    }
+    assert!(qt.density_grid(0, 4).is_empty());
+}
+
+#[test]
+fn coord_type_sets_item_size_and_precision() {
+    use std::mem::size_of;
+    assert!(size_of::<Item<f32>>() < size_of::<Item<f64>>());
+
+    // Two points one unit apart at 1e8 collapse in f32 but not in f64
+    let far = 100_000_000.0;
+    let b64 = Rect { min_x: 0.0f64, min_y: 0.0, max_x: 2.0 * far, max_y: 1.0 };
+    let mut q64 = QuadTree::new(b64, 4, 8);
+    assert!(q64.insert(Item { id: 1, point: Point { x: far, y: 0.5 } }));
+    assert!(q64.insert(Item { id: 2, point: Point { x: far + 1.0, y: 0.5 } }));
+    assert_eq!(q64.items_at(Point { x: far, y: 0.5 }).len(), 1);
+
+    let b32 = Rect { min_x: 0.0f32, min_y: 0.0, max_x: 2.0 * far as f32, max_y: 1.0 };
+    let mut q32 = QuadTree::new(b32, 4, 8);
+    assert!(q32.insert(Item { id: 1, point: Point { x: far as f32, y: 0.5 } }));
+    assert!(q32.insert(Item { id: 2, point: Point { x: far as f32 + 1.0, y: 0.5 } }));
+    assert_eq!(q32.items_at(Point { x: far as f32, y: 0.5 }).len(), 2);
+}
+
+#[test]
+fn is_empty_tracks_inserts_and_deletes_across_splits() {
+    let mut qt = QuadTree::new(r(0.0, 0.0, 100.0, 100.0), 1, 8);
+    assert!(qt.is_empty());
+    for id in 0..5u64 {
+        qt.insert(Item { id, point: pt(id as f32 * 20.0, 10.0) });
+    }
+    assert!(!qt.is_empty());
+    for id in 0..4u64 {
+        qt.delete(id, pt(id as f32 * 20.0, 10.0));
+        assert!(!qt.is_empty());
+    }
+    qt.delete(4, pt(80.0, 10.0));
+    assert!(qt.is_empty());
+
+    // Pre-made empty leaves don't count as items
+    qt.pre_subdivide(2);
+    assert!(qt.is_empty());
+    assert_eq!(qt.is_empty(), qt.count_items() == 0);
+}
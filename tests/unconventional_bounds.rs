@@ -1,4 +1,4 @@
-use fastquadtree::{Point, Rect, Item, QuadTree};
+use fastquadtree::{ConfigError, InsertError, Point, QuadTreeConfig, Rect, Item, QuadTree};
 
 fn r(x0: f32, y0: f32, x1: f32, y1: f32) -> Rect<f32> {
     Rect { min_x: x0, min_y: y0, max_x: x1, max_y: y1 }
@@ -263,4 +263,200 @@ fn out_of_bounds_insertion_negative() {
     
     // Verify only the valid insertions succeeded
     assert_eq!(qt.count_items(), 2);
-}
\ No newline at end of file
+}
+#[test]
+fn f64_tree_keeps_full_precision_for_large_coordinates() {
+    // Web-Mercator-scale meters where f32 would round to the nearest 0.5 m
+    let bounds = Rect { min_x: 6_000_000.0f64, min_y: 4_000_000.0, max_x: 7_000_000.0, max_y: 5_000_000.0 };
+    let mut qt = QuadTree::new(bounds, 2, 40);
+    let a = Point { x: 6_378_137.123456, y: 4_500_000.000001 };
+    let b = Point { x: 6_378_137.123457, y: 4_500_000.000002 };
+    assert!(qt.insert(Item { id: 1, point: a }));
+    assert!(qt.insert(Item { id: 2, point: b }));
+    assert!(qt.insert(Item { id: 3, point: Point { x: 6_900_000.5, y: 4_100_000.25 } }));
+
+    // Values come back bit-for-bit and nearby points stay distinguishable
+    let hits = qt.query(Rect { min_x: 6_378_137.0, min_y: 4_499_999.0, max_x: 6_378_138.0, max_y: 4_500_001.0 });
+    let mut hits = hits;
+    hits.sort_by_key(|h| h.0);
+    assert_eq!(hits, vec![(1, a.x, a.y), (2, b.x, b.y)]);
+    assert_eq!(qt.nearest_neighbor(a).unwrap().id, 1);
+    assert_eq!(qt.nearest_neighbor(b).unwrap().id, 2);
+    assert_eq!(qt.nearest_neighbor(b).unwrap().point, b);
+}
+
+#[test]
+fn non_finite_points_are_rejected_and_tree_stays_consistent() {
+    let mut qt = QuadTree::new(Rect { min_x: -100.0f64, min_y: -100.0, max_x: 100.0, max_y: 100.0 }, 2, 10);
+    for id in 0..20u64 {
+        assert!(qt.insert(Item { id, point: Point { x: id as f64 * 7.0 - 70.0, y: 5.0 } }));
+    }
+
+    let bad = [
+        Point { x: f64::NAN, y: 0.0 },
+        Point { x: 0.0, y: f64::NAN },
+        Point { x: f64::INFINITY, y: 0.0 },
+        Point { x: 0.0, y: f64::NEG_INFINITY },
+    ];
+    for (i, p) in bad.into_iter().enumerate() {
+        assert!(!p.is_finite());
+        assert!(!qt.insert(Item { id: 100 + i as u64, point: p }));
+        assert_eq!(qt.try_insert(Item { id: 100 + i as u64, point: p }), Err(InsertError::NonFiniteCoordinate));
+    }
+    assert_eq!(qt.try_insert(Item { id: 200, point: Point { x: 500.0, y: 0.0 } }), Ok(false));
+    assert_eq!(qt.try_insert(Item { id: 201, point: Point { x: 1.0, y: 1.0 } }), Ok(true));
+
+    assert_eq!(qt.count_items(), 21);
+    assert_eq!(qt.query(qt.boundary).len(), 21);
+    assert_eq!(qt.nearest_neighbor(Point { x: 1.0, y: 1.0 }).map(|it| it.id), Some(201));
+
+    // Infinite bounds contain -inf, so only the explicit check keeps it out
+    let mut open = QuadTree::new(Rect { min_x: f64::NEG_INFINITY, min_y: f64::NEG_INFINITY, max_x: 0.0, max_y: 0.0 }, 2, 4);
+    assert!(!open.insert(Item { id: 1, point: Point { x: f64::NEG_INFINITY, y: -1.0 } }));
+    assert_eq!(open.count_items(), 0);
+    assert!(Point { x: 1i32, y: -1 }.is_finite());
+}
+
+#[test]
+fn degenerate_bounds_are_rejected_by_new_with_config() {
+    let config = QuadTreeConfig { capacity: 4, split_threshold: 4, max_depth: 8, merge_threshold: 3 };
+    let bad = [
+        r(5.0, 0.0, 5.0, 10.0),  // zero width
+        r(0.0, 5.0, 10.0, 5.0),  // zero height
+        r(10.0, 0.0, 0.0, 10.0), // inverted x
+        r(0.0, 10.0, 10.0, 0.0), // inverted y
+        r(f32::NAN, 0.0, 10.0, 10.0),
+    ];
+    for b in bad {
+        assert_eq!(QuadTree::new_with_config(b, config).err(), Some(ConfigError::InvalidBoundary));
+    }
+    assert!(QuadTree::new_with_config(r(0.0, 0.0, 1.0, 1.0), config).is_ok());
+}
+
+#[test]
+#[should_panic(expected = "invalid QuadTree boundary")]
+fn zero_width_bounds_panic_in_new() {
+    QuadTree::<f32>::new(r(5.0, 0.0, 5.0, 10.0), 4, 8);
+}
+
+#[test]
+#[should_panic(expected = "invalid QuadTree boundary")]
+fn zero_height_bounds_panic_in_new() {
+    QuadTree::<f32>::new(r(0.0, 5.0, 10.0, 5.0), 4, 8);
+}
+
+#[test]
+#[should_panic(expected = "invalid QuadTree boundary")]
+fn inverted_bounds_panic_in_new() {
+    QuadTree::<f32>::new(r(10.0, 10.0, 0.0, 0.0), 4, 8);
+}
+
+#[test]
+fn large_negatively_offset_bounds_route_extremes_correctly() {
+    // +-1e7 m world shifted off-center, near f32's 1 m resolution at that scale
+    let bounds = r(-1.5e7, -1.2e7, 0.5e7, 0.8e7);
+    let mut qt = QuadTree::new(bounds, 2, 24);
+    let corners = [
+        (1, -1.5e7, -1.2e7),
+        (2, 0.5e7 - 1.0, -1.2e7),
+        (3, -1.5e7, 0.8e7 - 1.0),
+        (4, 0.5e7 - 1.0, 0.8e7 - 1.0),
+        (5, -0.5e7, -0.2e7), // exactly on the root split
+        (6, -0.5e7 - 1.0, -0.2e7 - 1.0),
+    ];
+    for &(id, x, y) in &corners {
+        assert!(qt.insert(item(id, x, y)), "id {id} rejected");
+    }
+    assert!(!qt.insert(item(9, 0.5e7, 0.0)));
+    assert!(!qt.insert(item(9, 0.0, 0.8e7)));
+
+    assert_eq!(ids(&qt.query(bounds)), vec![1, 2, 3, 4, 5, 6]);
+    for &(id, x, y) in &corners {
+        assert!(qt.contains(id, pt(x, y)), "id {id} misrouted");
+        assert_eq!(qt.nearest_neighbor(pt(x, y)).unwrap().id, id);
+        assert_eq!(ids(&qt.query(r(x, y, x + 1.0, y + 1.0))), vec![id]);
+    }
+    // The two points straddling the split land in opposite quadrants
+    assert_eq!(ids(&qt.query(r(-0.5e7, -0.2e7, 0.5e7, 0.8e7))), vec![4, 5]);
+
+    for &(id, x, y) in &corners {
+        assert!(qt.delete(id, pt(x, y)));
+    }
+    assert_eq!(qt.count_items(), 0);
+}
+
+#[test]
+fn large_f64_and_i64_bounds_split_without_overflow() {
+    let mut qf = QuadTree::new(Rect { min_x: -1e7f64, min_y: -1e7, max_x: 1e7, max_y: 1e7 }, 1, 60);
+    let big = 10_000_000i64;
+    let mut qi = QuadTree::new(Rect { min_x: -big, min_y: -big, max_x: big, max_y: big }, 1, 60);
+    for id in 0..64u64 {
+        // Points crowd both extreme corners so splits run deep on each side
+        let off = id as f64 * 0.25;
+        let f = if id % 2 == 0 { -1e7 + off } else { 1e7 - 1.0 - off };
+        assert!(qf.insert(Item { id, point: Point { x: f, y: f } }));
+        let k = id as i64;
+        let i = if id % 2 == 0 { -big + k } else { big - 1 - k };
+        assert!(qi.insert(Item { id, point: Point { x: i, y: i } }));
+    }
+    assert_eq!(qf.count_items(), 64);
+    assert_eq!(qi.count_items(), 64);
+    for it in qf.iter() {
+        assert!(qf.contains(it.id, it.point));
+    }
+    for it in qi.iter() {
+        assert!(qi.contains(it.id, it.point));
+    }
+    let low = qi.query(Rect { min_x: -big, min_y: -big, max_x: 0, max_y: 0 });
+    assert_eq!(low.len(), 32);
+    assert!(low.iter().all(|h| h.0 % 2 == 0));
+}
+
+// A coordinate type with only the arithmetic `Coord` asks for: no casts
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+struct Fixed(i32);
+
+impl std::ops::Add for Fixed {
+    type Output = Fixed;
+    fn add(self, o: Fixed) -> Fixed { Fixed(self.0 + o.0) }
+}
+impl std::ops::Sub for Fixed {
+    type Output = Fixed;
+    fn sub(self, o: Fixed) -> Fixed { Fixed(self.0 - o.0) }
+}
+impl std::ops::Mul for Fixed {
+    type Output = Fixed;
+    fn mul(self, o: Fixed) -> Fixed { Fixed(self.0 * o.0) }
+}
+impl std::ops::Div for Fixed {
+    type Output = Fixed;
+    fn div(self, o: Fixed) -> Fixed { Fixed(self.0 / o.0) }
+}
+impl num_traits::Zero for Fixed {
+    fn zero() -> Fixed { Fixed(0) }
+    fn is_zero(&self) -> bool { self.0 == 0 }
+}
+impl num_traits::One for Fixed {
+    fn one() -> Fixed { Fixed(1) }
+}
+
+#[test]
+fn coord_types_without_casts_insert_query_and_delete() {
+    let f = |x: i32, y: i32| Point { x: Fixed(x), y: Fixed(y) };
+    let bounds = Rect { min_x: Fixed(0), min_y: Fixed(0), max_x: Fixed(64), max_y: Fixed(64) };
+    let mut qt = QuadTree::new(bounds, 2, 8);
+    for (i, (x, y)) in [(1, 1), (5, 9), (40, 40), (63, 2), (10, 50)].into_iter().enumerate() {
+        assert!(qt.insert(Item { id: i as u64, point: f(x, y) }));
+    }
+    assert!(!qt.insert(Item { id: 9, point: f(64, 0) }));
+    assert!(qt.try_insert(Item { id: 9, point: f(64, 0) }) == Ok(false));
+
+    let range = Rect { min_x: Fixed(0), min_y: Fixed(0), max_x: Fixed(32), max_y: Fixed(32) };
+    let mut found: Vec<u64> = qt.query(range).iter().map(|it| it.0).collect();
+    found.sort_unstable();
+    assert_eq!(found, vec![0, 1]);
+    assert_eq!(qt.nearest_neighbor(f(39, 41)).map(|it| it.id), Some(2));
+    assert!(qt.delete(2, f(40, 40)));
+    assert_eq!(qt.count_items(), 4);
+    assert!(!qt.is_expandable());
+}